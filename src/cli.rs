@@ -0,0 +1,385 @@
+//! Command line interface
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::{Error, Result, anyhow};
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::{
+    data::{GameId, Iso8601Date, LIST_FILENAME, Meta, store::MetaStoreLocation},
+    plot::{
+        EpisodeLabels, Heading, Locale, Output, PlotSize, Preset, SmoothingParams, SummarySegment,
+    },
+};
+
+/// The Bonus Points List Visualizer
+#[derive(Debug, Parser)]
+#[allow(clippy::struct_excessive_bools)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Format of the log output
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Path or glob pattern of the list files, whose lists are merged
+    #[arg(long, default_value = LIST_FILENAME, value_name = "PATTERN")]
+    pub lists: String,
+
+    /// Where downloaded metadata is kept, as json:<PATH> or sqlite:<PATH>
+    #[arg(long, default_value_t, value_name = "STORE")]
+    pub meta_store: MetaStoreLocation,
+
+    /// Path or glob pattern of other list files, whose latest list is compared to the latest list
+    #[arg(long, value_name = "PATTERN")]
+    pub compare_lists: Option<String>,
+
+    /// Only use cached metadata and images, failing instead of making network requests
+    #[arg(long)]
+    pub skip_fetch: bool,
+
+    /// Download every image of every game before generating the visualizations, instead of while
+    /// drawing them
+    #[arg(long, conflicts_with = "skip_fetch")]
+    pub preload: bool,
+
+    /// downloaded, which only `sqlite:` stores record
+    /// downloaded, which only stores in a database record
+    #[arg(long, conflicts_with = "skip_fetch")]
+    pub refresh_metas: bool,
+
+    /// Number of games requested from IGDB at once
+    #[arg(long, default_value_t = 500)]
+    pub igdb_batch_size: usize,
+
+    /// Maximum number of images downloaded at once
+    #[arg(long, default_value_t = 8)]
+    pub download_jobs: usize,
+
+    /// Minimum time between the starts of consecutive image downloads, in milliseconds
+    #[arg(long, value_name = "MS")]
+    pub download_interval_ms: Option<u64>,
+
+    /// Minimum number of votes for a game to be included in IGDB rankings
+    #[arg(long, default_value_t = 0, value_name = "VOTES")]
+    pub min_rating_count: u32,
+
+    /// Dimensions of the generated visualizations, instead of each one's default
+    #[arg(long, value_name = "WxH")]
+    pub size: Option<PlotSize>,
+
+    /// Named dimensions, margins and font sizes of the generated visualizations (twitter, og,
+    /// square or print)
+    #[arg(long, value_name = "NAME", conflicts_with = "size")]
+    pub preset: Option<Preset>,
+
+    /// Only generate these visualizations, instead of all of them
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "PLOTS")]
+    pub plots: Vec<PlotKind>,
+
+    /// Generate a card listing the full ranking on a date (YYYY-MM-DD or "latest")
+    #[arg(long, value_name = "DATE")]
+    pub list_card: Option<ListDate>,
+
+    /// Generate the list over time with the given game's line highlighted
+    #[arg(long, value_name = "ID")]
+    pub highlight: Option<GameId>,
+
+    /// Label the episodes of the list over time with the dates of their lists, above their numbers
+    #[arg(long)]
+    pub dated_labels: bool,
+
+    /// Approximate number of episodes labelled in the list over time, instead of every tenth
+    #[arg(long, value_name = "COUNT", value_parser = clap::value_parser!(u32).range(1..))]
+    pub episode_ticks: Option<u32>,
+
+    /// Angle of the episode numbers of the list over time, counterclockwise from horizontal
+    #[arg(long, default_value_t = 0, value_name = "DEGREES", value_parser = clap::value_parser!(u32).range(0..=90))]
+    pub episode_label_angle: u32,
+
+    /// Also write the latest list and its statistics as Markdown into out/report.md
+    #[arg(long)]
+    pub markdown_report: bool,
+
+    /// Also write the latest list and its statistics as a self-contained HTML page into
+    /// out/report.html
+    #[arg(long)]
+    pub html_report: bool,
+
+    /// Ring the first and last games of every list in the list over time
+    #[arg(long)]
+    pub mark_extremes: bool,
+
+    /// Extend the list over time past the latest episode with each game's predicted position on
+    /// the next list
+    #[arg(long)]
+    pub predict: bool,
+
+    /// Draw cover thumbnails beside the names of games in the list over time and ranking
+    /// differences
+    #[arg(long)]
+    pub cover_labels: bool,
+
+    /// Color the ranking differences with a distinct color for every game, instead of by the
+    /// direction and size of the difference
+    #[arg(long)]
+    pub distinct_difference_colors: bool,
+
+    /// Width of the lines and markers of the list over time and ranking differences, in pixels
+    /// at their default sizes
+    #[arg(long, default_value_t = 2, value_name = "PX", value_parser = clap::value_parser!(u32).range(1..))]
+    pub stroke_width: u32,
+
+    /// Render bar chart race frames into out/race, with the given number of frames per list change
+    #[arg(long, value_name = "FRAMES")]
+    pub race_frames: Option<usize>,
+
+    /// Standard deviation of the release date density kernel, in days
+    #[arg(long, value_name = "DAYS")]
+    pub release_sigma: Option<f64>,
+
+    /// Width of the release date density buckets, in days
+    #[arg(long, value_name = "DAYS", value_parser = clap::value_parser!(u32).range(1..))]
+    pub release_bucket_days: Option<u32>,
+
+    /// Segments of the summary, in order, each optionally followed by its number of items
+    /// (e.g. list-toppers:3,overrated,genres)
+    #[arg(long, value_delimiter = ',', value_name = "SEGMENTS")]
+    pub summary_segments: Vec<SummarySegment>,
+
+    /// How much each game contributes to the release date density
+    #[arg(long, value_enum, default_value_t = ReleaseWeight::Count)]
+    pub release_weight: ReleaseWeight,
+
+    /// Write the PNG of the one visualization selected with --plots to standard output, instead
+    /// of writing visualizations into out/
+    #[arg(long, requires = "plots")]
+    pub stdout: bool,
+
+    /// Title drawn above the visualizations of a kind (e.g. coop="Playing Together"), may be
+    /// repeated for different kinds
+    #[arg(long = "title", value_name = "PLOT=TEXT")]
+    pub titles: Vec<PlotText>,
+
+    /// Subtitle drawn above the visualizations of a kind, below its title if any
+    #[arg(long = "subtitle", value_name = "PLOT=TEXT")]
+    pub subtitles: Vec<PlotText>,
+
+    /// JSON file translating the text drawn on the visualizations, instead of English
+    #[arg(long, value_name = "PATH")]
+    pub locale: Option<PathBuf>,
+}
+
+impl Cli {
+    /// Size of the visualizations, if not their defaults
+    pub fn plot_size(&self) -> Option<PlotSize> {
+        self.size.or_else(|| self.preset.map(|preset| preset.size))
+    }
+
+    /// Density estimation parameters of the release dates visualization
+    pub fn release_smoothing(&self) -> SmoothingParams {
+        SmoothingParams {
+            bucket_width: self
+                .release_bucket_days
+                .map(|days| Duration::from_hours(24) * days),
+            sigma_days: self.release_sigma,
+        }
+    }
+
+    /// Labelling of the episodes of the list over time
+    pub fn episode_labels(&self) -> EpisodeLabels {
+        EpisodeLabels {
+            count: self.episode_ticks.map(|count| count as usize),
+            angle: f64::from(self.episode_label_angle).to_radians(),
+            dated: self.dated_labels,
+        }
+    }
+
+    /// Locale loaded from `--locale`, or the English default
+    pub fn locale(&self) -> Result<Locale> {
+        self.locale
+            .as_deref()
+            .map_or_else(|| Ok(Locale::default()), Locale::load)
+    }
+
+    /// Whether the visualizations of `kind` are to be generated
+    pub fn selected(&self, kind: PlotKind) -> bool {
+        self.plots.is_empty() || self.plots.contains(&kind)
+    }
+
+    /// Headings set with `--title` and `--subtitle`, the last one given for a kind taking
+    /// precedence
+    pub fn headings(&self) -> Headings {
+        let mut headings = HashMap::<PlotKind, Heading>::new();
+        for title in &self.titles {
+            headings.entry(title.kind).or_default().title = Some(title.text.clone());
+        }
+        for subtitle in &self.subtitles {
+            headings.entry(subtitle.kind).or_default().subtitle = Some(subtitle.text.clone());
+        }
+        Headings(headings)
+    }
+}
+
+/// Text given for the visualizations of one kind
+#[derive(Debug, Clone)]
+pub struct PlotText {
+    pub kind: PlotKind,
+    pub text: String,
+}
+
+impl FromStr for PlotText {
+    type Err = Error;
+
+    /// Parses `<PLOT>=<TEXT>`
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, text) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("\"{s}\" is not of the form <PLOT>=<TEXT>"))?;
+        Ok(Self {
+            kind: PlotKind::from_str(kind, true).map_err(|err| anyhow!(err))?,
+            text: text.to_string(),
+        })
+    }
+}
+
+/// Headings of the visualizations of each kind
+#[derive(Debug, Default)]
+pub struct Headings(HashMap<PlotKind, Heading>);
+
+impl Headings {
+    pub fn get(&self, kind: PlotKind) -> Option<&Heading> {
+        self.0.get(&kind)
+    }
+
+    /// Output to `path`, with the heading of `kind` if any
+    pub fn output<'a, P>(&'a self, kind: PlotKind, path: &'a P) -> Output<'a>
+    where
+        P: AsRef<Path> + ?Sized,
+    {
+        Output::from(path).with_heading(self.get(kind))
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ReleaseWeight {
+    /// Every game equally
+    Count,
+    /// IGDB total rating, with unrated games not contributing
+    Rating,
+    /// Number of IGDB total rating votes
+    RatingCount,
+}
+
+impl ReleaseWeight {
+    /// Contribution of `meta` to the density
+    pub fn of(self, meta: &Meta) -> f64 {
+        match self {
+            Self::Count => 1.0,
+            Self::Rating => meta.total_rating.unwrap_or(0.0),
+            Self::RatingCount => f64::from(meta.total_rating_count.unwrap_or(0)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines
+    Text,
+    /// One JSON object per line, for machine parsing
+    Json,
+}
+
+/// Visualizations generated by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum PlotKind {
+    /// List over time, scaled and unscaled, and its legend
+    ListOverTime,
+    ReleaseDates,
+    Coop,
+    EraBreakdown,
+    BubbleChart,
+    GenerationPositions,
+    /// Trends in the genres of the top half of the list
+    GenreTrend,
+    /// Ranking differences to every IGDB rating
+    RankingDifference,
+    Summary,
+    EpisodeSummary,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Inspect or prune the image cache instead of generating visualizations
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Download every image used by the visualizations, without generating them
+    Prefetch,
+    /// Rewrite the list files in the latest format, keeping the originals with a .bak extension
+    Migrate,
+    /// Print the stored metadata of a game as JSON
+    ShowMeta {
+        /// IGDB id, or other id, of the game
+        id: GameId,
+    },
+    /// Copy the metadata of every game from another store into the one given by --meta-store
+    ImportMetas {
+        /// Store to copy from, as json:<PATH> or sqlite:<PATH>
+        #[arg(value_name = "STORE")]
+        from: MetaStoreLocation,
+    },
+    /// Copy the metadata of every game from the store given by --meta-store into another
+    ExportMetas {
+        /// Store to copy into, as json:<PATH> or sqlite:<PATH>
+        #[arg(value_name = "STORE")]
+        to: MetaStoreLocation,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Subcommand)]
+pub enum CacheAction {
+    /// Report the number and size of cached files
+    Stats,
+    /// Delete cached files not referenced by the downloaded metadata
+    Prune {
+        /// Also delete referenced files last modified longer ago than this, in days
+        #[arg(long, value_name = "DAYS")]
+        max_age: Option<u32>,
+
+        /// Also delete cached files referenced only by games which are no longer on the latest
+        /// list
+        #[arg(long)]
+        off_list: bool,
+
+        /// Only report which files would be deleted
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ListDate {
+    Latest,
+    Date(Iso8601Date),
+}
+
+impl FromStr for ListDate {
+    type Err = time::error::Parse;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "latest" {
+            Ok(Self::Latest)
+        } else {
+            s.parse().map(Self::Date)
+        }
+    }
+}
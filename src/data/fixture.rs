@@ -0,0 +1,46 @@
+//! Small fixed lists and metadata of made-up games, shared by tests
+
+use reqwest::Client;
+use serde_json::json;
+
+use super::{Data, Meta, Metas, migrate};
+use crate::request::resource::ResourceRequestor;
+
+const LIST: &str = include_str!("fixture/list.json");
+const META: &str = include_str!("fixture/meta.json");
+
+/// Data of the fixture, which never downloads anything
+pub fn data() -> Data {
+    with_metas(serde_json::from_str(META).unwrap())
+}
+
+/// Lists of the fixture without the metadata of any of their games
+pub fn data_without_metas() -> Data {
+    with_metas(Metas::default())
+}
+
+/// Lists of the fixture with only `metas`
+pub fn data_with(metas: impl IntoIterator<Item = Meta>) -> Data {
+    with_metas(Metas(
+        metas
+            .into_iter()
+            .map(|meta| (meta.id.clone(), meta))
+            .collect(),
+    ))
+}
+
+/// Metadata of a made-up game released at the Unix epoch, with nothing else known about it
+pub fn meta(id: u32, name: &str) -> Meta {
+    serde_json::from_value(json!({ "id": id, "name": name, "first_release_date": 0 })).unwrap()
+}
+
+fn with_metas(metas: Metas) -> Data {
+    let (_, file) = migrate::migrate(LIST).unwrap();
+    Data {
+        lists: file.lists,
+        metas,
+        res: ResourceRequestor::new(Client::new(), true, 1),
+        annotations: file.annotations,
+        min_rating_count: 0,
+    }
+}
@@ -1,10 +1,104 @@
 //#![allow(clippy::ref_option)]
 
-use serde::Deserialize;
-use time::Date;
+use std::{fmt, str::FromStr};
 
-time::serde::format_description!(iso8601, Date, "[year]-[month]-[day]");
+use serde::{Deserialize, Serialize};
+use time::{
+    Date, OffsetDateTime, UtcOffset, error, format_description::BorrowedFormatItem,
+    macros::format_description,
+};
 
-#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Deserialize)]
+const FORMAT: &[BorrowedFormatItem<'_>] = format_description!("[year]-[month]-[day]");
+
+time::serde::format_description!(iso8601, Date, FORMAT);
+
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct Iso8601Date(#[serde(with = "iso8601")] pub Date);
+
+impl Iso8601Date {
+    /// Whole days from this date to `other`, negative if `other` is earlier
+    pub fn days_until(self, other: Self) -> i64 {
+        (other.0 - self.0).whole_days()
+    }
+}
+
+impl fmt::Display for Iso8601Date {
+    /// Formats as `YYYY-MM-DD`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let formatted = self.0.format(FORMAT).map_err(|_| fmt::Error)?;
+        f.write_str(&formatted)
+    }
+}
+
+impl FromStr for Iso8601Date {
+    type Err = error::Parse;
+
+    /// Parses `YYYY-MM-DD`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Date::parse(s, FORMAT).map(Self)
+    }
+}
+
+impl From<Iso8601Date> for OffsetDateTime {
+    /// Midnight UTC at the start of the date
+    fn from(date: Iso8601Date) -> Self {
+        date.0.midnight().assume_utc()
+    }
+}
+
+impl From<OffsetDateTime> for Iso8601Date {
+    /// Date in UTC, so that later instants never map to earlier dates
+    fn from(date_time: OffsetDateTime) -> Self {
+        Self(date_time.to_offset(UtcOffset::UTC).date())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Month, macros::datetime};
+
+    use super::*;
+
+    fn date(year: i32, month: Month, day: u8) -> Iso8601Date {
+        Iso8601Date(Date::from_calendar_date(year, month, day).unwrap())
+    }
+
+    #[test]
+    fn round_trips_through_serde() {
+        let date = date(2022, Month::January, 7);
+        let json = serde_json::to_string(&date).unwrap();
+        assert_eq!(json, r#""2022-01-07""#);
+        assert_eq!(serde_json::from_str::<Iso8601Date>(&json).unwrap(), date);
+        assert_eq!(date.to_string(), "2022-01-07");
+        assert_eq!("2022-01-07".parse(), Ok(date));
+    }
+
+    #[test]
+    fn rejects_malformed_dates() {
+        for s in [
+            "",
+            "2022-1-7",
+            "2022-01-07T00:00",
+            "2022/01/07",
+            "2022-02-30",
+            "07-01-2022",
+        ] {
+            assert!(s.parse::<Iso8601Date>().is_err(), "{s}");
+            assert!(
+                serde_json::from_str::<Iso8601Date>(&format!("\"{s}\"")).is_err(),
+                "{s}"
+            );
+        }
+        assert!(serde_json::from_str::<Iso8601Date>("20220107").is_err());
+    }
+
+    #[test]
+    fn converts_through_utc() {
+        let first = date(2022, Month::January, 7);
+        assert_eq!(OffsetDateTime::from(first), datetime!(2022-01-07 00:00 UTC));
+        assert_eq!(Iso8601Date::from(datetime!(2022-01-07 23:59 UTC)), first);
+        assert_eq!(Iso8601Date::from(datetime!(2022-01-08 01:00 +02:00)), first);
+        assert_eq!(first.days_until(date(2022, Month::March, 18)), 70);
+    }
+}
@@ -0,0 +1,164 @@
+//! Kernel density estimation
+
+use std::f64::consts::PI;
+
+/// Samples of a normal distribution with standard deviation `sigma`, truncated at 3 sigma
+///
+/// `sigma` is measured in samples.
+#[must_use]
+pub fn gaussian_kernel(sigma: f64) -> Vec<f64> {
+    let num_points = (2 * (3.0 * sigma).ceil() as usize) + 1;
+    (0..num_points)
+        .map(|i| {
+            (-0.5 * ((i as f64 - num_points as f64 / 2.0) / sigma).powi(2)).exp()
+                / (sigma * (2.0 * PI).sqrt())
+        })
+        .collect()
+}
+
+/// Adds `kernel`, centered at each of `samples`' positions and scaled by their weights, onto
+/// `buckets`
+///
+/// Positions are measured in buckets from the start of `buckets`. Parts of the kernel falling
+/// outside of `buckets` are discarded.
+pub fn accumulate<I>(buckets: &mut [f64], kernel: &[f64], samples: I)
+where
+    I: IntoIterator<Item = (f64, f64)>,
+{
+    // Kernels starting more than a kernel length before the first bucket contribute nothing, so
+    // the histogram only needs to extend that far back.
+    let offset = kernel.len();
+    let mut counts = vec![0.0; buckets.len() + offset];
+    for (position, weight) in samples {
+        let start = (position - kernel.len() as f64 / 2.0).round() as i64 + offset as i64;
+        if let Ok(start) = usize::try_from(start)
+            && let Some(count) = counts.get_mut(start)
+        {
+            *count += weight;
+        }
+    }
+    convolve(buckets, &counts, offset, kernel);
+}
+
+/// Adds the convolution of `counts` with `kernel` onto `buckets`
+///
+/// `counts[i]` is the weight of a kernel starting at bucket `i - offset`.
+fn convolve(buckets: &mut [f64], counts: &[f64], offset: usize, kernel: &[f64]) {
+    for (i, &count) in counts.iter().enumerate() {
+        if count == 0.0 {
+            continue;
+        }
+        let (bucket_skip, kernel_skip) = if i < offset {
+            (0, offset - i)
+        } else {
+            (i - offset, 0)
+        };
+        for (bucket, &sample) in buckets
+            .iter_mut()
+            .skip(bucket_skip)
+            .zip(kernel.iter().skip(kernel_skip))
+        {
+            *bucket = count.mul_add(sample, *bucket);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Adds the kernel of every sample separately, as `accumulate` did before histogramming them
+    fn accumulate_naive(buckets: &mut [f64], kernel: &[f64], samples: &[(f64, f64)]) {
+        for &(position, weight) in samples {
+            let start = (position - kernel.len() as f64 / 2.0).round() as i64;
+            let (bucket_skip, kernel_skip) = if start < 0 {
+                (0, start.unsigned_abs() as usize)
+            } else {
+                (start as usize, 0)
+            };
+            for (bucket, &sample) in buckets
+                .iter_mut()
+                .skip(bucket_skip)
+                .zip(kernel.iter().skip(kernel_skip))
+            {
+                *bucket = weight.mul_add(sample, *bucket);
+            }
+        }
+    }
+
+    #[test]
+    fn accumulate_matches_naive() {
+        let kernel = gaussian_kernel(7.5);
+        // Spread over and around the buckets, including kernels cut off at either end, ones
+        // entirely outside, and several at the same position
+        let samples = (0..500)
+            .map(|i| {
+                let position = f64::from(i * 37 % 260) - 40.0 + f64::from(i % 7) / 7.0;
+                (position, f64::from(i % 3 + 1) * 0.5)
+            })
+            .chain([(-1000.0, 1.0), (1000.0, 1.0), (90.0, 2.0), (90.0, 2.0)])
+            .collect::<Vec<_>>();
+
+        let mut expected = vec![0.0; 180];
+        accumulate_naive(&mut expected, &kernel, &samples);
+        let mut actual = vec![0.0; 180];
+        accumulate(&mut actual, &kernel, samples.iter().copied());
+
+        for (i, (actual, expected)) in actual.iter().zip(&expected).enumerate() {
+            assert!(
+                (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+                "bucket {i}: {actual} != {expected}"
+            );
+        }
+    }
+
+    /// Index of the highest bucket
+    fn peak(buckets: &[f64]) -> usize {
+        buckets
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn weights_scale_peaks() {
+        let kernel = gaussian_kernel(3.0);
+        let density = |weights: [f64; 3]| {
+            let mut buckets = vec![0.0; 100];
+            accumulate(
+                &mut buckets,
+                &kernel,
+                [(20.0, weights[0]), (20.0, weights[1]), (70.0, weights[2])],
+            );
+            buckets
+        };
+
+        // Unweighted, the two games released together outweigh the one released alone
+        let unweighted = density([1.0, 1.0, 1.0]);
+        assert!(
+            (18..=22).contains(&peak(&unweighted)),
+            "{}",
+            peak(&unweighted)
+        );
+        let ratio = unweighted[20] / unweighted[70];
+        assert!((ratio - 2.0).abs() < 1e-9, "{ratio}");
+
+        // Weighted, the one game outweighs them
+        let weighted = density([0.5, 0.5, 3.0]);
+        assert!((68..=72).contains(&peak(&weighted)), "{}", peak(&weighted));
+        let ratio = weighted[70] / weighted[20];
+        assert!((ratio - 3.0).abs() < 1e-9, "{ratio}");
+
+        // Games without weight don't contribute at all
+        let without = density([0.0, 0.0, 1.0]);
+        assert!(without[20].abs() < 1e-12);
+    }
+
+    #[test]
+    fn kernel_is_normalized() {
+        let sum = gaussian_kernel(20.0).iter().sum::<f64>();
+        assert!((sum - 1.0).abs() < 0.01, "{sum}");
+    }
+}
@@ -0,0 +1,225 @@
+//! Versions of the format of list files, and migration between them
+//!
+//! Version 1 is a bare object mapping the date of every list to the ids of its games, which may
+//! also be wrapped as `lists` in an object giving its `version`. Version 2 wraps that object as
+//! `lists` in an object giving its `version`, alongside an optional `annotations` object mapping
+//! dates to notes about their episodes. Files of every version are
+//! read, migrated in memory, and only written in the latest one.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use super::{Iso8601Date, List, Lists};
+
+/// Version of the format of list files written by this binary, and the newest one it reads
+pub const LIST_FORMAT_VERSION: u64 = 2;
+
+/// Contents of a list file of any version
+#[derive(Debug, Deserialize)]
+pub struct ListFileContents {
+    pub lists: Lists,
+    /// Notes about the episodes of some dates, each of which may only be given once
+    #[serde(default, with = "serde_with::rust::maps_duplicate_key_is_error")]
+    pub annotations: HashMap<Iso8601Date, String>,
+}
+
+/// Version 1 list file which gives its version
+#[derive(Deserialize)]
+struct VersionedV1File {
+    lists: Lists,
+}
+
+/// List file in the latest format, with its lists and annotations in order
+#[derive(Serialize)]
+struct ListFile<'a> {
+    version: u64,
+    lists: BTreeMap<&'a Iso8601Date, &'a List>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    annotations: BTreeMap<&'a Iso8601Date, &'a String>,
+}
+
+/// Contents of `contents`, a list file of any supported version, along with that version
+pub fn migrate(contents: &str) -> Result<(u64, ListFileContents)> {
+    // Read on its own first, as the rest of the file may not be understood by this binary. The
+    // version is only required from version 2 onwards.
+    let version =
+        match serde_json::from_str(contents)? {
+            Value::Object(file) => match file.get("version") {
+                Some(version) => Some(version.as_u64().ok_or_else(|| {
+                    anyhow!("List file format version {version} is not a number")
+                })?),
+                None => None,
+            },
+            _ => return Err(anyhow!("List file is not an object")),
+        };
+    match version {
+        None => Ok((
+            1,
+            ListFileContents {
+                lists: serde_json::from_str(contents)?,
+                annotations: HashMap::new(),
+            },
+        )),
+        Some(1) => Ok((
+            1,
+            ListFileContents {
+                lists: serde_json::from_str::<VersionedV1File>(contents)?.lists,
+                annotations: HashMap::new(),
+            },
+        )),
+        Some(LIST_FORMAT_VERSION) => Ok((LIST_FORMAT_VERSION, serde_json::from_str(contents)?)),
+        Some(version) if version > LIST_FORMAT_VERSION => Err(anyhow!(
+            "List file is in format version {version}, but this binary is too old to read \
+             versions newer than {LIST_FORMAT_VERSION}"
+        )),
+        Some(version) => Err(anyhow!("Unknown list file format version {version}")),
+    }
+}
+
+/// List file holding `contents` in the latest format
+pub fn serialize(contents: &ListFileContents) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&ListFile {
+        version: LIST_FORMAT_VERSION,
+        lists: contents.lists.0.iter().collect(),
+        annotations: contents.annotations.iter().collect(),
+    })?)
+}
+
+/// Rewrites every list file matching the glob `pattern` in the latest format, keeping a copy of
+/// each original with a `.bak` extension
+pub fn migrate_files(pattern: &str) -> Result<()> {
+    for path in glob::glob(pattern)? {
+        let path = path?;
+        migrate_file(&path).with_context(|| format!("Could not migrate {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn migrate_file(path: &Path) -> Result<()> {
+    let (version, contents) = migrate(&fs::read_to_string(path)?)?;
+    if version == LIST_FORMAT_VERSION {
+        info!("{} is already in format version {version}", path.display());
+        return Ok(());
+    }
+    let with_suffix = |suffix: &str| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(suffix);
+        PathBuf::from(name)
+    };
+    // Renamed over the original once complete, so that it is never left half-written
+    let tmp_path = with_suffix(".tmp");
+    fs::write(&tmp_path, serialize(&contents)?)?;
+    fs::copy(path, with_suffix(".bak"))?;
+    fs::rename(&tmp_path, path)?;
+    info!(
+        "Migrated {} from format version {version} to {LIST_FORMAT_VERSION}",
+        path.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+
+    use super::*;
+    use crate::data::GameId;
+
+    fn lists(contents: &ListFileContents) -> BTreeMap<Iso8601Date, Vec<GameId>> {
+        contents
+            .lists
+            .0
+            .iter()
+            .map(|(date, list)| (*date, list.0.clone()))
+            .collect()
+    }
+
+    fn expected_lists() -> BTreeMap<Iso8601Date, Vec<GameId>> {
+        BTreeMap::from([
+            (
+                Iso8601Date(date!(2021 - 11 - 22)),
+                vec![GameId::Igdb(103_320)],
+            ),
+            (
+                Iso8601Date(date!(2021 - 11 - 28)),
+                vec![GameId::Igdb(103_320), GameId::Other("custom".into())],
+            ),
+        ])
+    }
+
+    #[test]
+    fn migrates_v1() {
+        let (version, contents) =
+            migrate(r#"{"2021-11-22": [103320], "2021-11-28": [103320, "custom"]}"#).unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(lists(&contents), expected_lists());
+        assert!(contents.annotations.is_empty());
+    }
+
+    #[test]
+    fn migrates_v1_with_version() {
+        let (version, contents) = migrate(
+            r#"{"version": 1, "lists": {"2021-11-22": [103320], "2021-11-28": [103320, "custom"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(lists(&contents), expected_lists());
+        assert!(contents.annotations.is_empty());
+    }
+
+    #[test]
+    fn reads_v2() {
+        let (version, contents) = migrate(
+            r#"{
+                "version": 2,
+                "lists": {"2021-11-22": [103320], "2021-11-28": [103320, "custom"]},
+                "annotations": {"2021-11-28": "Live episode"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(version, 2);
+        assert_eq!(lists(&contents), expected_lists());
+        assert_eq!(
+            contents.annotations,
+            HashMap::from([(
+                Iso8601Date(date!(2021 - 11 - 28)),
+                "Live episode".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn round_trips_through_latest_format() {
+        let (_, contents) =
+            migrate(r#"{"2021-11-22": [103320], "2021-11-28": [103320, "custom"]}"#).unwrap();
+        let (version, migrated) = migrate(&serialize(&contents).unwrap()).unwrap();
+        assert_eq!(version, LIST_FORMAT_VERSION);
+        assert_eq!(lists(&migrated), expected_lists());
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        assert!(migrate("not json").is_err());
+        assert!(migrate("[]").is_err());
+        assert!(migrate(r#"{"version": "2", "lists": {}}"#).is_err());
+        assert!(migrate(r#"{"version": 2}"#).is_err());
+        assert!(migrate(r#"{"2021-11-22": 103320}"#).is_err());
+        assert!(migrate(r#"{"2021-11-22": [1], "2021-11-22": [2]}"#).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let err = migrate(r#"{"version": 0, "lists": {}}"#).unwrap_err();
+        assert!(err.to_string().contains("Unknown"), "{err}");
+        let err = migrate(r#"{"version": 3, "lists": {}}"#).unwrap_err();
+        assert!(err.to_string().contains("too old"), "{err}");
+    }
+}
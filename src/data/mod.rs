@@ -1,36 +1,206 @@
 //! Data models
 
+#[cfg(test)]
+pub mod fixture;
 mod iso8601;
+pub mod kde;
+pub mod migrate;
+pub mod predict;
 mod serde_metas;
+pub mod stats;
+pub mod store;
 
 use core::fmt;
-use std::{cmp::Reverse, collections::HashMap, env, fs, hash::Hash};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::Infallible,
+    env, fs,
+    hash::Hash,
+    pin::pin,
+    str::FromStr,
+};
 
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
+use async_stream::try_stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use serde_with::skip_serializing_none;
 use time::{Duration, OffsetDateTime, serde::timestamp};
-use tracing::info;
+use tokio_stream::{Stream, StreamExt};
+use tracing::{info, warn};
 
 use crate::request::{igdb::IgdbRequestor, resource::ResourceRequestor};
 pub use iso8601::Iso8601Date;
+use store::{FetchTimes, MetaStoreLocation};
 
-const LIST_FILENAME: &str = "list.json";
-const META_FILENAME: &str = "meta.json";
-const META_TEMPLATE_FILENAME: &str = "meta_template.json";
+pub const LIST_FILENAME: &str = "list.json";
 pub const LOGO_FILENAME: &str = "res/logo.png";
+const BAYESIAN_PRIOR_WEIGHT: f64 = 10.0;
+/// Longest expected time between consecutive lists, beyond which some were likely lost
+const MAX_LIST_GAP: Duration = Duration::days(180);
 
+/// Value of the environment variable `name`, or the trimmed contents of the file named by
+/// `<name>_FILE`, which takes precedence, or `None` if neither is set
+fn credential(name: &str) -> Result<Option<String>> {
+    let file_var = format!("{name}_FILE");
+    if let Ok(path) = env::var(&file_var) {
+        return Ok(Some(
+            fs::read_to_string(&path)
+                .with_context(|| format!("Could not read {path}, named by {file_var}"))?
+                .trim()
+                .to_string(),
+        ));
+    }
+    Ok(env::var(name).ok())
+}
+
+/// IGDB client ID and secret needed to `purpose`, failing clearly unless both are given
+fn credentials<'a>(
+    client_id: Option<&'a str>,
+    client_secret: Option<&'a str>,
+    purpose: &str,
+) -> Result<(&'a str, &'a str)> {
+    let missing = match (client_id, client_secret) {
+        (Some(client_id), Some(client_secret)) => return Ok((client_id, client_secret)),
+        (None, None) => "neither a client ID nor a client secret",
+        (None, _) => "no client ID",
+        _ => "no client secret",
+    };
+    Err(anyhow!(
+        "IGDB credentials are needed to {purpose}, but {missing} was given"
+    ))
+}
+
+/// IGDB games on the latest of `lists` lacking metadata in `metas`, failing if any other game
+/// lacks it, which can't be downloaded, or if any lacks it while `skip_fetch` is set
+fn missing_metas(lists: &Lists, metas: &Metas, skip_fetch: bool) -> Result<Vec<GameId>> {
+    let missing_metas = lists
+        .latest()
+        .ok_or_else(|| anyhow!("Latest list doesn't exist"))?
+        .0
+        .iter()
+        .filter_map(|id| {
+            if metas.0.contains_key(id) {
+                None
+            } else {
+                Some(if matches!(id, GameId::Igdb(_)) {
+                    Ok(id.clone())
+                } else {
+                    Err(anyhow!("Missing metadata for \"{id}\""))
+                })
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if skip_fetch && !missing_metas.is_empty() {
+        return Err(anyhow!(
+            "Missing metadata for {} games and fetching is disabled",
+            missing_metas.len()
+        ));
+    }
+    Ok(missing_metas)
+}
+
+/// IGDB games on the latest of `lists` whose metadata in `metas` changed on IGDB since it was
+/// fetched at its time in `fetch_times`, or which have no such time
+async fn changed_metas(
+    req: &mut IgdbRequestor,
+    lists: &Lists,
+    metas: &Metas,
+    fetch_times: &FetchTimes,
+) -> Result<Vec<GameId>> {
+    let stored = lists
+        .latest()
+        .ok_or_else(|| anyhow!("Latest list doesn't exist"))?
+        .0
+        .iter()
+        .filter(|id| matches!(id, GameId::Igdb(_)) && metas.0.contains_key(*id))
+        .map(|id| (id, fetch_times.get(id)))
+        .collect::<Vec<_>>();
+    let updated = match stored.iter().filter_map(|(_, fetched)| *fetched).min() {
+        Some(since) => req
+            .changed_since(*since)
+            .await?
+            .into_iter()
+            .collect::<HashMap<_, _>>(),
+        None => HashMap::new(),
+    };
+    Ok(stored
+        .into_iter()
+        .filter(|(id, fetched)| {
+            fetched.is_none_or(|fetched| updated.get(*id).is_some_and(|updated| updated > fetched))
+        })
+        .map(|(id, _)| id.clone())
+        .collect())
+}
+
+/// Rankings keyed by the date of their episode, each of which may only be given once, read from
+/// list files of any version through [`migrate`]
 #[derive(Debug, Deserialize)]
 #[serde(transparent)]
-pub struct Lists(pub HashMap<Iso8601Date, List>);
+pub struct Lists(
+    // A plain map would silently keep the last of several lists of the same date
+    #[serde(with = "serde_with::rust::maps_duplicate_key_is_error")] pub HashMap<Iso8601Date, List>,
+);
 
 impl Lists {
-    fn latest(&self) -> Option<&List> {
+    /// Loads and merges the lists and annotations of every file matching the glob `pattern`. The
+    /// list and annotation of a date may each only be defined by one file.
+    pub fn load(pattern: &str) -> Result<(Self, HashMap<Iso8601Date, String>)> {
+        let mut lists = HashMap::new();
+        let mut sources = HashMap::new();
+        let mut annotations = HashMap::new();
+        let mut annotation_sources = HashMap::new();
+        for path in glob::glob(pattern)? {
+            let path = path?;
+            let (_, file) = migrate::migrate(
+                &fs::read_to_string(&path)
+                    .with_context(|| format!("Could not read {}", path.to_string_lossy()))?,
+            )
+            .with_context(|| format!("Could not parse {}", path.to_string_lossy()))?;
+            for (date, annotation) in file.annotations {
+                if let Some(source) = annotation_sources.insert(date, path.clone()) {
+                    return Err(anyhow!(
+                        "Annotation for {date} is defined in both {} and {}",
+                        source.to_string_lossy(),
+                        path.to_string_lossy()
+                    ));
+                }
+                annotations.insert(date, annotation);
+            }
+            for (date, list) in file.lists.0 {
+                if let Some(source) = sources.insert(date, path.clone()) {
+                    return Err(anyhow!(
+                        "List for {date} is defined in both {} and {}",
+                        source.to_string_lossy(),
+                        path.to_string_lossy()
+                    ));
+                }
+                lists.insert(date, list);
+            }
+        }
+        if sources.is_empty() {
+            return Err(anyhow!("No lists found in files matching {pattern}"));
+        }
+        Ok((Self(lists), annotations))
+    }
+
+    pub fn latest(&self) -> Option<&List> {
         self.0.iter().max_by_key(|(k, _)| *k).map(|(_, v)| v)
     }
 
+    /// Consecutive dates, in order, which are more than `max_gap` apart
+    pub fn validate_continuity(&self, max_gap: Duration) -> Vec<(Iso8601Date, Iso8601Date)> {
+        let mut dates = self.0.keys().copied().collect::<Vec<_>>();
+        dates.sort();
+        dates
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .filter(|(a, b)| b.0 - a.0 > max_gap)
+            .collect()
+    }
+
     fn penultimate(&self) -> Option<&List> {
         let mut keys: Vec<_> = self.0.keys().collect();
         keys.sort();
@@ -38,11 +208,19 @@ impl Lists {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct List(pub Vec<GameId>);
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+/// Games of the latest lists of two `Data`, in the order of the list they are taken from
+#[derive(Debug)]
+pub struct ListComparison<'a> {
+    pub shared: Vec<&'a GameId>,
+    pub only_a: Vec<&'a GameId>,
+    pub only_b: Vec<&'a GameId>,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum GameId {
     Igdb(u32),
@@ -60,6 +238,23 @@ impl fmt::Display for GameId {
     }
 }
 
+impl FromStr for GameId {
+    type Err = Infallible;
+
+    /// Inverse of [`Display`](fmt::Display)
+    ///
+    /// Numbers with leading zeros are kept as [`GameId::Other`] so that they are displayed unchanged.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(Self::None);
+        }
+        Ok(match s.parse() {
+            Ok(id) if s == "0" || !s.starts_with(['0', '+']) => Self::Igdb(id),
+            _ => Self::Other(s.to_string()),
+        })
+    }
+}
+
 #[repr(u8)]
 #[derive(Debug, Serialize_repr, Deserialize_repr)]
 pub enum PlatformCategory {
@@ -108,6 +303,66 @@ pub struct InvolvedCompany {
     pub company: Company,
 }
 
+/// Part a company played in making a game, declared from the most to the least significant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CompanyRole {
+    Developer,
+    Publisher,
+    Porter,
+    Supporter,
+    /// More than one of the above
+    Multiple,
+    /// None of the above
+    Unknown,
+}
+
+impl CompanyRole {
+    /// Roles given by IGDB, from the most to the least significant
+    pub const BY_PRIORITY: [Self; 4] = [
+        Self::Developer,
+        Self::Publisher,
+        Self::Porter,
+        Self::Supporter,
+    ];
+}
+
+impl InvolvedCompany {
+    pub fn has_role(&self, role: CompanyRole) -> bool {
+        match role {
+            CompanyRole::Developer => self.developer,
+            CompanyRole::Publisher => self.publisher,
+            CompanyRole::Porter => self.porting,
+            CompanyRole::Supporter => self.supporting,
+            CompanyRole::Multiple | CompanyRole::Unknown => self.role() == role,
+        }
+    }
+
+    /// Only role of the company, `Multiple` if it had several, or `Unknown` if it had none
+    pub fn role(&self) -> CompanyRole {
+        match self.roles().as_slice() {
+            [] => CompanyRole::Unknown,
+            &[role] => role,
+            _ => CompanyRole::Multiple,
+        }
+    }
+
+    /// Every role of the company, from the most to the least significant
+    pub fn roles(&self) -> Vec<CompanyRole> {
+        CompanyRole::BY_PRIORITY
+            .into_iter()
+            .filter(|&role| self.has_role(role))
+            .collect()
+    }
+
+    /// Most significant role of the company, or `Unknown` if it had none
+    pub fn primary_role(&self) -> CompanyRole {
+        CompanyRole::BY_PRIORITY
+            .into_iter()
+            .find(|&role| self.has_role(role))
+            .unwrap_or(CompanyRole::Unknown)
+    }
+}
+
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MultiplayerMode {
@@ -171,6 +426,50 @@ pub struct Meta {
     pub total_rating_count: Option<u32>,
 }
 
+impl Meta {
+    pub const fn get_rating(&self, kind: RatingKind) -> Option<f64> {
+        match kind {
+            RatingKind::User => self.rating,
+            RatingKind::Critic => self.aggregated_rating,
+            RatingKind::Total => self.total_rating,
+        }
+    }
+
+    pub const fn get_rating_count(&self, kind: RatingKind) -> Option<u32> {
+        match kind {
+            RatingKind::User => self.rating_count,
+            RatingKind::Critic => self.aggregated_rating_count,
+            RatingKind::Total => self.total_rating_count,
+        }
+    }
+
+    pub const fn has_rating(&self, kind: RatingKind) -> bool {
+        self.get_rating(kind).is_some()
+    }
+
+    /// URLs of all images associated with the game
+    pub fn image_urls(&self) -> impl Iterator<Item = &str> {
+        self.cover
+            .iter()
+            .chain(
+                self.game_engines
+                    .iter()
+                    .filter_map(|engine| engine.logo.as_ref()),
+            )
+            .chain(
+                self.involved_companies
+                    .iter()
+                    .filter_map(|involved_company| involved_company.company.logo.as_ref()),
+            )
+            .chain(
+                self.platforms
+                    .iter()
+                    .filter_map(|platform| platform.platform_logo.as_ref()),
+            )
+            .map(|url_field| url_field.url.as_str())
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct Metas(#[serde(with = "serde_metas")] pub HashMap<GameId, Meta>);
@@ -182,6 +481,10 @@ pub enum RatingKind {
     Total,
 }
 
+impl RatingKind {
+    pub const ALL: [Self; 3] = [Self::User, Self::Critic, Self::Total];
+}
+
 impl fmt::Display for RatingKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -196,63 +499,243 @@ impl fmt::Display for RatingKind {
     }
 }
 
+/// How ratings are ordered when ranking games
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RatingWeighting {
+    /// Raw rating value
+    Raw,
+    /// Rating pulled towards the mean rating the fewer votes it has
+    Bayesian,
+}
+
+/// Number of games supporting each kind of co-op
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MultiplayerSummary {
+    pub campaign_coop: u32,
+    pub lan_coop: u32,
+    pub offline_coop: u32,
+    pub online_coop: u32,
+    pub no_data: u32,
+}
+
+/// Progress of loading the lists and metadata
+#[derive(Debug)]
+pub enum DataLoadEvent {
+    LoadingLists,
+    ListsLoaded {
+        count: usize,
+    },
+    LoadingMetas,
+    MetasLoaded {
+        count: usize,
+    },
+    FetchingMeta {
+        id: GameId,
+    },
+    MetaFetched {
+        id: GameId,
+    },
+    /// Everything has been loaded
+    Done(Box<Data>),
+}
+
+/// Summary of the ratings of one kind of the games on the latest list
+#[derive(Debug, Clone, Copy)]
+pub struct RatingStats {
+    /// Number of rated games
+    pub count: usize,
+    pub mean: f64,
+    pub quartiles: stats::Quartiles,
+}
+
+/// Where `Data` loads its lists from, and how it downloads missing metadata
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Glob matching the files holding the lists
+    pub lists_pattern: String,
+    /// Where downloaded metadata is kept
+    pub meta_store: MetaStoreLocation,
+    /// Fail instead of downloading missing metadata
+    pub skip_fetch: bool,
+    /// Number of games whose metadata is requested at once
+    pub igdb_batch_size: usize,
+    /// Download the metadata of games on the latest list again if it changed on IGDB since it was
+    /// downloaded, or if it isn't known when it was
+    pub refresh_metas: bool,
+    /// Only needed to download missing metadata, and read as described by `Data::new_async` if not given
+    pub igdb_client_id: Option<String>,
+    /// Only needed to download missing metadata, and read as described by `Data::new_async` if not given
+    pub igdb_client_secret: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct Data {
     pub lists: Lists,
     pub metas: Metas,
     pub res: ResourceRequestor,
+    /// Notes about the episodes of some dates
+    annotations: HashMap<Iso8601Date, String>,
+    /// Games with fewer votes than this are left out of IGDB rankings
+    min_rating_count: u32,
 }
 
 impl Data {
-    pub async fn new(client: Client) -> Result<Self> {
-        info!("Loading lists");
-        let lists: Lists = serde_json::from_str(&fs::read_to_string(LIST_FILENAME)?)?;
-        info!("Loaded lists");
-        info!("Loading metadata");
-        let mut metas = if fs::exists(META_FILENAME)? {
-            serde_json::from_str(&fs::read_to_string(META_FILENAME)?)?
-        } else if fs::exists(META_TEMPLATE_FILENAME)? {
-            fs::copy(META_TEMPLATE_FILENAME, META_FILENAME)?;
-            serde_json::from_str(&fs::read_to_string(META_FILENAME)?)?
-        } else {
-            Metas::default()
-        };
+    /// Loads lists and metadata as described by `config`, yielding the progress of each step and
+    /// lastly the loaded data in `DataLoadEvent::Done`, or ending with an error if a step fails
+    ///
+    /// IGDB credentials not given in `config` are read from `CLIENT_ID` and `CLIENT_SECRET`, or
+    /// the files named by `CLIENT_ID_FILE` and `CLIENT_SECRET_FILE`, once metadata turns out to be
+    /// missing.
+    pub fn new_async(
+        client: Client,
+        config: Config,
+        res: ResourceRequestor,
+    ) -> impl Stream<Item = Result<DataLoadEvent>> {
+        try_stream! {
+            yield DataLoadEvent::LoadingLists;
+            let (lists, annotations) = Lists::load(&config.lists_pattern)?;
+            yield DataLoadEvent::ListsLoaded {
+                count: lists.0.len(),
+            };
+            yield DataLoadEvent::LoadingMetas;
+            let mut store = config.meta_store.open()?;
+            let mut metas = store.load_all()?;
 
-        let missing_metas = lists
-            .latest()
-            .ok_or_else(|| anyhow!("Latest list doesn't exist"))?
-            .0
-            .iter()
-            .filter_map(|id| {
-                if metas.0.contains_key(id) {
-                    None
+            let mut missing_metas = missing_metas(&lists, &metas, config.skip_fetch)?;
+
+            if !missing_metas.is_empty() || config.refresh_metas {
+                // Only read now, so that credential files needn't exist unless metadata is missing
+                let client_id = match &config.igdb_client_id {
+                    Some(client_id) => Some(client_id.clone()),
+                    None => credential("CLIENT_ID")?,
+                };
+                let client_secret = match &config.igdb_client_secret {
+                    Some(client_secret) => Some(client_secret.clone()),
+                    None => credential("CLIENT_SECRET")?,
+                };
+                let purpose = if missing_metas.is_empty() {
+                    "refresh the metadata".to_string()
                 } else {
-                    Some(if matches!(id, GameId::Igdb(_)) {
-                        Ok(id.clone())
-                    } else {
-                        Err(anyhow!("Missing metadata for \"{id}\""))
-                    })
+                    format!("download the metadata of {} games", missing_metas.len())
+                };
+                let (client_id, client_secret) =
+                    credentials(client_id.as_deref(), client_secret.as_deref(), &purpose)?;
+                let mut req = IgdbRequestor::new(client.clone(), client_id, client_secret)
+                    .with_batch_size(config.igdb_batch_size);
+                if config.refresh_metas {
+                    let changed =
+                        changed_metas(&mut req, &lists, &metas, &store.fetch_times()?).await?;
+                    info!(
+                        "Metadata of {} games changed on IGDB since it was downloaded",
+                        changed.len()
+                    );
+                    missing_metas.extend(changed);
                 }
-            })
-            .collect::<Result<Vec<_>>>()?;
-
-        if !missing_metas.is_empty() {
-            info!("Downloading missing metadata");
-            let client_id = env::var("CLIENT_ID")?;
-            let client_secret = env::var("CLIENT_SECRET")?;
-            let mut req = IgdbRequestor::new(client.clone(), &client_id, &client_secret);
-            metas.0.extend(req.games(missing_metas.as_slice()).await?.0);
-            fs::write(META_FILENAME, &serde_json::to_string_pretty(&metas)?)?;
-            info!("Downloaded missing metadata");
+
+                if !missing_metas.is_empty() {
+                    info!("Downloading missing metadata");
+                    for id in &missing_metas {
+                        yield DataLoadEvent::FetchingMeta { id: id.clone() };
+                    }
+                    let mut batches = pin!(req.games(&missing_metas));
+                    while let Some(fetched) = batches.next().await {
+                        let fetched = fetched?;
+                        for id in fetched.0.keys() {
+                            yield DataLoadEvent::MetaFetched { id: id.clone() };
+                        }
+                        // Stored batch by batch, so that an interrupted download needn't be
+                        // repeated
+                        store.upsert_many(&fetched, &FetchTimes::new())?;
+                        metas.0.extend(fetched.0);
+                    }
+                    info!("Downloaded missing metadata");
+                }
+            }
+
+            yield DataLoadEvent::MetasLoaded {
+                count: metas.0.len(),
+            };
+
+            let data = Self {
+                lists,
+                metas,
+                res,
+                annotations,
+                min_rating_count: 0,
+            };
+            data.validate();
+            yield DataLoadEvent::Done(Box::new(data));
         }
+    }
 
-        info!("Loaded metadata");
+    /// Loads lists and metadata as described by `config`, like `new_async` without reporting the
+    /// progress
+    pub async fn new_with_config(
+        client: Client,
+        config: &Config,
+        res: ResourceRequestor,
+    ) -> Result<Self> {
+        let events = Self::new_async(client, config.clone(), res);
+        let mut events = pin!(events);
+        while let Some(event) = events.next().await {
+            if let DataLoadEvent::Done(data) = event? {
+                return Ok(*data);
+            }
+        }
+        Err(anyhow!("Loading ended without any data"))
+    }
 
-        Ok(Self {
-            lists,
-            metas,
-            res: ResourceRequestor::new(client),
-        })
+    /// Warns about signs of missing or inconsistent data
+    fn validate(&self) {
+        for (from, to) in self.lists.validate_continuity(MAX_LIST_GAP) {
+            warn!(
+                "No lists between {from} and {to}, {} days apart",
+                from.days_until(to)
+            );
+        }
+        for date in self.annotations.keys() {
+            if !self.lists.0.contains_key(date) {
+                warn!("Annotation for {date} doesn't belong to any list");
+            }
+        }
+    }
+
+    /// Note about the episode of `date`, if it has one
+    pub fn annotation(&self, date: Iso8601Date) -> Option<&str> {
+        self.annotations.get(&date).map(String::as_str)
+    }
+
+    /// Number of votes below which games are left out of IGDB rankings
+    pub const fn min_rating_count(&self) -> u32 {
+        self.min_rating_count
+    }
+
+    /// Leaves games with fewer than `min_rating_count` votes out of IGDB rankings
+    #[must_use]
+    pub const fn with_min_rating_count(mut self, min_rating_count: u32) -> Self {
+        self.min_rating_count = min_rating_count;
+        self
+    }
+
+    /// Games on both, or only one, of the latest lists of `a` and `b`
+    pub fn compare_lists<'a>(a: &'a Self, b: &'a Self) -> ListComparison<'a> {
+        let latest = |data: &'a Self| {
+            data.latest()
+                .map(|list| list.0.iter().collect::<Vec<_>>())
+                .unwrap_or_default()
+        };
+        let (latest_a, latest_b) = (latest(a), latest(b));
+        let ids_a = latest_a.iter().copied().collect::<HashSet<_>>();
+        let ids_b = latest_b.iter().copied().collect::<HashSet<_>>();
+        let (shared, only_a) = latest_a.into_iter().partition(|id| ids_b.contains(id));
+        ListComparison {
+            shared,
+            only_a,
+            only_b: latest_b
+                .into_iter()
+                .filter(|id| !ids_a.contains(id))
+                .collect(),
+        }
     }
 
     /// All dates when list was changed
@@ -262,8 +745,8 @@ impl Data {
         dates
     }
 
-    /// Time that each game spent on the top / bottom of the list
-    pub fn extrema(&self, top: bool) -> Vec<(&GameId, Duration)> {
+    /// Time that each game spent on the top / bottom of the list, for at most `limit` games
+    pub fn extrema(&self, top: bool, limit: usize) -> Vec<(&GameId, Duration)> {
         let dates = self.dates();
         let mut extrema = HashMap::new();
 
@@ -278,52 +761,180 @@ impl Data {
         }
 
         let mut extrema = extrema.into_iter().collect::<Vec<_>>();
-        extrema.sort_by_key(|top| Reverse(top.1));
+        extrema.sort_by_key(|&(id, duration)| (Reverse(duration), id));
+        extrema.truncate(limit);
         extrema
     }
 
-    pub fn igdb_list(&self, kind: RatingKind) -> Vec<(f64, &Meta)> {
-        let mut igdb_list = self
-            .metas
-            .0
-            .values()
-            .filter_map(|meta| {
-                match kind {
-                    RatingKind::User => meta.rating,
-                    RatingKind::Critic => meta.aggregated_rating,
-                    RatingKind::Total => meta.total_rating,
-                }
-                .map(|rating| (rating, meta))
+    /// `extrema` alongside the fraction of the tracked period, from the first to the last list,
+    /// that each game spent on the top / bottom of the list
+    pub fn extrema_fractions(&self, top: bool, limit: usize) -> Vec<(&GameId, Duration, f64)> {
+        let dates = self.dates();
+        let span = match (dates.first(), dates.last()) {
+            (Some(first), Some(last)) => last.0 - first.0,
+            _ => Duration::ZERO,
+        };
+        self.extrema(top, limit)
+            .into_iter()
+            .map(|(id, duration)| {
+                let fraction = if span.is_positive() {
+                    duration / span
+                } else {
+                    0.0
+                };
+                (id, duration, fraction)
             })
+            .collect()
+    }
+
+    /// Games which spent time on the top / bottom of the list, scored by the fraction of the
+    /// tracked period spent there, each period weighted by the inverse of the list's length,
+    /// highest score first
+    pub fn extrema_normalized(&self, top: bool) -> Vec<(&GameId, f64)> {
+        let dates = self.dates();
+        let span = match (dates.first(), dates.last()) {
+            (Some(first), Some(last)) => last.0 - first.0,
+            _ => Duration::ZERO,
+        };
+        if !span.is_positive() {
+            return Vec::new();
+        }
+        let mut extrema = HashMap::new();
+
+        for period in dates.windows(2) {
+            let list = &self.lists.0[&period[0]].0;
+            let extremum = &list[if top { 0 } else { list.len() - 1 }];
+            let score = (period[1].0 - period[0].0) / span / list.len() as f64;
+            *extrema.entry(extremum).or_insert(0.0) += score;
+        }
+
+        let mut extrema = extrema.into_iter().collect::<Vec<_>>();
+        extrema.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        extrema
+    }
+
+    pub fn igdb_list(&self, kind: RatingKind, weighting: RatingWeighting) -> Vec<(f64, &Meta)> {
+        let mut igdb_list = self
+            .metas_sorted_by_name()
+            .into_iter()
+            .filter_map(|meta| meta.get_rating(kind).map(|rating| (rating, meta)))
             .collect::<Vec<_>>();
+        let num_rated = igdb_list.len();
+        igdb_list
+            .retain(|(_, meta)| meta.get_rating_count(kind).unwrap_or(0) >= self.min_rating_count);
+        if igdb_list.len() < num_rated {
+            info!(
+                "Excluded {} games with fewer than {} votes from the {kind}",
+                num_rated - igdb_list.len(),
+                self.min_rating_count
+            );
+        }
+        if weighting == RatingWeighting::Bayesian {
+            let prior_mean = igdb_list.iter().map(|(rating, _)| rating).sum::<f64>()
+                / igdb_list.len().max(1) as f64;
+            for (rating, meta) in &mut igdb_list {
+                *rating = stats::bayesian_average(
+                    *rating,
+                    meta.get_rating_count(kind).unwrap_or(0),
+                    prior_mean,
+                    BAYESIAN_PRIOR_WEIGHT,
+                );
+            }
+        }
+        // Stable sort, so equal ratings remain in alphabetical order
         igdb_list.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
         igdb_list
     }
 
-    /// Most common elements from metas
-    pub fn most_common<'a, FE, FH, I, T, N>(&'a self, extract: FE, hash: FH) -> Vec<(u32, &'a T)>
+    /// Companies by the number of games in which they had `role`, most common first
+    pub fn most_common_by_role(&self, role: CompanyRole) -> Vec<(u32, &Company)> {
+        self.most_common(
+            |meta| {
+                meta.involved_companies
+                    .iter()
+                    .filter(move |involved_company| involved_company.has_role(role))
+            },
+            |involved_company| involved_company.company.name.as_str(),
+        )
+        .into_iter()
+        .map(|(count, involved_company)| (count, &involved_company.company))
+        .collect()
+    }
+
+    /// Most common elements from metas, with ties ordered by `key`
+    pub fn most_common<'a, FE, FK, I, T, K>(&'a self, extract: FE, key: FK) -> Vec<(u32, &'a T)>
     where
         FE: Fn(&'a Meta) -> I,
-        FH: Fn(&'a T) -> N,
+        FK: Fn(&'a T) -> K,
         I: Iterator<Item = &'a T>,
-        N: Hash + Eq + 'a,
+        K: Hash + Ord + 'a,
         T: 'a,
     {
-        let mut values = HashMap::<N, (u32, &T)>::new();
-        for value in self.metas.0.values().flat_map(extract) {
+        let mut values = HashMap::<K, (u32, &T)>::new();
+        for value in self.metas_sorted_by_name().into_iter().flat_map(extract) {
             values
-                .entry(hash(value))
+                .entry(key(value))
                 .and_modify(|e| e.0 += 1)
                 .or_insert((1, value));
         }
-        let mut values = values.values().copied().collect::<Vec<_>>();
-        values.sort_by_key(|top| Reverse(top.0));
-        values
+        let mut values = values.into_iter().collect::<Vec<_>>();
+        values.sort_by(|(a_key, a), (b_key, b)| b.0.cmp(&a.0).then_with(|| a_key.cmp(b_key)));
+        values.into_iter().map(|(_, value)| value).collect()
+    }
+
+    /// Change per year in the share of each genre among the games in the top half of the list,
+    /// as the slope of a linear regression over every list
+    ///
+    /// Games without metadata are left out of the top half, and genres missing from a list count
+    /// as a share of zero.
+    pub fn genre_trend(&self) -> HashMap<String, f64> {
+        let dates = self.dates();
+        let Some(first) = dates.first() else {
+            return HashMap::new();
+        };
+        let shares = dates
+            .iter()
+            .map(|date| {
+                let list = &self.lists.0[date].0;
+                let top_half = list[..list.len().div_ceil(2)]
+                    .iter()
+                    .filter_map(|id| self.metas.0.get(id))
+                    .collect::<Vec<_>>();
+                let mut counts = HashMap::<&str, u32>::new();
+                for meta in &top_half {
+                    for genre in &meta.genres {
+                        *counts.entry(genre.name.as_str()).or_default() += 1;
+                    }
+                }
+                let years =
+                    (date.0 - first.0).as_seconds_f64() / Duration::days(365).as_seconds_f64();
+                (years, counts, top_half.len().max(1) as f64)
+            })
+            .collect::<Vec<_>>();
+        let genres = shares
+            .iter()
+            .flat_map(|(_, counts, _)| counts.keys().copied())
+            .collect::<HashSet<_>>();
+        genres
+            .into_iter()
+            .filter_map(|genre| {
+                let points = shares
+                    .iter()
+                    .map(|(years, counts, total)| {
+                        (
+                            *years,
+                            f64::from(counts.get(genre).copied().unwrap_or(0)) / total,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                Some((genre.to_string(), stats::linear_slope(&points)?))
+            })
+            .collect()
     }
 
     /// Difference in list position between The List and the IGDB ranking
     pub fn igdb_diffs(&self) -> Option<Vec<(i32, &Meta)>> {
-        let igdb_list = self.igdb_list(RatingKind::Total);
+        let igdb_list = self.igdb_list(RatingKind::Total, RatingWeighting::Raw);
         let latest_list = self.lists.latest()?;
         let mut diffs = igdb_list
             .iter()
@@ -340,6 +951,28 @@ impl Data {
         Some(diffs)
     }
 
+    /// Metas in alphabetical order of name
+    pub fn metas_sorted_by_name(&self) -> Vec<&Meta> {
+        let mut metas = self.metas.0.values().collect::<Vec<_>>();
+        metas.sort_by(|a, b| {
+            a.name
+                .cmp(&b.name)
+                .then(a.first_release_date.cmp(&b.first_release_date))
+        });
+        metas
+    }
+
+    /// Metas in ascending order of first release date
+    pub fn metas_sorted_by_date(&self) -> Vec<&Meta> {
+        let mut metas = self.metas.0.values().collect::<Vec<_>>();
+        metas.sort_by(|a, b| {
+            a.first_release_date
+                .cmp(&b.first_release_date)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        metas
+    }
+
     pub fn latest(&self) -> Option<&List> {
         self.lists.latest()
     }
@@ -348,6 +981,204 @@ impl Data {
         self.lists.penultimate()
     }
 
+    /// Statistics of the `kind` ratings of the games on the latest list, or `None` if none of
+    /// them have one
+    pub fn rating_stats(&self, kind: RatingKind) -> Option<RatingStats> {
+        let ratings = self
+            .latest()?
+            .0
+            .iter()
+            .filter_map(|id| self.metas.0.get(id)?.get_rating(kind))
+            .collect::<Vec<_>>();
+        Some(RatingStats {
+            count: ratings.len(),
+            mean: ratings.iter().sum::<f64>() / ratings.len() as f64,
+            quartiles: stats::quartiles(&ratings)?,
+        })
+    }
+
+    /// Metas of the first `n` games on the latest list
+    pub fn top_n(&self, n: usize) -> Option<Vec<&Meta>> {
+        self.resolve(self.latest()?.0.get(..n)?)
+    }
+
+    /// Metas of the last `n` games on the latest list
+    pub fn bottom_n(&self, n: usize) -> Option<Vec<&Meta>> {
+        let list = &self.latest()?.0;
+        self.resolve(list.get(list.len().checked_sub(n)?..)?)
+    }
+
+    fn resolve(&self, ids: &[GameId]) -> Option<Vec<&Meta>> {
+        ids.iter().map(|id| self.metas.0.get(id)).collect()
+    }
+
+    /// List as of `date`
+    pub fn list_at(&self, date: Iso8601Date) -> Option<&List> {
+        self.lists.0.get(&date)
+    }
+
+    /// List preceding the one on `date`
+    pub fn previous_list(&self, date: Iso8601Date) -> Option<&List> {
+        self.lists
+            .0
+            .iter()
+            .filter(|(k, _)| **k < date)
+            .max_by_key(|(k, _)| *k)
+            .map(|(_, v)| v)
+    }
+
+    /// Games which rose the most between the penultimate and latest list, by positions gained
+    pub fn most_improved(&self, n: usize) -> Option<Vec<(&Meta, i32)>> {
+        let mut movers = self.movers()?;
+        movers.retain(|(_, delta)| *delta > 0);
+        movers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.name.cmp(&b.0.name)));
+        movers.truncate(n);
+        Some(movers)
+    }
+
+    /// Games which fell the most between the penultimate and latest list, by positions gained
+    pub fn most_declined(&self, n: usize) -> Option<Vec<(&Meta, i32)>> {
+        let mut movers = self.movers()?;
+        movers.retain(|(_, delta)| *delta < 0);
+        movers.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.name.cmp(&b.0.name)));
+        movers.truncate(n);
+        Some(movers)
+    }
+
+    /// Games on the latest list which weren't on the penultimate one, with their positions
+    pub fn debuts(&self) -> Option<Vec<(&Meta, usize)>> {
+        Some(self.newcomers(self.penultimate()?, self.latest()?))
+    }
+
+    /// Games on the penultimate list which aren't on the latest one, with their former positions
+    pub fn departures(&self) -> Option<Vec<(&Meta, usize)>> {
+        Some(self.newcomers(self.latest()?, self.penultimate()?))
+    }
+
+    /// Games on `list` which aren't on `previous`, with their positions on `list`
+    fn newcomers(&self, previous: &List, list: &List) -> Vec<(&Meta, usize)> {
+        list.0
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| !previous.0.contains(id))
+            .filter_map(|(i, id)| Some((self.metas.0.get(id)?, i)))
+            .collect()
+    }
+
+    /// Game at the top of the latest list, and how long it has been there without interruption
+    pub fn top_streak(&self) -> Option<(&Meta, Duration)> {
+        let dates = self.dates();
+        let latest_date = *dates.last()?;
+        let top = self.lists.0[&latest_date].0.first()?;
+        let start = dates
+            .iter()
+            .rev()
+            .take_while(|date| self.lists.0[date].0.first() == Some(top))
+            .last()?;
+        Some((self.metas.0.get(top)?, latest_date.0 - start.0))
+    }
+
+    /// Number of separate stints on the list of every game that has ever been on it, the game
+    /// being absent from the list between them, most stints first
+    pub fn recurrences(&self) -> Vec<(GameId, usize)> {
+        let mut stints = HashMap::new();
+        let mut previous: Option<&List> = None;
+        for date in self.dates() {
+            let list = &self.lists.0[&date];
+            for id in &list.0 {
+                if previous.is_none_or(|previous| !previous.0.iter().any(|x| x == id)) {
+                    *stints.entry(id).or_insert(0) += 1;
+                }
+            }
+            previous = Some(list);
+        }
+
+        let mut stints = stints
+            .into_iter()
+            .map(|(id, count)| (id.clone(), count))
+            .collect::<Vec<_>>();
+        stints.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        stints
+    }
+
+    /// Positions gained by each game on both the penultimate and latest list
+    fn movers(&self) -> Option<Vec<(&Meta, i32)>> {
+        let latest = self.latest()?;
+        let penultimate = self.penultimate()?;
+        Some(
+            latest
+                .0
+                .iter()
+                .enumerate()
+                .filter_map(|(i, id)| {
+                    let previous = penultimate.0.iter().position(|prev_id| prev_id == id)?;
+                    Some((self.metas.0.get(id)?, previous as i32 - i as i32))
+                })
+                .collect(),
+        )
+    }
+
+    /// Co-op capabilities of games on the latest list
+    pub fn multiplayer_summary(&self) -> MultiplayerSummary {
+        let mut summary = MultiplayerSummary::default();
+        for meta in self
+            .latest()
+            .iter()
+            .flat_map(|list| list.0.iter())
+            .filter_map(|id| self.metas.0.get(id))
+        {
+            if meta.multiplayer_modes.is_empty() {
+                summary.no_data += 1;
+                continue;
+            }
+            let any =
+                |f: fn(&MultiplayerMode) -> bool| u32::from(meta.multiplayer_modes.iter().any(f));
+            summary.campaign_coop += any(|mode| mode.campaigncoop);
+            summary.lan_coop += any(|mode| mode.lancoop);
+            summary.offline_coop += any(|mode| mode.offlinecoop);
+            summary.online_coop += any(|mode| mode.onlinecoop);
+        }
+        summary
+    }
+
+    /// Latest list positions grouped by the earliest console generation of each game
+    ///
+    /// Games without a generation on any platform (e.g. PC-only) are grouped under `None`
+    pub fn positions_by_generation(&self) -> BTreeMap<Option<u32>, Vec<usize>> {
+        let mut groups = BTreeMap::<_, Vec<_>>::new();
+        for (position, id) in self
+            .latest()
+            .iter()
+            .flat_map(|list| list.0.iter().enumerate())
+        {
+            if let Some(meta) = self.metas.0.get(id) {
+                groups
+                    .entry(
+                        meta.platforms
+                            .iter()
+                            .filter_map(|platform| platform.generation)
+                            .min(),
+                    )
+                    .or_default()
+                    .push(position);
+            }
+        }
+        groups
+    }
+
+    /// Number of games on `list` released in each decade, keyed by the decade's first year
+    ///
+    /// Games without metadata are skipped.
+    pub fn games_released_in_decade(&self, list: &List) -> BTreeMap<i32, usize> {
+        let mut counts = BTreeMap::new();
+        for meta in list.0.iter().filter_map(|id| self.metas.0.get(id)) {
+            *counts
+                .entry(meta.first_release_date.year().div_euclid(10) * 10)
+                .or_default() += 1;
+        }
+        counts
+    }
+
     pub fn release_date_range(&self) -> Option<(OffsetDateTime, OffsetDateTime)> {
         Some((
             self.metas
@@ -363,3 +1194,408 @@ impl Data {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_id_round_trips() {
+        for id in [
+            GameId::Igdb(0),
+            GameId::Igdb(103_320),
+            GameId::Igdb(u32::MAX),
+            GameId::Other("SUBTERRA".into()),
+            GameId::Other("007".into()),
+            GameId::Other("00".into()),
+            GameId::Other("+5".into()),
+            GameId::Other("-1".into()),
+            GameId::Other("4294967296".into()),
+            GameId::Other("12 34".into()),
+            GameId::None,
+        ] {
+            assert_eq!(id.to_string().parse::<GameId>(), Ok(id));
+        }
+    }
+
+    #[test]
+    fn game_id_parses() {
+        assert_eq!("103320".parse(), Ok(GameId::Igdb(103_320)));
+        assert_eq!("0".parse(), Ok(GameId::Igdb(0)));
+        assert_eq!("4294967295".parse(), Ok(GameId::Igdb(u32::MAX)));
+        // Too large for an IGDB id
+        assert_eq!("4294967296".parse(), Ok(GameId::Other("4294967296".into())));
+        // Kept as written rather than normalized
+        assert_eq!("0042".parse(), Ok(GameId::Other("0042".into())));
+        assert_eq!("+42".parse(), Ok(GameId::Other("+42".into())));
+        assert_eq!("".parse(), Ok(GameId::None));
+    }
+
+    fn involved(name: &str, roles: &[CompanyRole]) -> InvolvedCompany {
+        InvolvedCompany {
+            developer: roles.contains(&CompanyRole::Developer),
+            porting: roles.contains(&CompanyRole::Porter),
+            publisher: roles.contains(&CompanyRole::Publisher),
+            supporting: roles.contains(&CompanyRole::Supporter),
+            company: Company {
+                country: None,
+                logo: None,
+                name: name.into(),
+                start_date: None,
+            },
+        }
+    }
+
+    #[test]
+    fn company_roles() {
+        let both = involved("Both", &[CompanyRole::Publisher, CompanyRole::Developer]);
+        assert_eq!(both.role(), CompanyRole::Multiple);
+        assert_eq!(both.primary_role(), CompanyRole::Developer);
+        assert_eq!(
+            both.roles(),
+            [CompanyRole::Developer, CompanyRole::Publisher]
+        );
+        assert!(both.has_role(CompanyRole::Publisher));
+        assert!(both.has_role(CompanyRole::Multiple));
+        assert!(!both.has_role(CompanyRole::Porter));
+
+        let porter = involved("Porter", &[CompanyRole::Porter]);
+        assert_eq!(porter.role(), CompanyRole::Porter);
+        assert!(!porter.has_role(CompanyRole::Multiple));
+
+        let none = involved("None", &[]);
+        assert_eq!(none.role(), CompanyRole::Unknown);
+        assert_eq!(none.primary_role(), CompanyRole::Unknown);
+        assert!(none.has_role(CompanyRole::Unknown));
+    }
+
+    #[test]
+    fn most_common_by_role() {
+        let game = |id, name, companies| {
+            let mut meta = fixture::meta(id, name);
+            meta.involved_companies = companies;
+            meta
+        };
+        let data = fixture::data_with([
+            game(
+                101,
+                "A",
+                vec![
+                    involved("Studio", &[CompanyRole::Developer]),
+                    involved("Press", &[CompanyRole::Publisher]),
+                ],
+            ),
+            game(
+                102,
+                "B",
+                vec![involved(
+                    "Studio",
+                    &[CompanyRole::Developer, CompanyRole::Publisher],
+                )],
+            ),
+            game(
+                103,
+                "C",
+                vec![
+                    involved("Press", &[CompanyRole::Publisher]),
+                    involved("Helper", &[CompanyRole::Supporter]),
+                    involved("Ghost", &[]),
+                ],
+            ),
+        ]);
+        let by_role = |role| {
+            data.most_common_by_role(role)
+                .into_iter()
+                .map(|(count, company)| (count, company.name.as_str()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(by_role(CompanyRole::Developer), [(2, "Studio")]);
+        assert_eq!(
+            by_role(CompanyRole::Publisher),
+            [(2, "Press"), (1, "Studio")]
+        );
+        assert_eq!(by_role(CompanyRole::Supporter), [(1, "Helper")]);
+        assert_eq!(by_role(CompanyRole::Porter), []);
+        assert_eq!(by_role(CompanyRole::Multiple), [(1, "Studio")]);
+        assert_eq!(by_role(CompanyRole::Unknown), [(1, "Ghost")]);
+    }
+
+    #[test]
+    fn multiplayer_summary_counts_games_once_per_kind() {
+        let mode = |campaign, lan, offline, online| MultiplayerMode {
+            campaigncoop: campaign,
+            lancoop: lan,
+            offlinecoop: offline,
+            onlinecoop: online,
+        };
+        let game = |id, modes| {
+            let mut meta = fixture::meta(id, &id.to_string());
+            meta.multiplayer_modes = modes;
+            meta
+        };
+        let data = fixture::data_with([
+            // Overlapping flags across the modes of several platforms
+            game(
+                101,
+                vec![
+                    mode(true, false, false, true),
+                    mode(false, false, true, true),
+                ],
+            ),
+            game(102, vec![mode(true, true, true, true)]),
+            game(103, vec![mode(false, false, false, false)]),
+            game(104, Vec::new()),
+        ]);
+        assert_eq!(
+            data.multiplayer_summary(),
+            MultiplayerSummary {
+                campaign_coop: 2,
+                lan_coop: 1,
+                offline_coop: 2,
+                online_coop: 2,
+                no_data: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn most_common_breaks_ties_by_key() {
+        let game = |id, name, genres: &[&str]| {
+            let mut meta = fixture::meta(id, name);
+            meta.genres = genres
+                .iter()
+                .map(|&genre| NameField { name: genre.into() })
+                .collect();
+            meta
+        };
+        let data = fixture::data_with([
+            game(101, "A", &["Zeta", "Mid"]),
+            game(102, "B", &["Zeta"]),
+            game(103, "C", &["Alpha", "Omega"]),
+            game(104, "D", &["Alpha"]),
+        ]);
+        let genres = data
+            .most_common(|meta| meta.genres.iter(), |genre| genre.name.as_str())
+            .into_iter()
+            .map(|(count, genre)| (count, genre.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            genres,
+            [(2, "Alpha"), (2, "Zeta"), (1, "Mid"), (1, "Omega")]
+        );
+    }
+
+    #[test]
+    fn extrema_break_duration_ties_by_id() {
+        let data = fixture::data();
+        // 103 and 105 each topped two lists, 101 one, and the latest list isn't counted
+        assert_eq!(
+            data.extrema(true, 5),
+            [
+                (&GameId::Igdb(103), Duration::days(28)),
+                (&GameId::Igdb(105), Duration::days(28)),
+                (&GameId::Igdb(101), Duration::days(14)),
+            ]
+        );
+        assert_eq!(
+            data.extrema(false, 5),
+            [
+                (&GameId::Igdb(102), Duration::days(42)),
+                (&GameId::Igdb(101), Duration::days(14)),
+                (&GameId::Other("PAPERCRAFT".into()), Duration::days(14)),
+            ]
+        );
+        assert_eq!(
+            data.extrema(true, 2),
+            [
+                (&GameId::Igdb(103), Duration::days(28)),
+                (&GameId::Igdb(105), Duration::days(28)),
+            ]
+        );
+    }
+
+    #[test]
+    fn igdb_list_leaves_out_games_below_min_rating_count() {
+        let game = |id, name, rating, rating_count| {
+            let mut meta = fixture::meta(id, name);
+            meta.rating = Some(rating);
+            meta.rating_count = rating_count;
+            meta
+        };
+        let data = fixture::data_with([
+            game(101, "Nine", 90.0, Some(9)),
+            game(102, "Ten", 80.0, Some(10)),
+            game(103, "Eleven", 70.0, Some(11)),
+            game(104, "Uncounted", 60.0, None),
+        ])
+        .with_min_rating_count(10);
+        let names = |weighting| {
+            data.igdb_list(RatingKind::User, weighting)
+                .into_iter()
+                .map(|(_, meta)| meta.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(RatingWeighting::Raw), ["Ten", "Eleven"]);
+        assert_eq!(names(RatingWeighting::Bayesian), ["Ten", "Eleven"]);
+        assert_eq!(
+            data.with_min_rating_count(0)
+                .igdb_list(RatingKind::User, RatingWeighting::Raw)
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn lists_reject_repeated_dates() {
+        let err =
+            serde_json::from_str::<Lists>(r#"{"2022-01-07": [1], "2022-01-07": [2]}"#).unwrap_err();
+        assert!(err.to_string().contains("duplicate"), "{err}");
+        assert!(
+            migrate::migrate(r#"{"version": 2, "lists": {"2022-01-07": [1], "2022-01-07": [2]}}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn lists_reject_dates_repeated_across_files() {
+        let dir = env::temp_dir().join(format!("tbp-viz-{}-lists", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.json"), r#"{"2022-01-07": [1]}"#).unwrap();
+        fs::write(
+            dir.join("b.json"),
+            r#"{"2022-01-07": [1], "2022-01-14": [2]}"#,
+        )
+        .unwrap();
+
+        let res = Lists::load(&dir.join("*.json").to_string_lossy());
+        fs::remove_dir_all(&dir).unwrap();
+        let err = res.unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("List for 2022-01-07 is defined in both"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn credentials_name_what_is_missing() {
+        assert_eq!(
+            credentials(Some("id"), Some("secret"), "test").unwrap(),
+            ("id", "secret")
+        );
+        for (client_id, client_secret, missing) in [
+            (None, None, "neither a client ID nor a client secret"),
+            (None, Some("secret"), "no client ID"),
+            (Some("id"), None, "no client secret"),
+        ] {
+            assert_eq!(
+                credentials(client_id, client_secret, "test")
+                    .unwrap_err()
+                    .to_string(),
+                format!("IGDB credentials are needed to test, but {missing} was given")
+            );
+        }
+    }
+
+    /// Config loading the lists of the fixture, with metadata stored in `meta_store`
+    fn fixture_config(meta_store: MetaStoreLocation) -> Config {
+        Config {
+            lists_pattern: concat!(env!("CARGO_MANIFEST_DIR"), "/src/data/fixture/list.json")
+                .into(),
+            meta_store,
+            skip_fetch: false,
+            igdb_batch_size: 10,
+            refresh_metas: false,
+            igdb_client_id: None,
+            igdb_client_secret: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn loads_cached_metas_without_credentials() {
+        let config = fixture_config(MetaStoreLocation::Json(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/src/data/fixture/meta.json").into(),
+        ));
+        let data = Data::new_with_config(
+            Client::new(),
+            &config,
+            ResourceRequestor::new(Client::new(), true, 1),
+        )
+        .await
+        .unwrap();
+        assert_eq!(data.metas.0.len(), fixture::data().metas.0.len());
+        assert!(
+            data.lists
+                .latest()
+                .unwrap()
+                .0
+                .iter()
+                .all(|id| data.metas.0.contains_key(id))
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_metas_need_credentials() {
+        let dir = env::temp_dir().join(format!("tbp-viz-{}-no-metas", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let store = MetaStoreLocation::Json(dir.join("meta.json"));
+        // Only the metadata which can't be downloaded
+        let mut metas = fixture::data().metas;
+        metas.0.retain(|id, _| !matches!(id, GameId::Igdb(_)));
+        store
+            .open()
+            .unwrap()
+            .upsert_many(&metas, &FetchTimes::new())
+            .unwrap();
+
+        let res = Data::new_with_config(
+            Client::new(),
+            &fixture_config(store),
+            ResourceRequestor::new(Client::new(), true, 1),
+        )
+        .await;
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "IGDB credentials are needed to download the metadata of 6 games, but neither a \
+             client ID nor a client secret was given"
+        );
+    }
+
+    #[test]
+    fn recurrences_count_stints() {
+        let mut data = fixture::data();
+        // 102 is off only the 4th list
+        assert_eq!(
+            data.recurrences(),
+            [
+                (GameId::Igdb(102), 2),
+                (GameId::Igdb(101), 1),
+                (GameId::Igdb(103), 1),
+                (GameId::Igdb(104), 1),
+                (GameId::Igdb(105), 1),
+                (GameId::Igdb(106), 1),
+                (GameId::Other("PAPERCRAFT".into()), 1),
+            ]
+        );
+
+        data.lists = serde_json::from_str(
+            r#"{
+                "2022-01-07": [1, 2, 3],
+                "2022-01-14": [2, 3],
+                "2022-01-21": [1, 3],
+                "2022-01-28": [3],
+                "2022-02-04": [1, 2, 3]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            data.recurrences(),
+            [
+                (GameId::Igdb(1), 3),
+                (GameId::Igdb(2), 2),
+                (GameId::Igdb(3), 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Extrapolation of the positions games will take on the next list
+//!
+//! Each game's next move is predicted as an exponentially weighted average of its moves over the
+//! most recent lists, and the uncertainty of that prediction as the weighted average of how far
+//! each of those moves strayed from the prediction made before it.
+//!
+//! This is a toy, not a forecast. Games mostly move because a new game was placed above them, so
+//! the model only captures how often that has recently happened, not where the next game will
+//! land. Every game is predicted on its own, so several may share a predicted position, and the
+//! next list is assumed to be as long as the latest one. Games new to the latest list have no
+//! moves to go by, so they are predicted to stay put with no uncertainty at all.
+
+use super::{GameId, List};
+
+/// Weight of the most recent move, the rest being spread over the earlier ones
+const SMOOTHING: f64 = 0.5;
+/// Number of lists, counting back from the latest, whose moves are considered
+const HISTORY: usize = 8;
+
+/// Predicted position on the next list of every game of the latest of the chronological
+/// `lists`, in its order, along with the half-width of the prediction's confidence band, both
+/// measured in positions
+#[must_use]
+pub fn predict(lists: &[&List]) -> Vec<(GameId, f64, f64)> {
+    let Some(latest) = lists.last() else {
+        return Vec::new();
+    };
+    let max_position = latest.0.len().saturating_sub(1) as f64;
+    let recent = &lists[lists.len().saturating_sub(HISTORY)..];
+    latest
+        .0
+        .iter()
+        .enumerate()
+        .map(|(current, id)| {
+            let positions = recent
+                .iter()
+                .filter_map(|list| list.0.iter().position(|x| x == id))
+                .collect::<Vec<_>>();
+            let (mut trend, mut confidence) = (0.0, 0.0);
+            for pair in positions.windows(2) {
+                let step = pair[1] as f64 - pair[0] as f64;
+                confidence =
+                    SMOOTHING.mul_add((step - trend).abs(), (1.0 - SMOOTHING) * confidence);
+                trend = SMOOTHING.mul_add(step, (1.0 - SMOOTHING) * trend);
+            }
+            (
+                id.clone(),
+                (current as f64 + trend).clamp(0.0, max_position),
+                confidence,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(ids: &[u32]) -> List {
+        List(ids.iter().copied().map(GameId::Igdb).collect())
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn predicts_steady_riser_and_stable_game() {
+        let lists = [
+            list(&[1, 2, 3, 4, 5]),
+            list(&[1, 2, 3, 5, 4]),
+            list(&[1, 2, 5, 3, 4]),
+            list(&[1, 5, 2, 3, 4]),
+        ];
+        let predictions = predict(&lists.iter().collect::<Vec<_>>());
+        let ids = predictions
+            .iter()
+            .map(|(id, _, _)| id.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, lists[3].0);
+
+        let (_, position, confidence) = &predictions[0];
+        assert_close(*position, 0.0);
+        assert_close(*confidence, 0.0);
+
+        // Moves of -1, -1, -1 smoothed towards -1, with the band narrowing as they repeat
+        let (_, position, confidence) = &predictions[1];
+        assert_close(*position, 1.0 - 0.875);
+        assert_close(*confidence, 0.375);
+    }
+
+    #[test]
+    fn keeps_new_games_in_place_and_within_the_list() {
+        let lists = [list(&[1, 2]), list(&[2, 1, 3])];
+        let predictions = predict(&lists.iter().collect::<Vec<_>>());
+        let (_, position, confidence) = &predictions[0];
+        assert_close(*position, 0.0);
+        assert_close(*confidence, 0.5);
+        let (_, position, confidence) = &predictions[2];
+        assert_close(*position, 2.0);
+        assert_close(*confidence, 0.0);
+        assert!(predict(&[]).is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+//! Descriptive statistics
+
+/// Minimum, lower quartile, median, upper quartile, and maximum of a sample
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quartiles {
+    pub min: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub max: f64,
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        f64::midpoint(sorted[mid - 1], sorted[mid])
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Quartiles using the median-of-halves method, excluding the median from both halves for odd lengths
+pub fn quartiles(values: &[f64]) -> Option<Quartiles> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let half = sorted.len() / 2;
+    let (lower, upper) = if sorted.len() == 1 {
+        (&sorted[..], &sorted[..])
+    } else {
+        (&sorted[..half], &sorted[sorted.len() - half..])
+    };
+
+    Some(Quartiles {
+        min: sorted[0],
+        q1: median(lower),
+        median: median(&sorted),
+        q3: median(upper),
+        max: sorted[sorted.len() - 1],
+    })
+}
+
+/// Bayesian average of a mean over `count` samples, as if `prior_weight` extra samples at
+/// `prior_mean` had been observed
+///
+/// Means with few samples are pulled towards `prior_mean`, and without samples it is returned as is.
+pub fn bayesian_average(mean: f64, count: u32, prior_mean: f64, prior_weight: f64) -> f64 {
+    let count = f64::from(count);
+    if count + prior_weight <= 0.0 {
+        return prior_mean;
+    }
+    count.mul_add(mean, prior_weight * prior_mean) / (count + prior_weight)
+}
+
+/// Slope of the least squares line through `points`, or `None` unless they have at least two
+/// distinct x coordinates
+pub fn linear_slope(points: &[(f64, f64)]) -> Option<f64> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (covariance, variance) = points.iter().fold((0.0, 0.0), |(cov, var), (x, y)| {
+        (
+            (x - mean_x).mul_add(y - mean_y, cov),
+            (x - mean_x).mul_add(x - mean_x, var),
+        )
+    });
+    (variance > 0.0).then(|| covariance / variance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quartiles_of_odd_length() {
+        assert_eq!(
+            quartiles(&[7.0, 1.0, 9.0, 3.0, 5.0]),
+            Some(Quartiles {
+                min: 1.0,
+                q1: 2.0,
+                median: 5.0,
+                q3: 8.0,
+                max: 9.0,
+            })
+        );
+    }
+
+    #[test]
+    fn quartiles_of_even_length() {
+        assert_eq!(
+            quartiles(&[8.0, 3.0, 1.0, 6.0, 2.0, 7.0, 5.0, 4.0]),
+            Some(Quartiles {
+                min: 1.0,
+                q1: 2.5,
+                median: 4.5,
+                q3: 6.5,
+                max: 8.0,
+            })
+        );
+    }
+
+    #[test]
+    fn quartiles_of_short_samples() {
+        assert_eq!(quartiles(&[]), None);
+        assert_eq!(
+            quartiles(&[4.0]),
+            Some(Quartiles {
+                min: 4.0,
+                q1: 4.0,
+                median: 4.0,
+                q3: 4.0,
+                max: 4.0,
+            })
+        );
+        assert_eq!(
+            quartiles(&[6.0, 2.0]),
+            Some(Quartiles {
+                min: 2.0,
+                q1: 2.0,
+                median: 4.0,
+                q3: 6.0,
+                max: 6.0,
+            })
+        );
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn bayesian_average_weighs_mean_against_prior() {
+        assert_close(bayesian_average(90.0, 10, 70.0, 10.0), 80.0);
+        assert_close(bayesian_average(90.0, 30, 70.0, 10.0), 85.0);
+        assert_close(bayesian_average(90.0, 10, 70.0, 0.0), 90.0);
+        assert!((bayesian_average(90.0, u32::MAX, 70.0, 10.0) - 90.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bayesian_average_without_samples_is_prior() {
+        assert_close(bayesian_average(90.0, 0, 70.0, 10.0), 70.0);
+        assert_close(bayesian_average(90.0, 0, 70.0, 0.0), 70.0);
+    }
+}
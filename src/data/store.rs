@@ -0,0 +1,392 @@
+//! Storage of downloaded metadata
+//!
+//! Metadata is stored either as a single JSON file, rewritten whole on every change, or as a
+//! `SQLite` database with a row per game.
+
+use core::fmt;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{Context, Error, Result, anyhow};
+use rusqlite::{Connection, OptionalExtension, params};
+use time::OffsetDateTime;
+
+use super::{GameId, Meta, Metas};
+
+const META_FILENAME: &str = "meta.json";
+const META_TEMPLATE_FILENAME: &str = "meta_template.json";
+
+/// When the metadata of games was fetched, keyed by their id
+pub type FetchTimes = HashMap<GameId, OffsetDateTime>;
+
+/// Storage of the metadata of games, keyed by their id
+pub trait MetaStore {
+    /// Metadata of every stored game
+    fn load_all(&self) -> Result<Metas>;
+
+    /// Metadata of the game `id`, if it is stored
+    fn get(&self, id: &GameId) -> Result<Option<Meta>>;
+
+    /// When the metadata of every stored game was fetched, if the store records it
+    fn fetch_times(&self) -> Result<FetchTimes>;
+
+    /// Stores `metas`, replacing the metadata already stored for any of their games
+    ///
+    /// Games are recorded as fetched at their time in `fetch_times`, or now if they have none.
+    fn upsert_many(&mut self, metas: &Metas, fetch_times: &FetchTimes) -> Result<()>;
+}
+
+/// Kind and path of a metadata store, given as `<KIND>:<PATH>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetaStoreLocation {
+    Json(PathBuf),
+    Sqlite(PathBuf),
+}
+
+impl MetaStoreLocation {
+    /// Opens the store, creating it if it doesn't exist
+    pub fn open(&self) -> Result<Box<dyn MetaStore>> {
+        Ok(match self {
+            Self::Json(path) => Box::new(JsonMetaStore::new(path)),
+            Self::Sqlite(path) => Box::new(SqliteMetaStore::open(path)?),
+        })
+    }
+}
+
+impl Default for MetaStoreLocation {
+    fn default() -> Self {
+        Self::Json(META_FILENAME.into())
+    }
+}
+
+impl fmt::Display for MetaStoreLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(path) => write!(f, "json:{}", path.display()),
+            Self::Sqlite(path) => write!(f, "sqlite:{}", path.display()),
+        }
+    }
+}
+
+impl FromStr for MetaStoreLocation {
+    type Err = Error;
+
+    /// Parses `json:<PATH>` or `sqlite:<PATH>`
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            Some(("json", path)) if !path.is_empty() => Ok(Self::Json(path.into())),
+            Some(("sqlite", path)) if !path.is_empty() => Ok(Self::Sqlite(path.into())),
+            _ => Err(anyhow!(
+                "\"{s}\" is not of the form json:<PATH> or sqlite:<PATH>"
+            )),
+        }
+    }
+}
+
+/// Copies the metadata of every game in `from` into `to`, along with when it was fetched,
+/// returning the number of games copied
+pub fn copy(from: &MetaStoreLocation, to: &MetaStoreLocation) -> Result<usize> {
+    let from = from.open()?;
+    let metas = from.load_all()?;
+    to.open()?.upsert_many(&metas, &from.fetch_times()?)?;
+    Ok(metas.0.len())
+}
+
+/// Metadata of every game as an array in a single JSON file
+///
+/// A missing file is started from `meta_template.json` if that exists.
+#[derive(Debug)]
+pub struct JsonMetaStore {
+    path: PathBuf,
+}
+
+impl JsonMetaStore {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl MetaStore for JsonMetaStore {
+    fn load_all(&self) -> Result<Metas> {
+        if !fs::exists(&self.path)? {
+            if !fs::exists(META_TEMPLATE_FILENAME)? {
+                return Ok(Metas::default());
+            }
+            fs::copy(META_TEMPLATE_FILENAME, &self.path)?;
+        }
+        let metas = fs::read_to_string(&self.path)
+            .with_context(|| format!("Could not read {}", self.path.display()))?;
+        Ok(serde_json::from_str(&metas)?)
+    }
+
+    fn get(&self, id: &GameId) -> Result<Option<Meta>> {
+        Ok(self.load_all()?.0.remove(id))
+    }
+
+    /// Not recorded, so always empty
+    fn fetch_times(&self) -> Result<FetchTimes> {
+        Ok(FetchTimes::new())
+    }
+
+    fn upsert_many(&mut self, metas: &Metas, _fetch_times: &FetchTimes) -> Result<()> {
+        let stored = self.load_all()?;
+        // Written in the same form as `Metas`, an array of every game's metadata
+        let all = stored
+            .0
+            .values()
+            .filter(|meta| !metas.0.contains_key(&meta.id))
+            .chain(metas.0.values())
+            .collect::<Vec<_>>();
+        fs::write(&self.path, serde_json::to_string_pretty(&all)?)?;
+        Ok(())
+    }
+}
+
+/// Metadata of every game as a row of a `SQLite` database, holding its metadata as JSON alongside
+/// its name and when it was stored
+#[derive(Debug)]
+pub struct SqliteMetaStore {
+    conn: Connection,
+}
+
+impl SqliteMetaStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn =
+            Connection::open(path).with_context(|| format!("Could not open {}", path.display()))?;
+        // Ids are keyed by their JSON, which tells IGDB ids apart from other ids of the same digits
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS metas (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                fetched_at INTEGER NOT NULL,
+                meta TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS metas_name ON metas (name);
+            CREATE INDEX IF NOT EXISTS metas_fetched_at ON metas (fetched_at);",
+        )?;
+        Ok(Self { conn })
+    }
+}
+
+impl MetaStore for SqliteMetaStore {
+    fn load_all(&self) -> Result<Metas> {
+        let mut stmt = self.conn.prepare("SELECT meta FROM metas")?;
+        let metas = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .map(|meta| {
+                let meta: Meta = serde_json::from_str(&meta?)?;
+                Ok((meta.id.clone(), meta))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Metas(metas))
+    }
+
+    fn get(&self, id: &GameId) -> Result<Option<Meta>> {
+        self.conn
+            .query_row(
+                "SELECT meta FROM metas WHERE id = ?1",
+                [serde_json::to_string(id)?],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .map(|meta| Ok(serde_json::from_str(&meta)?))
+            .transpose()
+    }
+
+    fn fetch_times(&self) -> Result<FetchTimes> {
+        let mut stmt = self.conn.prepare("SELECT id, fetched_at FROM metas")?;
+        stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?
+        .map(|row| {
+            let (id, fetched_at) = row?;
+            Ok((
+                serde_json::from_str(&id)?,
+                OffsetDateTime::from_unix_timestamp(fetched_at)?,
+            ))
+        })
+        .collect()
+    }
+
+    fn upsert_many(&mut self, metas: &Metas, fetch_times: &FetchTimes) -> Result<()> {
+        let now = OffsetDateTime::now_utc();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO metas (id, name, fetched_at, meta) VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT (id) DO UPDATE SET
+                    name = excluded.name,
+                    fetched_at = excluded.fetched_at,
+                    meta = excluded.meta",
+            )?;
+            for meta in metas.0.values() {
+                stmt.execute(params![
+                    serde_json::to_string(&meta.id)?,
+                    meta.name,
+                    fetch_times.get(&meta.id).unwrap_or(&now).unix_timestamp(),
+                    serde_json::to_string(meta)?,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use serde_json::json;
+
+    use super::*;
+
+    /// Path in the temporary directory unique to this process and `name`, removed when dropped
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let path = env::temp_dir().join(format!("tbp-viz-{}-{name}", std::process::id()));
+            let _ = fs::remove_file(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn meta(id: &GameId, name: &str, rating: f64) -> Meta {
+        serde_json::from_value(json!({
+            "id": id,
+            "first_release_date": 1_600_000_000,
+            "name": name,
+            "total_rating": rating,
+            "total_rating_count": 12,
+        }))
+        .unwrap()
+    }
+
+    fn metas(metas: impl IntoIterator<Item = Meta>) -> Metas {
+        Metas(
+            metas
+                .into_iter()
+                .map(|meta| (meta.id.clone(), meta))
+                .collect(),
+        )
+    }
+
+    fn names(metas: &Metas) -> HashMap<GameId, String> {
+        metas
+            .0
+            .iter()
+            .map(|(id, meta)| (id.clone(), meta.name.clone()))
+            .collect()
+    }
+
+    /// Behaviour shared by every kind of store, starting from an empty `store`
+    fn check_store(store: &mut dyn MetaStore) {
+        assert!(store.load_all().unwrap().0.is_empty());
+        assert!(store.get(&GameId::Igdb(1)).unwrap().is_none());
+
+        store
+            .upsert_many(
+                &metas([
+                    meta(&GameId::Igdb(1), "One", 50.0),
+                    meta(&GameId::Other("1".into()), "Other one", 60.0),
+                ]),
+                &FetchTimes::new(),
+            )
+            .unwrap();
+        store
+            .upsert_many(
+                &metas([
+                    meta(&GameId::Igdb(1), "One, renamed", 75.5),
+                    meta(&GameId::Igdb(2), "Two", 80.0),
+                ]),
+                &FetchTimes::new(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            names(&store.load_all().unwrap()),
+            HashMap::from([
+                (GameId::Igdb(1), "One, renamed".to_string()),
+                (GameId::Other("1".into()), "Other one".to_string()),
+                (GameId::Igdb(2), "Two".to_string()),
+            ])
+        );
+        let one = store.get(&GameId::Igdb(1)).unwrap().unwrap();
+        assert_eq!(one.name, "One, renamed");
+        assert_eq!(one.total_rating, Some(75.5));
+        assert_eq!(
+            store.get(&GameId::Other("1".into())).unwrap().unwrap().name,
+            "Other one"
+        );
+        assert!(store.get(&GameId::Igdb(3)).unwrap().is_none());
+    }
+
+    #[test]
+    fn json_store() {
+        let path = TempPath::new("store.json");
+        // Kept from being started from the template
+        fs::write(&path.0, "[]").unwrap();
+        check_store(&mut JsonMetaStore::new(&path.0));
+    }
+
+    #[test]
+    fn sqlite_store() {
+        let path = TempPath::new("store.db");
+        check_store(&mut SqliteMetaStore::open(&path.0).unwrap());
+    }
+
+    #[test]
+    fn copy_keeps_fetch_times() {
+        let from = TempPath::new("copy-from.db");
+        let to = TempPath::new("copy-to.db");
+        let fetched_at = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        SqliteMetaStore::open(&from.0)
+            .unwrap()
+            .upsert_many(
+                &metas([meta(&GameId::Igdb(1), "One", 50.0)]),
+                &FetchTimes::from([(GameId::Igdb(1), fetched_at)]),
+            )
+            .unwrap();
+
+        let count = copy(
+            &MetaStoreLocation::Sqlite(from.0.clone()),
+            &MetaStoreLocation::Sqlite(to.0.clone()),
+        )
+        .unwrap();
+
+        assert_eq!(count, 1);
+        assert_eq!(
+            SqliteMetaStore::open(&to.0).unwrap().fetch_times().unwrap(),
+            FetchTimes::from([(GameId::Igdb(1), fetched_at)])
+        );
+    }
+
+    #[test]
+    fn parses_locations() {
+        for location in [
+            MetaStoreLocation::Json("meta.json".into()),
+            MetaStoreLocation::Sqlite("dir/meta.db".into()),
+        ] {
+            assert_eq!(
+                location.to_string().parse::<MetaStoreLocation>().unwrap(),
+                location
+            );
+        }
+        assert!("meta.json".parse::<MetaStoreLocation>().is_err());
+        assert!("json:".parse::<MetaStoreLocation>().is_err());
+        assert!("csv:meta.csv".parse::<MetaStoreLocation>().is_err());
+    }
+}
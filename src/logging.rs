@@ -0,0 +1,134 @@
+//! Log output formats, and progress bars drawn below the log
+
+use std::{
+    fmt,
+    io::{self, Write},
+    sync::OnceLock,
+};
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde_json::{Map, Value};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+};
+use tracing_subscriber::{
+    fmt::{FmtContext, FormatEvent, FormatFields, format::Writer},
+    registry::LookupSpan,
+};
+
+const COUNTER_TEMPLATE: &str = "{msg:>10} [{bar:40}] {pos}/{len}";
+const SPINNER_TEMPLATE: &str = "{spinner} {msg}";
+/// Interval at which spinners turn while nothing else is reported
+const SPINNER_TICK: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Progress bars, if drawn
+static BARS: OnceLock<MultiProgress> = OnceLock::new();
+
+/// Draws progress bars onto standard error from now on
+pub fn enable_progress_bars() {
+    BARS.get_or_init(|| MultiProgress::with_draw_target(ProgressDrawTarget::stderr()));
+}
+
+/// Bar counting the completed items of `what`, if progress bars are drawn, starting at none of
+/// none
+pub fn counter(what: &str) -> Option<ProgressBar> {
+    let style = ProgressStyle::with_template(COUNTER_TEMPLATE)
+        .unwrap_or_else(|_| ProgressStyle::default_bar())
+        .progress_chars("=> ");
+    Some(
+        BARS.get()?
+            .add(ProgressBar::new(0).with_style(style))
+            .with_message(what.to_string()),
+    )
+}
+
+/// Spinner showing the state of a task, if progress bars are drawn
+pub fn spinner() -> Option<ProgressBar> {
+    let style = ProgressStyle::with_template(SPINNER_TEMPLATE)
+        .unwrap_or_else(|_| ProgressStyle::default_spinner());
+    let bar = BARS
+        .get()?
+        .add(ProgressBar::new_spinner().with_style(style));
+    bar.enable_steady_tick(SPINNER_TICK);
+    Some(bar)
+}
+
+/// Writer of log lines which hides the progress bars while writing, so that lines aren't drawn
+/// over them
+#[derive(Debug)]
+pub struct SuspendingWriter<W>(pub W);
+
+impl<W: Write> Write for SuspendingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match BARS.get() {
+            Some(bars) => bars.suspend(|| self.0.write(buf)),
+            None => self.0.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Formats each event as a JSON object on its own line
+#[derive(Debug)]
+pub struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        let line = serde_json::json!({
+            "timestamp": OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .map_err(|_| fmt::Error)?,
+            "level": metadata.level().as_str(),
+            "target": metadata.target(),
+            "fields": fields.0,
+        });
+        writeln!(writer, "{line}")
+    }
+}
+
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}").into());
+    }
+}
@@ -8,28 +8,318 @@
     clippy::future_not_send
 )]
 
+mod cli;
 mod data;
+mod logging;
 mod plot;
+mod report;
 mod request;
 
-use std::{fs, sync::Arc};
+use std::{
+    collections::HashSet,
+    env, fs,
+    io::{self, IsTerminal, Write},
+    pin::pin,
+    sync::Arc,
+    time::Duration,
+};
 
-use anyhow::{Error, Result};
-use data::{Data, RatingKind};
-use reqwest::Client;
-use tokio::task::{JoinSet, LocalSet};
-use tracing::Level;
-use tracing_subscriber::FmtSubscriber;
+use anyhow::{Error, Result, anyhow};
+use clap::Parser;
+use cli::{CacheAction, Cli, Command, ListDate, LogFormat, PlotKind};
+use data::{
+    Config, Data, DataLoadEvent, GameId, Lists, RatingKind, RatingWeighting, migrate,
+    store::{self, MetaStoreLocation},
+};
+use logging::SuspendingWriter;
+use plot::{ListOverTimeOptions, Locale, Output, RankingDifferenceOptions, Thumbnails};
+use request::resource::{DownloadEvent, ImageSize, ResourceRequestor};
+use reqwest::{Client, Proxy};
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver},
+    task::{JoinSet, LocalSet},
+};
+use tokio_stream::{Stream, StreamExt};
+use tracing::{Level, info, warn};
+use tracing_subscriber::{FmtSubscriber, fmt::writer::BoxMakeWriter};
 
 macro_rules! spawn_blocking_tasks {
-    ($set:ident, $data:ident, $($f:expr),+) => {
-        $({
+    ($set:ident, $data:ident, $headings:ident, $locale:ident, $thumbnails:ident, $selected:ident, $($kind:expr => $f:expr),+) => {
+        $(if $selected($kind) {
             let $data = $data.clone();
+            let $headings = $headings.clone();
+            // Not every plot draws localized text, or labels games with thumbnails
+            #[allow(unused_variables)]
+            let $locale = $locale.clone();
+            #[allow(unused_variables)]
+            let $thumbnails = $thumbnails.clone();
             $set.spawn_blocking(move || $f);
         })+
     };
 }
 
+/// HTTP client, proxied through `HTTPS_PROXY` and `HTTP_PROXY` if set
+fn client() -> Result<Client> {
+    let mut builder = Client::builder();
+    if let Ok(proxy) = env::var("HTTPS_PROXY") {
+        builder = builder.proxy(Proxy::https(&proxy)?);
+    }
+    if let Ok(proxy) = env::var("HTTP_PROXY") {
+        builder = builder.proxy(Proxy::http(&proxy)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Logs to standard output, or to standard error if `stderr`
+///
+/// Progress is drawn as bars onto standard error if it is a terminal and the log is text, and
+/// only logged otherwise.
+fn init_logging(format: LogFormat, stderr: bool) -> Result<()> {
+    if matches!(format, LogFormat::Text) && io::stderr().is_terminal() {
+        logging::enable_progress_bars();
+    }
+    let builder = FmtSubscriber::builder()
+        .with_max_level(Level::INFO)
+        .with_writer(if stderr {
+            BoxMakeWriter::new(|| SuspendingWriter(io::stderr()))
+        } else {
+            BoxMakeWriter::new(|| SuspendingWriter(io::stdout()))
+        });
+    match format {
+        LogFormat::Text => tracing::subscriber::set_global_default(builder.finish())?,
+        LogFormat::Json => tracing::subscriber::set_global_default(
+            builder.event_format(logging::JsonFormat).finish(),
+        )?,
+    }
+    Ok(())
+}
+
+/// Counts completed image downloads on a progress bar, or logs their number whenever one ends
+async fn report_downloads(mut events: UnboundedReceiver<DownloadEvent>) {
+    let (mut queued, mut finished, mut failed) = (0u32, 0u32, 0u32);
+    // Only shown once there is something to download
+    let mut bar = None;
+    while let Some(event) = events.recv().await {
+        match event {
+            DownloadEvent::Queued => queued += 1,
+            DownloadEvent::Started => continue,
+            DownloadEvent::Finished => finished += 1,
+            DownloadEvent::Failed => failed += 1,
+        }
+        if bar.is_none() {
+            bar = logging::counter("images");
+        }
+        if let Some(bar) = &bar {
+            bar.set_length(queued.into());
+            bar.set_position((finished + failed).into());
+            if failed > 0 {
+                bar.set_message(format!("images, {failed} failed"));
+            }
+        } else if !matches!(event, DownloadEvent::Queued) {
+            info!(
+                queued,
+                finished, failed, "Downloaded {finished} of {queued} images, {failed} failed"
+            );
+        }
+    }
+}
+
+/// Drives `events` of loading the lists and metadata to the loaded data, logging each step and
+/// counting the games whose metadata has been downloaded on a progress bar, or logging their
+/// number
+async fn load_with_progress(events: impl Stream<Item = Result<DataLoadEvent>>) -> Result<Data> {
+    let mut events = pin!(events);
+    let mut pending = HashSet::new();
+    let mut fetched = 0u32;
+    // Only shown once there is something to download
+    let mut bar = None;
+    while let Some(event) = events.next().await {
+        match event? {
+            DataLoadEvent::LoadingLists => info!("Loading lists"),
+            DataLoadEvent::ListsLoaded { count } => info!("Loaded {count} lists"),
+            DataLoadEvent::LoadingMetas => info!("Loading metadata"),
+            DataLoadEvent::MetasLoaded { count } => info!("Loaded metadata of {count} games"),
+            DataLoadEvent::FetchingMeta { id } => {
+                pending.insert(id);
+                if bar.is_none() {
+                    bar = logging::counter("metadata");
+                }
+                if let Some(bar) = &bar {
+                    bar.inc_length(1);
+                }
+            }
+            DataLoadEvent::MetaFetched { id } => {
+                pending.remove(&id);
+                fetched += 1;
+                let remaining = pending.len();
+                if let Some(bar) = &bar {
+                    bar.inc(1);
+                } else {
+                    info!(%id, fetched, remaining, "Downloaded metadata of {id}, {remaining} remaining");
+                }
+            }
+            DataLoadEvent::Done(data) => return Ok(*data),
+        }
+    }
+    Err(anyhow!("Loading ended without any data"))
+}
+
+/// Human-readable size of `bytes`
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64;
+    let mut unit = UNITS[0];
+    for next_unit in UNITS {
+        size /= 1024.0;
+        unit = next_unit;
+        if size < 1024.0 {
+            break;
+        }
+    }
+    format!("{size:.1} {unit}")
+}
+
+fn cache(action: CacheAction, meta_store: &MetaStoreLocation, lists_pattern: &str) -> Result<()> {
+    // Only ever inspects the cache, never downloading anything
+    let res = ResourceRequestor::new(client()?, true, 1);
+    match action {
+        CacheAction::Stats => {
+            let usage = res.cache_usage()?;
+            for (dir, usage) in &usage {
+                let dir = if dir.as_os_str().is_empty() {
+                    ".".to_string()
+                } else {
+                    dir.to_string_lossy().into_owned()
+                };
+                println!("{dir}: {} files, {}", usage.files, format_size(usage.bytes));
+            }
+            println!(
+                "Total: {} files, {}",
+                usage.values().map(|usage| usage.files).sum::<usize>(),
+                format_size(usage.values().map(|usage| usage.bytes).sum())
+            );
+        }
+        CacheAction::Prune {
+            max_age,
+            off_list,
+            dry_run,
+        } => {
+            let mut metas = meta_store.open()?.load_all()?;
+            if off_list {
+                let (lists, _) = Lists::load(lists_pattern)?;
+                let latest_list = lists
+                    .latest()
+                    .ok_or_else(|| anyhow!("Latest list doesn't exist"))?;
+                metas.0.retain(|id, _| latest_list.0.contains(id));
+            }
+            let num_deleted = res.prune_cache(
+                &metas,
+                max_age.map(|days| Duration::from_hours(24) * days),
+                dry_run,
+            )?;
+            if dry_run {
+                info!("Would delete {num_deleted} cached files");
+            } else {
+                info!("Deleted {num_deleted} cached files");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Downloads every image drawn in any plot, failing if any of them can't be obtained
+async fn prefetch(data: &Data) -> Result<()> {
+    let resources = plot::resource_urls(data);
+    let cached = resources
+        .iter()
+        .map(|(size, url)| data.res.is_cached(*size, url))
+        .collect::<Result<Vec<_>>>()?;
+    let results = data.res.get_all(resources.iter().cloned()).await?;
+    let (mut num_cached, mut num_downloaded, mut num_failed) = (0, 0, 0);
+    for (resource, cached) in resources.iter().zip(cached) {
+        match &results[resource] {
+            Ok(_) if cached => num_cached += 1,
+            Ok(_) => num_downloaded += 1,
+            Err(err) => {
+                warn!("Could not fetch {}: {err:#}", resource.1);
+                num_failed += 1;
+            }
+        }
+    }
+    info!(
+        "Prefetched {} images: {num_cached} cached, {num_downloaded} downloaded, {num_failed} failed",
+        resources.len()
+    );
+    if num_failed > 0 {
+        return Err(anyhow!("Could not fetch {num_failed} images"));
+    }
+    Ok(())
+}
+
+/// Loads the lists and metadata, comparing them to the lists in `--compare-lists` if given
+async fn load_data(cli: &Cli) -> Result<Data> {
+    // Credentials are only needed to download missing metadata, so `.env` is optional
+    if let Err(err) = dotenvy::dotenv()
+        && !err.not_found()
+    {
+        return Err(err.into());
+    }
+    let client = client()?;
+    let (download_events, download_events_rx) = mpsc::unbounded_channel();
+    tokio::spawn(report_downloads(download_events_rx));
+    let mut res = ResourceRequestor::new(client.clone(), cli.skip_fetch, cli.download_jobs)
+        .with_events(download_events)
+        .with_image_cache();
+    if let Some(interval) = cli.download_interval_ms {
+        res = res.with_min_interval(Duration::from_millis(interval));
+    }
+    let config = Config {
+        lists_pattern: cli.lists.clone(),
+        meta_store: cli.meta_store.clone(),
+        skip_fetch: cli.skip_fetch,
+        igdb_batch_size: cli.igdb_batch_size,
+        refresh_metas: cli.refresh_metas,
+        igdb_client_id: None,
+        igdb_client_secret: None,
+    };
+    let data = load_with_progress(Data::new_async(client.clone(), config.clone(), res.clone()))
+        .await?
+        .with_min_rating_count(cli.min_rating_count);
+    if let Some(pattern) = &cli.compare_lists {
+        let config = Config {
+            lists_pattern: pattern.clone(),
+            ..config
+        };
+        let other = Data::new_with_config(client, &config, res).await?;
+        compare_lists(&data, &other, pattern);
+    }
+    Ok(data)
+}
+
+/// Logs which games of the latest list are shared with, or missing from, the latest list of `other`
+fn compare_lists(data: &Data, other: &Data, other_pattern: &str) {
+    let name = |data: &Data, id: &GameId| {
+        data.metas
+            .0
+            .get(id)
+            .map_or_else(|| id.to_string(), |meta| meta.name.clone())
+    };
+    let comparison = Data::compare_lists(data, other);
+    info!(
+        "{} games are shared with the lists in {other_pattern}",
+        comparison.shared.len()
+    );
+    for id in comparison.only_a {
+        info!("Only on the list: {}", name(data, id));
+    }
+    for id in comparison.only_b {
+        info!("Only in {other_pattern}: {}", name(other, id));
+    }
+}
+
 pub async fn join_local(mut set: JoinSet<Result<()>>, local_set: LocalSet) -> Result<()> {
     tokio::try_join!(
         async {
@@ -46,39 +336,389 @@ pub async fn join_local(mut set: JoinSet<Result<()>>, local_set: LocalSet) -> Re
     Ok(())
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    tracing::subscriber::set_global_default(
-        FmtSubscriber::builder()
-            .with_max_level(Level::INFO)
-            .finish(),
-    )?;
-    dotenvy::dotenv()?;
-    let client = Client::new();
-    let data = Arc::new(Data::new(client.clone()).await?);
-
-    fs::create_dir_all("out")?;
+/// Writes the PNG of the only visualization selected with `--plots` to standard output
+async fn write_stdout(cli: &Cli, data: Arc<Data>) -> Result<()> {
+    let [kind] = cli.plots[..] else {
+        return Err(anyhow!(
+            "Exactly one visualization must be selected to write to standard output"
+        ));
+    };
+    let size = cli.plot_size();
+    let headings = cli.headings();
+    let locale = cli.locale()?;
+    let mut png = Vec::new();
+    let output = Output::from(&mut png).with_heading(headings.get(kind));
+    match kind {
+        PlotKind::ReleaseDates => {
+            let weight = cli.release_weight;
+            plot::release_dates(
+                output,
+                size,
+                &locale,
+                &data,
+                cli.release_smoothing(),
+                |meta| weight.of(meta),
+            )?;
+        }
+        PlotKind::Coop => plot::coop(output, size, &locale, &data)?,
+        PlotKind::EraBreakdown => plot::era_breakdown(output, size, &locale, &data)?,
+        PlotKind::BubbleChart => plot::bubble_chart(output, size, &locale, &data)?,
+        PlotKind::GenerationPositions => plot::generation_positions(output, size, &locale, &data)?,
+        PlotKind::GenreTrend => plot::genre_trend(output, size, &locale, &data)?,
+        PlotKind::Summary => {
+            plot::summary(output, size, &cli.summary_segments, &locale, data).await?;
+        }
+        PlotKind::EpisodeSummary => plot::episode_summary(output, size, &locale, data).await?,
+        PlotKind::ListOverTime | PlotKind::RankingDifference => {
+            return Err(anyhow!(
+                "{kind:?} consists of several images, which can't be written to standard output"
+            ));
+        }
+    }
+    io::stdout().write_all(&png)?;
+    Ok(())
+}
 
-    let mut plots = JoinSet::new();
-    let local_plots = LocalSet::new();
+/// Spawns the plots which are drawn synchronously onto `plots`
+#[allow(clippy::too_many_lines)]
+fn spawn_blocking_plots(
+    cli: &Cli,
+    data: &Arc<Data>,
+    locale: &Arc<Locale>,
+    thumbnails: Option<&Arc<Thumbnails>>,
+    plots: &mut JoinSet<Result<()>>,
+) {
+    let size = cli.plot_size();
+    let selected = |kind| cli.selected(kind);
+    let release_smoothing = cli.release_smoothing();
+    let release_weight = cli.release_weight;
+    // Thumbnails and the highlighted game are filled in by each plot, which borrows them from its
+    // own clones
+    let list_over_time_options = ListOverTimeOptions {
+        size,
+        scale: false,
+        labels: cli.episode_labels(),
+        stroke_width: cli.stroke_width,
+        mark_extremes: cli.mark_extremes,
+        predict: cli.predict,
+        thumbnails: None,
+        highlight: None,
+    };
+    let ranking_difference_options = RankingDifferenceOptions {
+        size,
+        kind: RatingKind::User,
+        weighting: RatingWeighting::Raw,
+        distinct_colors: cli.distinct_difference_colors,
+        stroke_width: cli.stroke_width,
+        thumbnails: None,
+    };
+    let headings = Arc::new(cli.headings());
+    let thumbnails = thumbnails.cloned();
 
     spawn_blocking_tasks!(
         plots,
         data,
-        plot::list_over_time("out/list_over_time_scaled.png", true, &data),
-        plot::list_over_time("out/list_over_time.png", false, &data),
-        plot::release_dates("out/release_dates.png", &data),
-        plot::ranking_difference("out/rating_differences_user.png", RatingKind::User, &data),
-        plot::ranking_difference(
-            "out/rating_differences_critic.png",
-            RatingKind::Critic,
+        headings,
+        locale,
+        thumbnails,
+        selected,
+        PlotKind::ListOverTime => plot::list_over_time(
+            headings.output(PlotKind::ListOverTime, "out/list_over_time_scaled.png"),
+            &ListOverTimeOptions {
+                scale: true,
+                thumbnails: thumbnails.as_deref(),
+                ..list_over_time_options
+            },
+            &locale,
+            &data
+        ),
+        PlotKind::ListOverTime => plot::list_over_time(
+            headings.output(PlotKind::ListOverTime, "out/list_over_time.png"),
+            &ListOverTimeOptions {
+                scale: false,
+                thumbnails: thumbnails.as_deref(),
+                ..list_over_time_options
+            },
+            &locale,
+            &data
+        ),
+        PlotKind::ListOverTime => plot::list_over_time_legend(
+            headings.output(PlotKind::ListOverTime, "out/list_over_time_legend.png"),
+            size,
+            list_over_time_options.stroke_width,
+            &data
+        ),
+        PlotKind::ReleaseDates => plot::release_dates(
+            headings.output(PlotKind::ReleaseDates, "out/release_dates.png"),
+            size,
+            &locale,
+            &data,
+            release_smoothing,
+            |meta| release_weight.of(meta)
+        ),
+        PlotKind::Coop =>
+            plot::coop(
+            headings.output(PlotKind::Coop, "out/coop.png"),
+            size,
+            &locale,
+            &data
+        ),
+        PlotKind::EraBreakdown => plot::era_breakdown(
+            headings.output(PlotKind::EraBreakdown, "out/era_breakdown.png"),
+            size,
+            &locale,
+            &data
+        ),
+        PlotKind::BubbleChart => plot::bubble_chart(
+            headings.output(PlotKind::BubbleChart, "out/bubble_chart.png"),
+            size,
+            &locale,
+            &data
+        ),
+        PlotKind::GenerationPositions => plot::generation_positions(
+            headings.output(PlotKind::GenerationPositions, "out/generation_positions.png"),
+            size,
+            &locale,
+            &data
+        ),
+        PlotKind::GenreTrend => plot::genre_trend(
+            headings.output(PlotKind::GenreTrend, "out/genre_trend.png"),
+            size,
+            &locale,
+            &data
+        ),
+        PlotKind::RankingDifference => plot::ranking_difference(
+            Some(headings.output(
+                PlotKind::RankingDifference,
+                &plot::ranking_difference_path(RatingKind::User, RatingWeighting::Raw)
+            )),
+            &RankingDifferenceOptions {
+                kind: RatingKind::User,
+                weighting: RatingWeighting::Raw,
+                thumbnails: thumbnails.as_deref(),
+                ..ranking_difference_options
+            },
+            &locale,
+            &data
+        ),
+        PlotKind::RankingDifference => plot::ranking_difference(
+            Some(headings.output(
+                PlotKind::RankingDifference,
+                &plot::ranking_difference_path(RatingKind::Critic, RatingWeighting::Raw)
+            )),
+            &RankingDifferenceOptions {
+                kind: RatingKind::Critic,
+                weighting: RatingWeighting::Raw,
+                thumbnails: thumbnails.as_deref(),
+                ..ranking_difference_options
+            },
+            &locale,
+            &data
+        ),
+        PlotKind::RankingDifference => plot::ranking_difference(
+            Some(headings.output(
+                PlotKind::RankingDifference,
+                &plot::ranking_difference_path(RatingKind::Total, RatingWeighting::Raw)
+            )),
+            &RankingDifferenceOptions {
+                kind: RatingKind::Total,
+                weighting: RatingWeighting::Raw,
+                thumbnails: thumbnails.as_deref(),
+                ..ranking_difference_options
+            },
+            &locale,
+            &data
+        ),
+        PlotKind::RankingDifference => plot::ranking_difference(
+            Some(headings.output(
+                PlotKind::RankingDifference,
+                &plot::ranking_difference_path(RatingKind::Total, RatingWeighting::Bayesian)
+            )),
+            &RankingDifferenceOptions {
+                kind: RatingKind::Total,
+                weighting: RatingWeighting::Bayesian,
+                thumbnails: thumbnails.as_deref(),
+                ..ranking_difference_options
+            },
+            &locale,
             &data
         )
     );
-    plots.spawn_local_on(
-        async move { plot::summary("out/summary.png", data).await },
-        &local_plots,
-    );
+    if let Some(highlight) = cli.highlight.clone()
+        && cli.selected(PlotKind::ListOverTime)
+    {
+        let data = data.clone();
+        let locale = locale.clone();
+        plots.spawn_blocking(move || {
+            plot::list_over_time(
+                headings.output(
+                    PlotKind::ListOverTime,
+                    &format!("out/list_over_time_highlight_{highlight}.png"),
+                ),
+                &ListOverTimeOptions {
+                    thumbnails: thumbnails.as_deref(),
+                    highlight: Some(&highlight),
+                    ..list_over_time_options
+                },
+                &locale,
+                &data,
+            )
+        });
+    }
+}
+
+/// Spawns the plots which need to await images onto `local_plots`
+fn spawn_local_plots(
+    cli: &Cli,
+    data: Arc<Data>,
+    locale: &Arc<Locale>,
+    plots: &mut JoinSet<Result<()>>,
+    local_plots: &LocalSet,
+) -> Result<()> {
+    let size = cli.plot_size();
+    if let Some(list_card) = cli.list_card {
+        let card_date = match list_card {
+            ListDate::Latest => *data
+                .dates()
+                .last()
+                .ok_or_else(|| anyhow!("Latest list doesn't exist"))?,
+            ListDate::Date(card_date) => card_date,
+        };
+        let data = data.clone();
+        let locale = locale.clone();
+        plots.spawn_local_on(
+            async move {
+                let path = format!("out/list_card_{card_date}.png");
+                plot::list_card(&path, card_date, &locale, data).await
+            },
+            local_plots,
+        );
+    }
+    if let Some(frames_per_transition) = cli.race_frames {
+        let data = data.clone();
+        let locale = locale.clone();
+        plots.spawn_local_on(
+            async move {
+                plot::race_frames("out/race", size, &locale, &data, frames_per_transition).await
+            },
+            local_plots,
+        );
+    }
+    let headings = Arc::new(cli.headings());
+    if cli.selected(PlotKind::EpisodeSummary) {
+        let data = data.clone();
+        let headings = headings.clone();
+        let locale = locale.clone();
+        plots.spawn_local_on(
+            async move {
+                plot::episode_summary(
+                    headings.output(PlotKind::EpisodeSummary, "out/episode_summary.png"),
+                    size,
+                    &locale,
+                    data,
+                )
+                .await
+            },
+            local_plots,
+        );
+    }
+    if cli.selected(PlotKind::Summary) {
+        let segments = cli.summary_segments.clone();
+        let locale = locale.clone();
+        plots.spawn_local_on(
+            async move {
+                plot::summary(
+                    headings.output(PlotKind::Summary, "out/summary.png"),
+                    size,
+                    &segments,
+                    &locale,
+                    data,
+                )
+                .await
+            },
+            local_plots,
+        );
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    init_logging(cli.log_format, cli.stdout)?;
+    match cli.command {
+        Some(Command::Cache { action }) => return cache(action, &cli.meta_store, &cli.lists),
+        Some(Command::Migrate) => return migrate::migrate_files(&cli.lists),
+        Some(Command::ShowMeta { ref id }) => {
+            let meta = cli
+                .meta_store
+                .open()?
+                .get(id)?
+                .ok_or_else(|| anyhow!("No metadata stored for \"{id}\""))?;
+            println!("{}", serde_json::to_string_pretty(&meta)?);
+            return Ok(());
+        }
+        Some(Command::ImportMetas { ref from }) => {
+            let count = store::copy(from, &cli.meta_store)?;
+            info!(
+                "Imported metadata of {count} games from {from} into {}",
+                cli.meta_store
+            );
+            return Ok(());
+        }
+        Some(Command::ExportMetas { ref to }) => {
+            let count = store::copy(&cli.meta_store, to)?;
+            info!(
+                "Exported metadata of {count} games from {} into {to}",
+                cli.meta_store
+            );
+            return Ok(());
+        }
+        _ => {}
+    }
+    let data = Arc::new(load_data(&cli).await?);
+    if matches!(cli.command, Some(Command::Prefetch)) {
+        return prefetch(&data).await;
+    }
+
+    if cli.preload {
+        for size in ImageSize::ALL {
+            data.res.preload_all(&data, size).await?;
+        }
+    }
+
+    if cli.stdout {
+        return write_stdout(&cli, data).await;
+    }
+
+    fs::create_dir_all("out")?;
+
+    if cli.markdown_report {
+        fs::write(
+            report::MARKDOWN_REPORT_FILENAME,
+            report::generate_markdown(&data)?,
+        )?;
+        info!("Generated report {}", report::MARKDOWN_REPORT_FILENAME);
+    }
+    if cli.html_report {
+        fs::write(
+            report::HTML_REPORT_FILENAME,
+            report::generate_html(&data, &data.res).await?,
+        )?;
+        info!("Generated report {}", report::HTML_REPORT_FILENAME);
+    }
+
+    let mut plots = JoinSet::new();
+    let local_plots = LocalSet::new();
+
+    let locale = Arc::new(cli.locale()?);
+    let thumbnails = if cli.cover_labels {
+        Some(Arc::new(Thumbnails::fetch(&data).await?))
+    } else {
+        None
+    };
+    spawn_blocking_plots(&cli, &data, &locale, thumbnails.as_ref(), &mut plots);
+    spawn_local_plots(&cli, data, &locale, &mut plots, &local_plots)?;
 
     join_local(plots, local_plots).await?;
 
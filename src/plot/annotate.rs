@@ -0,0 +1,230 @@
+//! Labeled callouts pointing at points of a plot
+
+use std::{
+    f64::consts::FRAC_PI_2,
+    iter::{self, Once},
+    result,
+};
+
+use plotters::{
+    element::{BackendCoordOnly, CoordMapper, Drawable, PointCollection},
+    style::{IntoTextStyle, ShapeStyle},
+};
+use plotters_backend::{
+    BackendCoord, DrawingBackend, DrawingErrorKind,
+    text_anchor::{HPos, Pos, VPos},
+};
+
+use super::{color::Color, font::Font, size::Scale, text};
+
+const LEADER_LENGTH: u32 = 48;
+const PADDING: u32 = 8;
+const CORNER_RADIUS: u32 = 8;
+const CORNER_SEGMENTS: usize = 4;
+
+/// Side of the target on which an annotation's text is placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    const fn flipped(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
+/// Text in a rounded box, with a leader line to `target`
+#[derive(Debug)]
+pub struct Annotation<Coord> {
+    target: Coord,
+    text: String,
+    anchor: Direction,
+    font_size: u32,
+    leader_length: u32,
+    padding: u32,
+    corner_radius: u32,
+    /// Vertical offset of the box, moving it out of the way of other annotations
+    nudge: i32,
+}
+
+impl<Coord> Annotation<Coord> {
+    pub fn new(target: Coord, text: String, anchor: Direction, scale: Scale) -> Self {
+        Self {
+            target,
+            text,
+            anchor,
+            font_size: scale.text(Font::DEFAULT_SIZE),
+            leader_length: scale.px(LEADER_LENGTH),
+            padding: scale.px(PADDING),
+            corner_radius: scale.px(CORNER_RADIUS),
+            nudge: 0,
+        }
+    }
+
+    /// Top left and bottom right corners of the box of text measuring `text_size`, with the
+    /// target at `target`, placed towards `anchor`
+    const fn text_box(
+        &self,
+        target: BackendCoord,
+        text_size: (u32, u32),
+        anchor: Direction,
+    ) -> (BackendCoord, BackendCoord) {
+        let width = (text_size.0 + 2 * self.padding) as i32;
+        let height = (text_size.1 + 2 * self.padding) as i32;
+        let leader = self.leader_length as i32;
+        let (x, y) = (target.0, target.1 + self.nudge);
+        let top_left = match anchor {
+            Direction::Up => (x - width / 2, y - leader - height),
+            Direction::Down => (x - width / 2, y + leader),
+            Direction::Left => (x - leader - width, y - height / 2),
+            Direction::Right => (x + leader, y - height / 2),
+        };
+        (top_left, (top_left.0 + width, top_left.1 + height))
+    }
+
+    /// Approximate size of the text, before it can be measured by a backend
+    fn estimated_text_size(&self) -> (u32, u32) {
+        (
+            text::estimated_width(&self.text, self.font_size),
+            self.font_size,
+        )
+    }
+}
+
+/// Nudges `annotations` vertically, in order of their targets' heights, so that their boxes
+/// don't overlap. `position` maps targets to pixels.
+pub fn spread<Coord, F>(annotations: &mut [Annotation<Coord>], position: F)
+where
+    F: Fn(&Coord) -> BackendCoord,
+{
+    annotations.sort_by_key(|annotation| position(&annotation.target).1);
+    let mut placed: Vec<(BackendCoord, BackendCoord)> = Vec::new();
+    for annotation in annotations {
+        let target = position(&annotation.target);
+        let text_size = annotation.estimated_text_size();
+        loop {
+            let (top_left, bottom_right) =
+                annotation.text_box(target, text_size, annotation.anchor);
+            let Some(overlap) = placed
+                .iter()
+                .filter(|(other_top_left, other_bottom_right)| {
+                    top_left.0 < other_bottom_right.0
+                        && other_top_left.0 < bottom_right.0
+                        && top_left.1 < other_bottom_right.1
+                        && other_top_left.1 < bottom_right.1
+                })
+                .map(|(_, other_bottom_right)| other_bottom_right.1 - top_left.1)
+                .max()
+            else {
+                placed.push((top_left, bottom_right));
+                break;
+            };
+            annotation.nudge += overlap;
+        }
+    }
+}
+
+/// Corners of a rectangle with rounded corners, clockwise from the top left
+fn rounded_rectangle(
+    (left, top): BackendCoord,
+    (right, bottom): BackendCoord,
+    radius: u32,
+) -> Vec<BackendCoord> {
+    let radius = (radius as i32)
+        .min((right - left) / 2)
+        .min((bottom - top) / 2);
+    [
+        (left + radius, top + radius, 2.0),
+        (right - radius, top + radius, 3.0),
+        (right - radius, bottom - radius, 0.0),
+        (left + radius, bottom - radius, 1.0),
+    ]
+    .into_iter()
+    .flat_map(|(x, y, quadrant)| {
+        (0..=CORNER_SEGMENTS).map(move |i| {
+            let angle = (quadrant + i as f64 / CORNER_SEGMENTS as f64) * FRAC_PI_2;
+            (
+                x + (f64::from(radius) * angle.cos()).round() as i32,
+                y + (f64::from(radius) * angle.sin()).round() as i32,
+            )
+        })
+    })
+    .collect()
+}
+
+impl<DB, Coord> Drawable<DB> for Annotation<Coord>
+where
+    DB: DrawingBackend,
+{
+    fn draw<I>(
+        &self,
+        mut pos: I,
+        backend: &mut DB,
+        parent_dim: (u32, u32),
+    ) -> result::Result<(), DrawingErrorKind<<DB as DrawingBackend>::ErrorType>>
+    where
+        I: Iterator<Item = <BackendCoordOnly as CoordMapper>::Output>,
+    {
+        let Some(target) = pos.next() else {
+            return Ok(());
+        };
+        let style = Font::new(self.font_size)
+            .with_anchor::<Color>(Pos {
+                h_pos: HPos::Center,
+                v_pos: VPos::Center,
+            })
+            .into_text_style(&parent_dim);
+        let text_size = backend.estimate_text_size(&self.text, &style)?;
+
+        let fits = |(top_left, bottom_right): (BackendCoord, BackendCoord)| {
+            top_left.0 >= 0
+                && top_left.1 >= 0
+                && bottom_right.0 <= parent_dim.0 as i32
+                && bottom_right.1 <= parent_dim.1 as i32
+        };
+        let mut anchor = self.anchor;
+        if !fits(self.text_box(target, text_size, anchor))
+            && fits(self.text_box(target, text_size, anchor.flipped()))
+        {
+            anchor = anchor.flipped();
+        }
+        let (top_left, bottom_right) = self.text_box(target, text_size, anchor);
+        let center = (
+            i32::midpoint(top_left.0, bottom_right.0),
+            i32::midpoint(top_left.1, bottom_right.1),
+        );
+
+        let leader_end = match anchor {
+            Direction::Up => (center.0, bottom_right.1),
+            Direction::Down => (center.0, top_left.1),
+            Direction::Left => (bottom_right.0, center.1),
+            Direction::Right => (top_left.0, center.1),
+        };
+        backend.draw_line(target, leader_end, &ShapeStyle::from(Color::FONT_PRIMARY))?;
+        backend.fill_polygon(
+            rounded_rectangle(top_left, bottom_right, self.corner_radius),
+            &ShapeStyle::from(Color::BG_SECONDARY).filled(),
+        )?;
+        backend.draw_text(&self.text, &style, center)
+    }
+}
+
+impl<'a, Coord> PointCollection<'a, Coord> for &'a Annotation<Coord>
+where
+    Coord: 'a,
+{
+    type Point = &'a Coord;
+    type IntoIter = Once<&'a Coord>;
+    fn point_iter(self) -> Once<&'a Coord> {
+        iter::once(&self.target)
+    }
+}
@@ -1,10 +1,29 @@
+use std::str::FromStr;
+
+use anyhow::{Error, Result, anyhow};
 use image::Rgba;
-use palette::{FromColor, Hsv, Srgb};
+use palette::{FromColor, Hsv, Mix, Srgb};
 use plotters_backend::BackendColor;
+use serde::{Deserialize, Serialize};
+
+const HEAT_MAX_HUE: f64 = 120.0;
+/// Most hues told apart on one pass over the wheel, before repeating them in another shade
+const MAX_HUES: usize = 24;
+/// Saturation and value of each pass over the wheel
+const SHADES: [(f64, f64); 4] = [(1.0, 1.0), (0.5, 1.0), (0.8, 0.85), (0.3, 0.95)];
 
-#[derive(Debug, Clone, Copy)]
+/// Serialized as `"#RRGGBB"`, and deserialized from either that or `[r, g, b]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "ColorRepr", into = "String")]
 pub struct Color(pub u8, pub u8, pub u8);
 
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorRepr {
+    Hex(String),
+    Rgb([u8; 3]),
+}
+
 impl Color {
     pub const FONT_PRIMARY: Self = Self(0xf9, 0xf9, 0xf9);
     pub const BG_PRIMARY: Self = Self(0x4e, 0x2f, 0x63);
@@ -12,14 +31,34 @@ impl Color {
     pub const ACCENT_PINK: Self = Self(0xed, 0x0d, 0x7f);
     pub const ACCENT_BLUE: Self = Self(0x42, 0xbc, 0xec);
     pub const ACCENT_YELLOW: Self = Self(0xfa, 0xe6, 0x16);
+    pub const ACCENTS: [Self; 3] = [Self::ACCENT_PINK, Self::ACCENT_BLUE, Self::ACCENT_YELLOW];
+
+    /// Color on a scale from red at `0.0` to green at `1.0`
+    pub fn heat(fraction: f64) -> Self {
+        Self::from_hsv(HEAT_MAX_HUE * fraction.clamp(0.0, 1.0), 1.0, 1.0)
+    }
+
+    /// Color of a signed `value` out of a largest magnitude of `max`, from `neutral` at zero to
+    /// `negative` or `positive` at the extremes
+    pub fn diverging(value: f64, max: f64, negative: Self, neutral: Self, positive: Self) -> Self {
+        let fraction = if max > 0.0 { value.abs() / max } else { 0.0 };
+        neutral.lerp(if value < 0.0 { negative } else { positive }, fraction)
+    }
+
+    /// Color `fraction` of the way from `self` to `other`
+    #[must_use]
+    pub fn lerp(self, other: Self, fraction: f64) -> Self {
+        Srgb::from(self)
+            .into_format::<f64>()
+            .mix(Srgb::from(other).into_format(), fraction.clamp(0.0, 1.0))
+            .into_format::<u8>()
+            .into()
+    }
 
-    fn from_hsv(angle: f64) -> Self {
-        let color = Srgb::from_color(Hsv::new_srgb(angle, 1.0, 1.0));
-        Self(
-            (color.red * 255.0) as u8,
-            (color.green * 255.0) as u8,
-            (color.blue * 255.0) as u8,
-        )
+    fn from_hsv(angle: f64, saturation: f64, value: f64) -> Self {
+        Srgb::from_color(Hsv::new_srgb(angle, saturation, value))
+            .into_format::<u8>()
+            .into()
     }
 }
 
@@ -32,34 +71,235 @@ impl plotters::style::Color for Color {
     }
 }
 
+impl FromStr for Color {
+    type Err = Error;
+
+    /// Parses `#RRGGBB`
+    fn from_str(s: &str) -> Result<Self> {
+        let hex = s
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| anyhow!("Color \"{s}\" is not of the form #RRGGBB"))?;
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| anyhow!("Color \"{s}\" is not of the form #RRGGBB"))
+        };
+        Ok(Self(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+impl TryFrom<ColorRepr> for Color {
+    type Error = Error;
+
+    fn try_from(value: ColorRepr) -> Result<Self> {
+        match value {
+            ColorRepr::Hex(hex) => hex.parse(),
+            ColorRepr::Rgb([r, g, b]) => Ok(Self(r, g, b)),
+        }
+    }
+}
+
+impl From<Color> for String {
+    fn from(value: Color) -> Self {
+        format!("#{:02x}{:02x}{:02x}", value.0, value.1, value.2)
+    }
+}
+
+impl From<Srgb<u8>> for Color {
+    fn from(value: Srgb<u8>) -> Self {
+        Self(value.red, value.green, value.blue)
+    }
+}
+
+impl From<Color> for Srgb<u8> {
+    fn from(value: Color) -> Self {
+        Self::new(value.0, value.1, value.2)
+    }
+}
+
 impl From<Color> for Rgba<u8> {
     fn from(value: Color) -> Self {
         Self([value.0, value.1, value.2, 0xff])
     }
 }
 
+/// Colors of `elements` items, of which any `spacing` consecutive ones have hues as far apart as
+/// possible
+///
+/// The hues are evenly spaced around the wheel, up to [`MAX_HUES`] of them per pass. More items
+/// take further passes, each in a different shade with its hues offset between those of the
+/// previous passes, so that every one of the `elements` colors is distinct.
 #[derive(Debug)]
 pub struct ColorIterator {
-    dangle: f64,
-    i: f64,
+    hues: usize,
+    shades: usize,
+    stride: usize,
+    elements: usize,
+    i: usize,
 }
 
 impl Iterator for ColorIterator {
     type Item = Color;
 
-    /// Guaranteed to return `Some`
+    /// Guaranteed to return `Some`, repeating colors after `elements` items
     fn next(&mut self) -> Option<Self::Item> {
-        self.i += 1.0;
-        Some(Color::from_hsv(self.dangle * self.i))
+        let (pass, slot) = (self.i / self.hues, self.i % self.hues);
+        // Wrap at `elements`, as the last pass over the wheel may have more slots than are left
+        self.i = (self.i + 1) % self.elements;
+        let hue = (slot * self.stride) % self.hues;
+        let offset = (pass % self.shades) as f64 / self.shades as f64;
+        let (saturation, value) = SHADES[pass % self.shades];
+        Some(Color::from_hsv(
+            (hue as f64 + offset) * 360.0 / self.hues as f64,
+            saturation,
+            value,
+        ))
     }
 }
 
 impl ColorIterator {
     #[must_use]
     pub fn new(spacing: usize, elements: usize) -> Self {
+        let elements = elements.max(1);
+        let shades = elements.div_ceil(MAX_HUES).clamp(1, SHADES.len());
+        let hues = elements.div_ceil(shades);
         Self {
-            dangle: (360.0 - (360.0 / elements as f64)) / spacing as f64,
-            i: -1.0,
+            hues,
+            shades,
+            stride: Self::stride(spacing, hues),
+            elements,
+            i: 0,
         }
     }
+
+    /// Step between the hues of consecutive items, out of `hues` evenly spaced ones, maximizing
+    /// the smallest distance between any two hues within `spacing` consecutive items
+    fn stride(spacing: usize, hues: usize) -> usize {
+        let distance = |stride: usize| {
+            (1..spacing.min(hues))
+                .map(|step| {
+                    let hue = (step * stride) % hues;
+                    hue.min(hues - hue)
+                })
+                .min()
+                .unwrap_or(hues)
+        };
+        // Only strides coprime to the number of hues visit all of them
+        (1..hues)
+            .filter(|&stride| gcd(stride, hues) == 1)
+            .max_by_key(|&stride| (distance(stride), std::cmp::Reverse(stride)))
+            .unwrap_or(1)
+    }
+}
+
+const fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn hue(color: Color) -> f64 {
+        Hsv::from_color(Srgb::from(color).into_format::<f64>())
+            .hue
+            .into_positive_degrees()
+    }
+
+    fn hue_distance(a: Color, b: Color) -> f64 {
+        let distance = (hue(a) - hue(b)).abs();
+        distance.min(360.0 - distance)
+    }
+
+    /// Pairs of sizes of windows of consecutive items and numbers of items to color, including
+    /// fewer items than a window, exactly one pass over the wheel, and several passes
+    const CASES: [(usize, usize); 12] = [
+        (4, 1),
+        (4, 3),
+        (4, 12),
+        (4, 24),
+        (4, 30),
+        (4, 100),
+        (4, 200),
+        (2, 50),
+        (3, 25),
+        (6, 12),
+        (6, 100),
+        (12, 100),
+    ];
+
+    #[test]
+    fn hues_within_window_are_apart() {
+        for (spacing, elements) in CASES {
+            let colors = ColorIterator::new(spacing, elements)
+                .take(elements)
+                .collect::<Vec<_>>();
+            // At least half as far apart as if the hues within a window were evenly spaced,
+            // allowing for the rounding of colors to 8 bits
+            let min_distance = 180.0 / spacing as f64 - 1.0;
+            for (start, window) in colors.windows(spacing.min(elements)).enumerate() {
+                for (i, &a) in window.iter().enumerate() {
+                    for &b in &window[i + 1..] {
+                        let distance = hue_distance(a, b);
+                        assert!(
+                            distance >= min_distance,
+                            "{spacing} of {elements}: hues {distance}° apart in window at {start}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn colors_of_elements_are_distinct() {
+        for (spacing, elements) in CASES {
+            let colors = ColorIterator::new(spacing, elements)
+                .take(elements)
+                .collect::<HashSet<_>>();
+            assert_eq!(colors.len(), elements, "{spacing} of {elements}");
+        }
+    }
+
+    #[test]
+    fn colors_repeat_after_elements() {
+        // Including numbers of items which don't fill their last pass over the wheel
+        for (spacing, elements) in CASES.into_iter().chain([(4, 25), (4, 97), (3, 7)]) {
+            let colors = ColorIterator::new(spacing, elements)
+                .take(2 * elements)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                colors[..elements],
+                colors[elements..],
+                "{spacing} of {elements}"
+            );
+        }
+        assert_eq!(
+            ColorIterator::new(4, 0).take(2).collect::<Vec<_>>(),
+            ColorIterator::new(4, 1).take(2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn diverges_from_neutral_to_extremes() {
+        let (negative, neutral, positive) =
+            (Color::ACCENT_BLUE, Color::BG_PRIMARY, Color::ACCENT_PINK);
+        let diverging = |value| Color::diverging(value, 4.0, negative, neutral, positive);
+        assert_eq!(diverging(0.0), neutral);
+        assert_eq!(diverging(-4.0), negative);
+        assert_eq!(diverging(4.0), positive);
+        assert_eq!(diverging(-2.0), neutral.lerp(negative, 0.5));
+        assert_eq!(diverging(2.0), neutral.lerp(positive, 0.5));
+        assert_ne!(diverging(2.0), neutral);
+        assert_ne!(diverging(2.0), positive);
+        // Beyond the largest magnitude, and without any magnitude at all
+        assert_eq!(diverging(-8.0), negative);
+        assert_eq!(diverging(8.0), positive);
+        assert_eq!(
+            Color::diverging(3.0, 0.0, negative, neutral, positive),
+            neutral
+        );
+    }
 }
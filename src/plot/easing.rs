@@ -0,0 +1,7 @@
+pub fn ease_in_out_cubic(x: f64) -> f64 {
+    if x < 0.5 {
+        4.0 * x.powi(3)
+    } else {
+        1.0 - (-2.0f64).mul_add(x, 2.0).powi(3) / 2.0
+    }
+}
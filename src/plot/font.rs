@@ -1,4 +1,4 @@
-use plotters::style::{HasDimension, IntoTextStyle, TextStyle};
+use plotters::style::{Color as _, FontStyle, HasDimension, IntoTextStyle, TextStyle};
 
 use super::color::Color;
 
@@ -6,26 +6,38 @@ use super::color::Color;
 pub struct Font {
     name: &'static str,
     size: u32,
-    color: &'static Color,
+    bold: bool,
+    color: Color,
 }
 
 impl Default for Font {
     fn default() -> Self {
         Self {
             name: "Rubik",
-            size: 24,
-            color: &Color::FONT_PRIMARY,
+            size: Self::DEFAULT_SIZE,
+            bold: false,
+            color: Color::FONT_PRIMARY,
         }
     }
 }
 
 impl Font {
+    pub const DEFAULT_SIZE: u32 = 24;
+
     pub fn new(size: u32) -> Self {
         Self {
             size,
             ..Default::default()
         }
     }
+
+    pub const fn with_color(self, color: Color) -> Self {
+        Self { color, ..self }
+    }
+
+    pub const fn bold(self) -> Self {
+        Self { bold: true, ..self }
+    }
 }
 
 impl<'a> IntoTextStyle<'a> for Font {
@@ -33,6 +45,17 @@ impl<'a> IntoTextStyle<'a> for Font {
     where
         P: HasDimension,
     {
-        (self.name, self.size, self.color).into_text_style(parent)
+        let mut style = (
+            self.name,
+            self.size,
+            if self.bold {
+                FontStyle::Bold
+            } else {
+                FontStyle::Normal
+            },
+        )
+            .into_text_style(parent);
+        style.color = self.color.to_backend_color();
+        style
     }
 }
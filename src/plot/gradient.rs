@@ -0,0 +1,208 @@
+//! Areas filled with gradients, which the bitmap backend doesn't support natively
+
+use std::result;
+
+use plotters::{
+    element::{BackendCoordOnly, CoordMapper, Drawable, PointCollection},
+    style::{Color, RGBAColor},
+};
+use plotters_backend::{BackendColor, BackendStyle, DrawingBackend, DrawingErrorKind};
+
+/// Area between a polyline and a horizontal baseline, filled with a vertical gradient from
+/// `top` at its highest point to `bottom` at its lowest
+///
+/// Swapping the colors reverses the direction of the gradient.
+#[derive(Debug)]
+pub struct GradientArea<X, Y> {
+    vertices: Vec<(X, Y)>,
+    top: RGBAColor,
+    bottom: RGBAColor,
+}
+
+impl<X, Y> GradientArea<X, Y>
+where
+    X: Clone,
+    Y: Clone,
+{
+    pub fn new<I, T, B>(points: I, baseline: Y, top: &T, bottom: &B) -> Self
+    where
+        I: IntoIterator<Item = (X, Y)>,
+        T: Color,
+        B: Color,
+    {
+        let mut vertices = points.into_iter().collect::<Vec<_>>();
+        if let (Some((first_x, _)), Some((last_x, _))) = (vertices.first(), vertices.last()) {
+            let (first_x, last_x) = (first_x.clone(), last_x.clone());
+            vertices.push((last_x, baseline.clone()));
+            vertices.push((first_x, baseline));
+        }
+        Self {
+            vertices,
+            top: top.to_rgba(),
+            bottom: bottom.to_rgba(),
+        }
+    }
+}
+
+/// Solid color of a single span
+struct Span(BackendColor);
+
+impl BackendStyle for Span {
+    fn color(&self) -> BackendColor {
+        self.0
+    }
+}
+
+/// Color `fraction` of the way from `from` to `to`, including opacity
+fn lerp(from: BackendColor, to: BackendColor, fraction: f64) -> BackendColor {
+    let channel = |from: u8, to: u8| {
+        f64::from(to)
+            .mul_add(fraction, f64::from(from) * (1.0 - fraction))
+            .round() as u8
+    };
+    BackendColor {
+        alpha: to.alpha.mul_add(fraction, from.alpha * (1.0 - fraction)),
+        rgb: (
+            channel(from.rgb.0, to.rgb.0),
+            channel(from.rgb.1, to.rgb.1),
+            channel(from.rgb.2, to.rgb.2),
+        ),
+    }
+}
+
+impl<DB, X, Y> Drawable<DB> for GradientArea<X, Y>
+where
+    DB: DrawingBackend,
+{
+    fn draw<I>(
+        &self,
+        pos: I,
+        backend: &mut DB,
+        _parent_dim: (u32, u32),
+    ) -> result::Result<(), DrawingErrorKind<<DB as DrawingBackend>::ErrorType>>
+    where
+        I: Iterator<Item = <BackendCoordOnly as CoordMapper>::Output>,
+    {
+        let vertices = pos.collect::<Vec<_>>();
+        let (Some(min_y), Some(max_y)) = (
+            vertices.iter().map(|&(_, y)| y).min(),
+            vertices.iter().map(|&(_, y)| y).max(),
+        ) else {
+            return Ok(());
+        };
+        let edges = vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .map(|(&(x0, y0), &(x1, y1))| {
+                (
+                    (f64::from(x0), f64::from(y0)),
+                    (f64::from(x1), f64::from(y1)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Each scanline is split into spans inside the area by the even-odd rule
+        let mut crossings = Vec::new();
+        for y in min_y..=max_y {
+            let center = f64::from(y) + 0.5;
+            crossings.clear();
+            crossings.extend(
+                edges
+                    .iter()
+                    .filter(|((_, y0), (_, y1))| (*y0 <= center) != (*y1 <= center))
+                    .map(|&((x0, y0), (x1, y1))| ((center - y0) / (y1 - y0)).mul_add(x1 - x0, x0)),
+            );
+            crossings.sort_by(f64::total_cmp);
+            let color = Span(lerp(
+                self.top.to_backend_color(),
+                self.bottom.to_backend_color(),
+                f64::from(y - min_y) / f64::from((max_y - min_y).max(1)),
+            ));
+            for span in crossings.chunks_exact(2) {
+                let (start, end) = (span[0].round() as i32, span[1].round() as i32 - 1);
+                if start <= end {
+                    backend.draw_line((start, y), (end, y), &color)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, X, Y> PointCollection<'a, (X, Y)> for &'a GradientArea<X, Y>
+where
+    X: 'a,
+    Y: 'a,
+{
+    type Point = &'a (X, Y);
+    type IntoIter = &'a [(X, Y)];
+    fn point_iter(self) -> &'a [(X, Y)] {
+        &self.vertices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plotters::{
+        prelude::{BitMapBackend, IntoDrawingArea},
+        style::{BLUE, RED},
+    };
+
+    use super::*;
+
+    const WIDTH: u32 = 20;
+    const HEIGHT: u32 = 101;
+
+    /// Pixels of a rectangle filling the image, fading from `top` in its first row towards
+    /// `bottom` at its last
+    fn draw(top: &RGBAColor, bottom: &RGBAColor) -> Vec<u8> {
+        let mut buffer = vec![0; (WIDTH * HEIGHT * 3) as usize];
+        {
+            let root = BitMapBackend::with_buffer(&mut buffer, (WIDTH, HEIGHT)).into_drawing_area();
+            root.draw(&GradientArea::new(
+                [(0, 0), (WIDTH as i32, 0)],
+                HEIGHT as i32,
+                top,
+                bottom,
+            ))
+            .unwrap();
+            root.present().unwrap();
+        }
+        buffer
+    }
+
+    /// Asserts that the pixel at row `y` is within a few steps of `expected`
+    fn assert_pixel(buffer: &[u8], y: u32, expected: [u8; 3]) {
+        let i = (y * WIDTH * 3) as usize;
+        let actual = &buffer[i..i + 3];
+        assert!(
+            actual
+                .iter()
+                .zip(expected)
+                .all(|(&actual, expected)| actual.abs_diff(expected) <= 3),
+            "row {y} is {actual:?} instead of {expected:?}"
+        );
+    }
+
+    #[test]
+    fn fades_from_top_to_bottom() {
+        let (red, blue) = (RED.to_rgba(), BLUE.to_rgba());
+        let buffer = draw(&red, &blue);
+        assert_pixel(&buffer, 0, [255, 0, 0]);
+        assert_pixel(&buffer, HEIGHT / 2, [128, 0, 128]);
+        assert_pixel(&buffer, HEIGHT - 1, [0, 0, 255]);
+
+        // Swapped colors reverse the direction
+        let buffer = draw(&blue, &red);
+        assert_pixel(&buffer, 0, [0, 0, 255]);
+        assert_pixel(&buffer, HEIGHT - 1, [255, 0, 0]);
+    }
+
+    #[test]
+    fn fades_opacity() {
+        let buffer = draw(&RED.to_rgba(), &RED.mix(0.0));
+        assert_pixel(&buffer, 0, [255, 0, 0]);
+        assert_pixel(&buffer, HEIGHT / 2, [128, 0, 0]);
+        assert_pixel(&buffer, HEIGHT - 1, [0, 0, 0]);
+    }
+}
@@ -1,11 +1,35 @@
-use anyhow::Result;
-use image::{DynamicImage, imageops::FilterType};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
 
-use super::color::Color;
+use anyhow::{Result, anyhow};
+use image::{DynamicImage, RgbImage, imageops::FilterType};
+use plotters::{
+    prelude::{BitMapBackend, IntoDrawingArea},
+    style::IntoTextStyle,
+};
+use plotters_backend::text_anchor::{HPos, Pos, VPos};
 
-pub fn load(img: &[u8], nwidth: u32, nheight: u32, bg: Color) -> Result<DynamicImage> {
+use super::{color::Color, font::Font};
+use crate::request::resource::ImageSize;
+
+/// Maximum number of initials drawn on a placeholder
+const PLACEHOLDER_MAX_INITIALS: usize = 3;
+/// Approximate width of a glyph relative to the font size
+const PLACEHOLDER_GLYPH_WIDTH: f64 = 0.7;
+
+/// Decodes `img` and resizes it to fit within `nwidth`x`nheight` using `filter`, or
+/// nearest-neighbor sampling, filling transparent pixels with `bg`
+pub fn load(
+    img: &[u8],
+    nwidth: u32,
+    nheight: u32,
+    bg: Color,
+    filter: Option<FilterType>,
+) -> Result<DynamicImage> {
     let mut img = image::load_from_memory(img)?
-        .resize(nwidth, nheight, FilterType::Nearest)
+        .resize(nwidth, nheight, filter.unwrap_or(FilterType::Nearest))
         .into_rgba8();
     for pixel in img.pixels_mut() {
         if pixel.0[3] == 0x00 {
@@ -14,3 +38,179 @@ pub fn load(img: &[u8], nwidth: u32, nheight: u32, bg: Color) -> Result<DynamicI
     }
     Ok(DynamicImage::ImageRgba8(img))
 }
+
+/// Resource and arguments of a call to [`load`], the filter being identified by its discriminant
+type ImageKey = (ImageSize, String, u32, u32, Color, Option<u8>);
+
+/// Images decoded and resized by [`load`], shared between clones, so that drawing the same
+/// resource at the same size again skips decoding it. The default cache is disabled, keeping
+/// nothing.
+#[derive(Debug, Clone, Default)]
+pub struct ImageCache(Option<Arc<Mutex<HashMap<ImageKey, DynamicImage>>>>);
+
+impl ImageCache {
+    #[must_use]
+    pub fn enabled() -> Self {
+        Self(Some(Arc::default()))
+    }
+
+    /// [`load`] of the resource at `url` of `size`, whose contents are `img`, or a copy of its
+    /// earlier result with the same arguments if enabled
+    #[allow(clippy::too_many_arguments)]
+    pub fn load(
+        &self,
+        size: ImageSize,
+        url: &str,
+        img: &[u8],
+        nwidth: u32,
+        nheight: u32,
+        bg: Color,
+        filter: Option<FilterType>,
+    ) -> Result<DynamicImage> {
+        let Some(images) = &self.0 else {
+            return load(img, nwidth, nheight, bg, filter);
+        };
+        let key = (
+            size,
+            url.to_string(),
+            nwidth,
+            nheight,
+            bg,
+            filter.map(|filter| filter as u8),
+        );
+        if let Some(image) = lock(images).get(&key) {
+            return Ok(image.clone());
+        }
+        // Decoded without holding the lock, at worst decoding the same image twice at once
+        let image = load(img, nwidth, nheight, bg, filter)?;
+        lock(images).insert(key, image.clone());
+        Ok(image)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // The cache is never left half-updated, so a panic while holding the lock can be ignored
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Solid `bg` tile with the initials of `name` centered in `fg`, standing in for a missing image
+pub fn placeholder(
+    width: u32,
+    height: u32,
+    name: &str,
+    bg: Color,
+    fg: Color,
+) -> Result<DynamicImage> {
+    let mut buf = vec![0; width as usize * height as usize * 3];
+    {
+        let root = BitMapBackend::with_buffer(&mut buf, (width, height)).into_drawing_area();
+        root.fill(&bg)?;
+        let initials = initials(name);
+        if !initials.is_empty() {
+            let size = (f64::from(height) / 2.0)
+                .min(f64::from(width) / (PLACEHOLDER_GLYPH_WIDTH * initials.len() as f64))
+                as u32;
+            root.draw_text(
+                &initials,
+                &Font::new(size.max(1))
+                    .with_color(fg)
+                    .with_anchor::<Color>(Pos {
+                        h_pos: HPos::Center,
+                        v_pos: VPos::Center,
+                    })
+                    .into_text_style(&root),
+                ((width / 2) as i32, (height / 2) as i32),
+            )?;
+        }
+        root.present()?;
+    }
+    Ok(DynamicImage::ImageRgb8(
+        RgbImage::from_raw(width, height, buf)
+            .ok_or_else(|| anyhow!("Placeholder buffer has the wrong size"))?,
+    ))
+}
+
+/// Uppercase first characters of the first few words of `name`
+fn initials(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .flat_map(char::to_uppercase)
+        .take(PLACEHOLDER_MAX_INITIALS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{ImageFormat, Rgb};
+
+    use super::*;
+
+    const URL: &str = "https://example.com/cover.png";
+
+    /// PNG of a 4x4 image of one color
+    fn png() -> Vec<u8> {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::from_pixel(4, 4, Rgb([0xed, 0x0d, 0x7f])))
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn second_load_skips_decoding() {
+        let cache = ImageCache::enabled();
+        let load = |img: &[u8], nwidth| {
+            cache.load(
+                ImageSize::CoverSmall,
+                URL,
+                img,
+                nwidth,
+                2,
+                Color::BG_PRIMARY,
+                None,
+            )
+        };
+        let first = load(&png(), 2).unwrap();
+        // Contents which can't be decoded, so only a cached image can be returned
+        let second = load(b"not an image", 2).unwrap();
+        assert_eq!(second, first);
+        // Other arguments are decoded anew
+        assert!(load(b"not an image", 3).is_err());
+        assert!(
+            ImageCache::default()
+                .load(
+                    ImageSize::CoverSmall,
+                    URL,
+                    b"not an image",
+                    2,
+                    2,
+                    Color::BG_PRIMARY,
+                    None
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn clones_share_loaded_images() {
+        let cache = ImageCache::enabled();
+        let clone = cache.clone();
+        let first = cache
+            .load(ImageSize::Hd, URL, &png(), 2, 2, Color::BG_PRIMARY, None)
+            .unwrap();
+        let second = clone
+            .load(
+                ImageSize::Hd,
+                URL,
+                b"not an image",
+                2,
+                2,
+                Color::BG_PRIMARY,
+                None,
+            )
+            .unwrap();
+        assert_eq!(second, first);
+    }
+}
@@ -0,0 +1,353 @@
+//! Text drawn on the visualizations, which can be translated
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use time::Date;
+
+use crate::data::{RatingKind, RatingWeighting};
+
+/// Words, and templates whose `{placeholders}` are filled in, drawn on the visualizations
+///
+/// Deserialized from JSON, with any missing field taking its English default.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Locale {
+    /// Separator between groups of three digits, or empty for none
+    thousands_separator: String,
+    /// Names of the months, from January
+    months: [String; 12],
+    /// With `{year}`, `{month}`, `{month_name}` and `{day}`
+    date: String,
+    /// With `{year}`
+    year: String,
+    pub episode: String,
+    pub ranking: String,
+    user_ranking: String,
+    critic_ranking: String,
+    total_ranking: String,
+    /// With `{ranking}`
+    vote_weighted: String,
+    pub release_date: String,
+    pub games_axis: String,
+    pub games_share_axis: String,
+    pub console_generation: String,
+    pub genre_trend_axis: String,
+    /// Games not released on any console
+    pub pc_os: String,
+    /// With `{generation}`
+    generation: String,
+    /// With `{decade}`
+    decade: String,
+    pub campaign_coop: String,
+    pub lan_coop: String,
+    pub offline_coop: String,
+    pub online_coop: String,
+    pub no_multiplayer_data: String,
+    pub no_release_dates: String,
+    /// With `{date}`
+    list_card_title: String,
+    /// Marks a game which wasn't on the previous list
+    pub new_entry: String,
+    /// With `{position}`
+    enters_at: String,
+    /// With `{position}`
+    now_at: String,
+    pub segments: SegmentTitles,
+    pub since_last_episode: String,
+    pub compared_to_igdb: String,
+    pub normalized_by_list_length: String,
+    /// With `{count}`
+    games: String,
+    /// With `{days}` and `{percent}`
+    days_share: String,
+    /// With `{percent}`
+    share_per_game: String,
+    /// With `{delta}`, which is signed
+    positions: String,
+    /// With `{position}`
+    position: String,
+    /// With `{position}`
+    former_position: String,
+    pub new_this_episode: String,
+    /// With `{days}`
+    streak: String,
+    /// With `{position}` and `{name}`
+    ranked: String,
+}
+
+/// Titles of the segments of the summary and episode summary
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SegmentTitles {
+    pub list_toppers: String,
+    pub barrel_bottoms: String,
+    pub risers: String,
+    pub fallers: String,
+    pub overrated: String,
+    pub underrated: String,
+    pub engines: String,
+    pub companies: String,
+    pub platforms: String,
+    pub genres: String,
+    pub developers: String,
+    pub publishers: String,
+    pub porters: String,
+    pub supporters: String,
+    pub full_ranking: String,
+    pub new_entries: String,
+    pub departures: String,
+    pub top_riser: String,
+    pub top_faller: String,
+    pub no_changes: String,
+    pub number_one: String,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            thousands_separator: ",".to_string(),
+            months: [
+                "January",
+                "February",
+                "March",
+                "April",
+                "May",
+                "June",
+                "July",
+                "August",
+                "September",
+                "October",
+                "November",
+                "December",
+            ]
+            .map(str::to_string),
+            date: "{year}-{month}-{day}".to_string(),
+            year: "{year}".to_string(),
+            episode: "Episode".to_string(),
+            ranking: "Bonus Points Ranking".to_string(),
+            user_ranking: "IGDB User Ranking".to_string(),
+            critic_ranking: "IGDB Critic Ranking".to_string(),
+            total_ranking: "IGDB Ranking".to_string(),
+            vote_weighted: "{ranking} (vote-weighted)".to_string(),
+            release_date: "Release Date".to_string(),
+            games_axis: "Games".to_string(),
+            games_share_axis: "Games on the List (%)".to_string(),
+            console_generation: "Earliest Console Generation".to_string(),
+            genre_trend_axis: "Change in Share of the Top Half (percentage points per year)"
+                .to_string(),
+            pc_os: "PC/OS".to_string(),
+            generation: "Gen {generation}".to_string(),
+            decade: "{decade}s".to_string(),
+            campaign_coop: "Campaign Co-op".to_string(),
+            lan_coop: "LAN Co-op".to_string(),
+            offline_coop: "Offline Co-op".to_string(),
+            online_coop: "Online Co-op".to_string(),
+            no_multiplayer_data: "No Multiplayer Data".to_string(),
+            no_release_dates: "No release dates to show".to_string(),
+            list_card_title: "The Bonus Points List, {date}".to_string(),
+            new_entry: "NEW".to_string(),
+            enters_at: "Enters at #{position}".to_string(),
+            now_at: "Now #{position}".to_string(),
+            segments: SegmentTitles::default(),
+            since_last_episode: "since the last episode".to_string(),
+            compared_to_igdb: "compared to IGDB ranking".to_string(),
+            normalized_by_list_length: "relative to the list's length".to_string(),
+            games: "{count} games".to_string(),
+            days_share: "{days} days ({percent}%)".to_string(),
+            share_per_game: "{percent}% per game listed".to_string(),
+            positions: "{delta} positions".to_string(),
+            position: "#{position}".to_string(),
+            former_position: "was #{position}".to_string(),
+            new_this_episode: "New this episode".to_string(),
+            streak: "For {days} days".to_string(),
+            ranked: "{position}. {name}".to_string(),
+        }
+    }
+}
+
+impl Default for SegmentTitles {
+    fn default() -> Self {
+        Self {
+            list_toppers: "List Toppers".to_string(),
+            barrel_bottoms: "Barrel Bottoms".to_string(),
+            risers: "Risers".to_string(),
+            fallers: "Fallers".to_string(),
+            overrated: "Overrated".to_string(),
+            underrated: "Underrated".to_string(),
+            engines: "Game Engines".to_string(),
+            companies: "Companies".to_string(),
+            platforms: "Platforms".to_string(),
+            genres: "Genres".to_string(),
+            developers: "Developers".to_string(),
+            publishers: "Publishers".to_string(),
+            porters: "Porters".to_string(),
+            supporters: "Supporters".to_string(),
+            full_ranking: "Full Ranking".to_string(),
+            new_entries: "New Entries".to_string(),
+            departures: "Departures".to_string(),
+            top_riser: "Top Riser".to_string(),
+            top_faller: "Top Faller".to_string(),
+            no_changes: "No Changes".to_string(),
+            number_one: "Number One".to_string(),
+        }
+    }
+}
+
+/// `template` with each `{name}` replaced by its value
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+impl Locale {
+    /// Loads the locale from the JSON file at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        serde_json::from_str(
+            &fs::read_to_string(path)
+                .with_context(|| format!("Could not read {}", path.to_string_lossy()))?,
+        )
+        .with_context(|| format!("Could not parse locale {}", path.to_string_lossy()))
+    }
+
+    /// `n` with its digits grouped in threes
+    pub fn number(&self, n: u64) -> String {
+        let digits = n.to_string();
+        let mut grouped = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push_str(&self.thousands_separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    /// Change in a number, with a proper minus sign
+    fn signed(&self, n: i64) -> String {
+        if n < 0 {
+            format!("\u{2212}{}", self.number(n.unsigned_abs()))
+        } else {
+            format!("+{}", self.number(n.unsigned_abs()))
+        }
+    }
+
+    pub fn date(&self, date: Date) -> String {
+        fill(
+            &self.date,
+            &[
+                ("year", &date.year().to_string()),
+                ("month", &format!("{:02}", u8::from(date.month()))),
+                (
+                    "month_name",
+                    &self.months[usize::from(u8::from(date.month())) - 1],
+                ),
+                ("day", &format!("{:02}", date.day())),
+            ],
+        )
+    }
+
+    /// Name of the IGDB ranking by `kind` of rating, weighted by `weighting`
+    pub fn igdb_ranking(&self, kind: RatingKind, weighting: RatingWeighting) -> String {
+        let ranking = match kind {
+            RatingKind::User => &self.user_ranking,
+            RatingKind::Critic => &self.critic_ranking,
+            RatingKind::Total => &self.total_ranking,
+        };
+        match weighting {
+            RatingWeighting::Raw => ranking.clone(),
+            RatingWeighting::Bayesian => fill(&self.vote_weighted, &[("ranking", ranking)]),
+        }
+    }
+
+    pub fn year(&self, year: i32) -> String {
+        fill(&self.year, &[("year", &year.to_string())])
+    }
+
+    pub fn generation(&self, generation: u32) -> String {
+        fill(&self.generation, &[("generation", &generation.to_string())])
+    }
+
+    pub fn decade(&self, decade: i32) -> String {
+        fill(&self.decade, &[("decade", &decade.to_string())])
+    }
+
+    pub fn list_card_title(&self, date: Date) -> String {
+        fill(&self.list_card_title, &[("date", &self.date(date))])
+    }
+
+    /// Label of the 1-based `position` at which a game entered the list
+    pub fn enters_at(&self, position: usize) -> String {
+        fill(
+            &self.enters_at,
+            &[("position", &self.number(position as u64))],
+        )
+    }
+
+    /// Label of the current 1-based `position` of a game
+    pub fn now_at(&self, position: usize) -> String {
+        fill(&self.now_at, &[("position", &self.number(position as u64))])
+    }
+
+    /// Caption of a number of games
+    pub fn games(&self, count: u32) -> String {
+        fill(&self.games, &[("count", &self.number(count.into()))])
+    }
+
+    /// Caption of a number of days, which are `fraction` of some total
+    pub fn days_share(&self, days: i64, fraction: f64) -> String {
+        fill(
+            &self.days_share,
+            &[
+                ("days", &self.number(days.unsigned_abs())),
+                ("percent", &format!("{:.0}", fraction * 100.0)),
+            ],
+        )
+    }
+
+    /// Caption of a fraction of some total, divided by the number of games on the list
+    pub fn share_per_game(&self, fraction: f64) -> String {
+        fill(
+            &self.share_per_game,
+            &[("percent", &format!("{:.2}", fraction * 100.0))],
+        )
+    }
+
+    /// Caption of a change in position
+    pub fn positions(&self, delta: i32) -> String {
+        fill(&self.positions, &[("delta", &self.signed(delta.into()))])
+    }
+
+    /// Caption of a 1-based `position` on the list
+    pub fn position(&self, position: usize) -> String {
+        fill(
+            &self.position,
+            &[("position", &self.number(position as u64))],
+        )
+    }
+
+    /// Caption of a 1-based `position` on the list which a game left
+    pub fn former_position(&self, position: usize) -> String {
+        fill(
+            &self.former_position,
+            &[("position", &self.number(position as u64))],
+        )
+    }
+
+    /// Caption of how many days a game has spent at the top of the list
+    pub fn streak(&self, days: i64) -> String {
+        fill(&self.streak, &[("days", &self.number(days.unsigned_abs()))])
+    }
+
+    /// Game `name` at the 1-based `position` on the list
+    pub fn ranked(&self, position: usize, name: &str) -> String {
+        fill(
+            &self.ranked,
+            &[("position", &self.number(position as u64)), ("name", name)],
+        )
+    }
+}
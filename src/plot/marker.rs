@@ -24,6 +24,8 @@ impl MarkerKind {
 #[derive(Debug)]
 pub struct Marker<Coord> {
     center: Coord,
+    /// Radius in pixels
+    size: u32,
     style: ShapeStyle,
     kind: MarkerKind,
 }
@@ -42,14 +44,13 @@ where
         I: Iterator<Item = <BackendCoordOnly as CoordMapper>::Output>,
     {
         match self.kind {
-            MarkerKind::Triangle => {
-                TriangleMarker::new(&self.center, 5, self.style).draw(pos, backend, parent_dim)
-            }
+            MarkerKind::Triangle => TriangleMarker::new(&self.center, self.size, self.style)
+                .draw(pos, backend, parent_dim),
             MarkerKind::Circle => {
-                Circle::new(&self.center, 5, self.style).draw(pos, backend, parent_dim)
+                Circle::new(&self.center, self.size, self.style).draw(pos, backend, parent_dim)
             }
             MarkerKind::Cross => {
-                Cross::new(&self.center, 5, self.style).draw(pos, backend, parent_dim)
+                Cross::new(&self.center, self.size, self.style).draw(pos, backend, parent_dim)
             }
         }
     }
@@ -67,12 +68,13 @@ where
 }
 
 impl<Coord> Marker<Coord> {
-    pub fn new<S>(kind: MarkerKind, coord: Coord, style: S) -> Self
+    pub fn new<S>(kind: MarkerKind, coord: Coord, size: u32, style: S) -> Self
     where
         S: Into<ShapeStyle>,
     {
         Self {
             center: coord,
+            size,
             style: style.into(),
             kind,
         }
@@ -0,0 +1,67 @@
+use std::env;
+
+use anyhow::{Context, Result};
+use png::{BitDepth, ColorType, Encoder};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+const SOFTWARE: &str = concat!("tbp-viz ", env!("CARGO_PKG_VERSION"));
+/// Seconds since the Unix epoch embedded as the creation time instead of the current time, as
+/// understood by reproducible builds
+const SOURCE_DATE_EPOCH_VAR: &str = "SOURCE_DATE_EPOCH";
+
+/// Encodes RGB `pixels` as a PNG with description, software, and creation time text chunks
+///
+/// The creation time is taken from `SOURCE_DATE_EPOCH` if set, so that the same visualization
+/// can be encoded to the same bytes.
+pub fn encode(width: u32, height: u32, pixels: &[u8], description: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut encoder = Encoder::new(&mut out, width, height);
+    encoder.set_color(ColorType::Rgb);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.add_itxt_chunk("Description".to_string(), description.to_string())?;
+    encoder.add_text_chunk("Software".to_string(), SOFTWARE.to_string())?;
+    encoder.add_text_chunk(
+        "Creation Time".to_string(),
+        creation_time()?.format(&Rfc3339)?,
+    )?;
+    encoder.write_header()?.write_image_data(pixels)?;
+    Ok(out)
+}
+
+fn creation_time() -> Result<OffsetDateTime> {
+    let Ok(epoch) = env::var(SOURCE_DATE_EPOCH_VAR) else {
+        return Ok(OffsetDateTime::now_utc());
+    };
+    let epoch = epoch
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid {SOURCE_DATE_EPOCH_VAR} \"{epoch}\""))?;
+    Ok(OffsetDateTime::from_unix_timestamp(epoch)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use png::Decoder;
+
+    use super::*;
+
+    #[test]
+    fn embeds_text_chunks() {
+        let png = encode(2, 1, &[0; 6], "Two black pixels").unwrap();
+        let reader = Decoder::new(Cursor::new(png)).read_info().unwrap();
+        let info = reader.info();
+
+        let text = info
+            .uncompressed_latin1_text
+            .iter()
+            .map(|chunk| (chunk.keyword.as_str(), chunk.text.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(text[0], ("Software", SOFTWARE));
+        assert_eq!(text[1].0, "Creation Time");
+        OffsetDateTime::parse(text[1].1, &Rfc3339).unwrap();
+        assert_eq!(info.utf8_text[0].keyword, "Description");
+        assert_eq!(info.utf8_text[0].get_text().unwrap(), "Two black pixels");
+    }
+}
@@ -1,8 +1,32 @@
+mod annotate;
 mod color;
+mod easing;
 mod font;
+mod gradient;
 mod img;
+mod locale;
 mod marker;
+mod metadata;
+mod output;
 mod plots;
+mod presets;
+mod progress;
 mod range;
+mod size;
+mod text;
+mod thumbnail;
 
-pub use plots::{list_over_time, ranking_difference, release_dates, summary};
+pub use color::Color;
+pub use img::ImageCache;
+pub use locale::Locale;
+pub use output::{Heading, Output};
+pub use presets::Preset;
+pub use size::PlotSize;
+pub use thumbnail::Thumbnails;
+
+pub use plots::{
+    EpisodeLabels, ListOverTimeOptions, RankingDifferenceOptions, SmoothingParams, SummarySegment,
+    bubble_chart, coop, episode_summary, era_breakdown, generation_positions, genre_trend,
+    list_card, list_over_time, list_over_time_legend, race_frames, ranking_difference,
+    ranking_difference_path, release_dates, resource_urls, summary,
+};
@@ -0,0 +1,198 @@
+//! Destinations of rendered visualizations
+
+use std::{fmt, fs, path::Path};
+
+use anyhow::Result;
+use plotters::{
+    coord::Shift,
+    prelude::{BitMapBackend, DrawingArea, IntoDrawingArea},
+    style::IntoTextStyle,
+};
+use plotters_backend::text_anchor::{HPos, Pos, VPos};
+
+use super::{
+    color::Color,
+    font::Font,
+    metadata,
+    progress::{self, Stage},
+    size::PlotSize,
+    text,
+};
+
+const BYTES_PER_PIXEL: usize = 3;
+/// Width at which headings are drawn at their nominal sizes, scaled proportionally otherwise
+const HEADING_REFERENCE_SIZE: PlotSize = PlotSize::new(2048, 0);
+const HEADING_MARGIN: u32 = 16;
+const HEADING_GAP: u32 = 8;
+const TITLE_FONT_SIZE: u32 = 64;
+const SUBTITLE_FONT_SIZE: u32 = 32;
+
+/// Title and subtitle drawn above a visualization
+#[derive(Debug, Clone, Default)]
+pub struct Heading {
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+}
+
+/// Where the PNG of a visualization is written, and the heading drawn above it
+#[derive(Debug)]
+pub struct Output<'a> {
+    target: Target<'a>,
+    heading: Option<&'a Heading>,
+    /// Whether the stages of rendering are reported
+    progress: bool,
+}
+
+#[derive(Debug)]
+enum Target<'a> {
+    File(&'a Path),
+    /// Replaces the contents of the buffer
+    Buffer(&'a mut Vec<u8>),
+}
+
+impl<'a> Output<'a> {
+    /// Draws `heading`, if any, above the visualization, which is shrunk to make room for it
+    #[must_use]
+    pub const fn with_heading(self, heading: Option<&'a Heading>) -> Self {
+        Self { heading, ..self }
+    }
+
+    /// Doesn't report the stages of rendering, as for frames whose animation reports its own
+    #[must_use]
+    pub const fn without_progress(self) -> Self {
+        Self {
+            progress: false,
+            ..self
+        }
+    }
+
+    /// Draws a visualization of `(width, height)` pixels with `draw`, and writes it as a PNG
+    /// with `description` embedded
+    pub fn render<F>(
+        mut self,
+        (width, height): (u32, u32),
+        description: &str,
+        draw: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&DrawingArea<BitMapBackend<'_>, Shift>) -> Result<()>,
+    {
+        self.report(Stage::Drawing);
+        let mut pixels = vec![0; width as usize * height as usize * BYTES_PER_PIXEL];
+        {
+            let root = BitMapBackend::with_buffer(&mut pixels, (width, height)).into_drawing_area();
+            if let Some(heading) = self.heading {
+                let (band, plot) =
+                    root.split_vertically(heading_height(heading, PlotSize::new(width, height)));
+                draw_heading(&band, heading)?;
+                draw(&plot)?;
+            } else {
+                draw(&root)?;
+            }
+            root.present()?;
+        }
+
+        self.report(Stage::Encoding);
+        let png = metadata::encode(width, height, &pixels, description)?;
+        match &mut self.target {
+            Target::File(path) => fs::write(path, png)?,
+            Target::Buffer(buffer) => **buffer = png,
+        }
+        self.report(Stage::Written);
+        Ok(())
+    }
+
+    fn report(&self, stage: Stage) {
+        if self.progress {
+            progress::report(self, stage);
+        }
+    }
+}
+
+/// Height of the band holding `heading` above a visualization of `size`
+fn heading_height(heading: &Heading, size: PlotSize) -> u32 {
+    let scale = size.width_scale_from(HEADING_REFERENCE_SIZE);
+    let mut height = 2 * scale.margin(HEADING_MARGIN);
+    if heading.title.is_some() {
+        height += scale.text(TITLE_FONT_SIZE);
+    }
+    if heading.subtitle.is_some() {
+        height += scale.text(SUBTITLE_FONT_SIZE);
+    }
+    if heading.title.is_some() && heading.subtitle.is_some() {
+        height += scale.px(HEADING_GAP);
+    }
+    height.min(size.height)
+}
+
+/// Draws `heading` centered in `band`, each line cut off with an ellipsis if too long
+fn draw_heading(band: &DrawingArea<BitMapBackend<'_>, Shift>, heading: &Heading) -> Result<()> {
+    let (width, height) = band.dim_in_pixel();
+    let scale = PlotSize::new(width, height).width_scale_from(HEADING_REFERENCE_SIZE);
+    let margin = scale.margin(HEADING_MARGIN);
+    band.fill(&Color::BG_PRIMARY)?;
+
+    let mut y = margin;
+    for (line, font) in [
+        (
+            &heading.title,
+            Font::new(scale.text(TITLE_FONT_SIZE)).bold(),
+        ),
+        (&heading.subtitle, Font::new(scale.text(SUBTITLE_FONT_SIZE))),
+    ] {
+        let Some(line) = line else {
+            continue;
+        };
+        let style = font
+            .with_anchor::<Color>(Pos {
+                h_pos: HPos::Center,
+                v_pos: VPos::Center,
+            })
+            .into_text_style(band);
+        let font_size = style.font.get_size() as u32;
+        text::draw_wrapped_text(
+            band,
+            line,
+            &style,
+            ((width / 2) as i32, (y + font_size / 2) as i32),
+            width.saturating_sub(2 * margin),
+            1,
+            1.0,
+        )?;
+        y += font_size + scale.px(HEADING_GAP);
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for Output<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.target {
+            Target::File(path) => write!(f, "{}", path.to_string_lossy()),
+            Target::Buffer(_) => write!(f, "<buffer>"),
+        }
+    }
+}
+
+impl<'a, P> From<&'a P> for Output<'a>
+where
+    P: AsRef<Path> + ?Sized,
+{
+    fn from(value: &'a P) -> Self {
+        Self {
+            target: Target::File(value.as_ref()),
+            heading: None,
+            progress: true,
+        }
+    }
+}
+
+impl<'a> From<&'a mut Vec<u8>> for Output<'a> {
+    fn from(value: &'a mut Vec<u8>) -> Self {
+        Self {
+            target: Target::Buffer(value),
+            heading: None,
+            progress: true,
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::{cmp::Reverse, fs};
+
+use anyhow::{Context, Result, anyhow};
+use plotters::{
+    chart::ChartBuilder,
+    prelude::{BitMapElement, Circle},
+    style::{Color as _, IntoTextStyle, ShapeStyle},
+};
+use plotters_backend::text_anchor::{HPos, Pos, VPos};
+use tracing::info;
+
+use crate::{
+    data::{Data, LOGO_FILENAME, RatingKind},
+    plot::{color::Color, img, locale::Locale, output::Output, size::PlotSize},
+};
+
+const DEFAULT_SIZE: PlotSize = PlotSize::new(2048, 1024);
+const MARGIN: u32 = 64;
+const LOGO_MARGIN: i32 = 16;
+const LOGO_WIDTH: u32 = 170;
+const LOGO_HEIGHT: u32 = 90;
+const X_LABEL_AREA_SIZE: u32 = 56;
+const Y_LABEL_AREA_SIZE: u32 = 96;
+const MIN_RADIUS: f64 = 3.0;
+const MAX_RADIUS: f64 = 48.0;
+const BUBBLE_OPACITY: f64 = 0.7;
+const NUM_LABELLED: usize = 3;
+
+/// Plots the IGDB rating of each game on the latest list against its position, with bubble area
+/// encoding the number of ratings and color encoding the critic rating
+pub fn bubble_chart<'a, O>(
+    output: O,
+    size: Option<PlotSize>,
+    locale: &Locale,
+    data: &Data,
+) -> Result<()>
+where
+    O: Into<Output<'a>>,
+{
+    let output = output.into();
+    let name = output.to_string();
+    info!("Generating visualization {name}");
+
+    draw_bubble_chart(output, size.unwrap_or(DEFAULT_SIZE), locale, data)
+        .with_context(|| format!("Could not generate bubble chart visualization {name}"))?;
+
+    info!("Generated visualization {name}");
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_lines)]
+fn draw_bubble_chart(
+    output: Output<'_>,
+    size: PlotSize,
+    locale: &Locale,
+    data: &Data,
+) -> Result<()> {
+    let scale = size.scale_from(DEFAULT_SIZE);
+    let latest_list = data
+        .latest()
+        .ok_or_else(|| anyhow!("Latest list doesn't exist"))?;
+    let num_games = latest_list.0.len();
+
+    let mut bubbles = latest_list
+        .0
+        .iter()
+        .enumerate()
+        .filter_map(|(i, id)| {
+            let meta = data.metas.0.get(id)?;
+            Some((
+                i + 1,
+                meta.total_rating?,
+                meta.total_rating_count.unwrap_or(0),
+                meta.aggregated_rating,
+            ))
+        })
+        .collect::<Vec<_>>();
+    if bubbles.is_empty() {
+        return Err(anyhow!("No games on the latest list have an IGDB rating"));
+    }
+    // Draw large bubbles first, so that they don't hide small ones
+    bubbles.sort_by_key(|bubble| Reverse(bubble.2));
+    let max_count = f64::from(bubbles[0].2.max(1));
+
+    let description = format!(
+        "IGDB rating of {} games against their position on The Bonus Points list",
+        bubbles.len()
+    );
+    output.render((size.width, size.height), &description, |root| {
+        root.fill(&Color::BG_PRIMARY)?;
+
+        let logo = img::load(
+            &fs::read(LOGO_FILENAME)?,
+            scale.px(LOGO_WIDTH),
+            scale.px(LOGO_HEIGHT),
+            Color::BG_PRIMARY,
+            scale.filter(),
+        )?;
+        let logo_margin = scale.offset(LOGO_MARGIN);
+        root.draw(&BitMapElement::from(((logo_margin, logo_margin), logo)))?;
+
+        let mut chart = ChartBuilder::on(root)
+            .x_label_area_size(scale.text(X_LABEL_AREA_SIZE))
+            .y_label_area_size(scale.text(Y_LABEL_AREA_SIZE))
+            .margin(scale.margin(MARGIN))
+            .margin_top(scale.px(LOGO_HEIGHT + 2 * LOGO_MARGIN as u32))
+            .build_cartesian_2d(0.0..(num_games + 1) as f64, 0.0..100.0)?;
+
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .x_label_formatter(&|x| locale.number(x.round() as u64))
+            .y_label_formatter(&|y| format!("{y:.0}"))
+            .x_desc(&locale.ranking)
+            .y_desc(RatingKind::Total.to_string())
+            .label_style(scale.font())
+            .axis_style(Color::FONT_PRIMARY)
+            .draw()?;
+
+        chart.draw_series(bubbles.iter().map(|&(position, rating, count, critic)| {
+            let radius = scale
+                .len(MAX_RADIUS * (f64::from(count) / max_count).sqrt())
+                .max(scale.len(MIN_RADIUS));
+            let color = critic.map_or(Color::FONT_PRIMARY, |critic| Color::heat(critic / 100.0));
+            Circle::new(
+                (position as f64, rating),
+                radius.round() as u32,
+                ShapeStyle::from(color.mix(BUBBLE_OPACITY)).filled(),
+            )
+        }))?;
+
+        let labelled = data
+            .top_n(NUM_LABELLED.min(num_games))
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(i, meta)| (i + 1, meta))
+            .chain(
+                data.bottom_n(NUM_LABELLED.min(num_games))
+                    .into_iter()
+                    .flatten()
+                    .enumerate()
+                    .map(|(i, meta)| (num_games - NUM_LABELLED.min(num_games) + i + 1, meta)),
+            );
+        for (position, meta) in labelled {
+            let Some(rating) = meta.total_rating else {
+                continue;
+            };
+            let (x, y) = chart.backend_coord(&(position as f64, rating));
+            root.draw_text(
+                &meta.name,
+                &scale
+                    .font()
+                    .with_anchor::<Color>(Pos {
+                        h_pos: HPos::Center,
+                        v_pos: VPos::Center,
+                    })
+                    .into_text_style(root),
+                (x, y),
+            )?;
+        }
+
+        Ok(())
+    })
+}
@@ -0,0 +1,152 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+use plotters::{
+    chart::ChartBuilder,
+    coord::ranged1d::{IntoSegmentedCoord, SegmentValue},
+    prelude::{BitMapElement, Rectangle},
+    style::ShapeStyle,
+};
+use tracing::info;
+
+use crate::{
+    data::{Data, LOGO_FILENAME},
+    plot::{color::Color, img, locale::Locale, output::Output, size::PlotSize},
+};
+
+const DEFAULT_SIZE: PlotSize = PlotSize::new(2048, 1024);
+const MARGIN: u32 = 64;
+const LOGO_MARGIN: i32 = 16;
+const LOGO_WIDTH: u32 = 170;
+const LOGO_HEIGHT: u32 = 90;
+const X_LABEL_AREA_SIZE: u32 = 56;
+const Y_LABEL_AREA_SIZE: u32 = 96;
+const BAR_MARGIN: u32 = 48;
+
+pub fn coop<'a, O>(output: O, size: Option<PlotSize>, locale: &Locale, data: &Data) -> Result<()>
+where
+    O: Into<Output<'a>>,
+{
+    let output = output.into();
+    let name = output.to_string();
+    info!("Generating visualization {name}");
+
+    draw_coop(output, size.unwrap_or(DEFAULT_SIZE), locale, data)
+        .with_context(|| format!("Could not generate co-op visualization {name}"))?;
+
+    info!("Generated visualization {name}");
+
+    Ok(())
+}
+
+fn draw_coop(output: Output<'_>, size: PlotSize, locale: &Locale, data: &Data) -> Result<()> {
+    let scale = size.scale_from(DEFAULT_SIZE);
+    let summary = data.multiplayer_summary();
+    let bars = [
+        (
+            &locale.campaign_coop,
+            summary.campaign_coop,
+            Color::ACCENT_PINK,
+        ),
+        (&locale.lan_coop, summary.lan_coop, Color::ACCENT_BLUE),
+        (
+            &locale.offline_coop,
+            summary.offline_coop,
+            Color::ACCENT_YELLOW,
+        ),
+        (
+            &locale.online_coop,
+            summary.online_coop,
+            Color::FONT_PRIMARY,
+        ),
+        (
+            &locale.no_multiplayer_data,
+            summary.no_data,
+            Color::BG_SECONDARY,
+        ),
+    ];
+    let max_count = bars.iter().map(|bar| bar.1).max().unwrap_or(0).max(1);
+
+    output.render(
+        (size.width, size.height),
+        "Co-op capabilities of the games on The Bonus Points list",
+        |root| {
+            root.fill(&Color::BG_PRIMARY)?;
+
+            let logo = img::load(
+                &fs::read(LOGO_FILENAME)?,
+                scale.px(LOGO_WIDTH),
+                scale.px(LOGO_HEIGHT),
+                Color::BG_PRIMARY,
+                scale.filter(),
+            )?;
+            let logo_margin = scale.offset(LOGO_MARGIN);
+            root.draw(&BitMapElement::from(((logo_margin, logo_margin), logo)))?;
+
+            let mut chart = ChartBuilder::on(root)
+                .x_label_area_size(scale.text(X_LABEL_AREA_SIZE))
+                .y_label_area_size(scale.text(Y_LABEL_AREA_SIZE))
+                .margin(scale.margin(MARGIN))
+                .build_cartesian_2d((0..bars.len() - 1).into_segmented(), 0..max_count)?;
+
+            chart
+                .configure_mesh()
+                .disable_mesh()
+                .x_labels(bars.len())
+                .x_label_formatter(&|value| match value {
+                    SegmentValue::CenterOf(i) => bars[*i].0.clone(),
+                    _ => String::new(),
+                })
+                .y_label_formatter(&|count| locale.number((*count).into()))
+                .y_desc(&locale.games_axis)
+                .label_style(scale.font())
+                .axis_style(Color::FONT_PRIMARY)
+                .draw()?;
+
+            chart.draw_series(bars.iter().enumerate().map(|(i, (_, count, color))| {
+                let mut bar = Rectangle::new(
+                    [
+                        (SegmentValue::Exact(i), 0),
+                        (SegmentValue::Exact(i + 1), *count),
+                    ],
+                    ShapeStyle::from(*color).filled(),
+                );
+                bar.set_margin(0, 0, scale.px(BAR_MARGIN), scale.px(BAR_MARGIN));
+                bar
+            }))?;
+
+            Ok(())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+    use crate::data::fixture;
+
+    #[test]
+    fn errors_name_the_output_path() {
+        let dir = env::temp_dir().join(format!("tbp-viz-{}-read-only", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let writable = fs::metadata(&dir).unwrap().permissions();
+        let mut read_only = writable.clone();
+        read_only.set_readonly(true);
+        fs::set_permissions(&dir, read_only).unwrap();
+        // Privileged users write to read-only directories regardless, but not below files
+        let path = if fs::write(dir.join("probe"), b"").is_ok() {
+            dir.join("probe").join("coop.png")
+        } else {
+            dir.join("coop.png")
+        };
+
+        let res = coop(&path, None, &Locale::default(), &fixture::data());
+        fs::set_permissions(&dir, writable).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let err = format!("{:#}", res.unwrap_err());
+        assert!(err.contains(&*path.to_string_lossy()), "{err}");
+    }
+}
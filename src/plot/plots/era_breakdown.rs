@@ -0,0 +1,163 @@
+use std::{collections::BTreeSet, fs, iter};
+
+use anyhow::{Context, Result, anyhow};
+use plotters::{
+    chart::ChartBuilder,
+    prelude::{BitMapElement, Polygon, Rectangle},
+    style::{IntoTextStyle, ShapeStyle},
+};
+use plotters_backend::text_anchor::{HPos, Pos, VPos};
+use tracing::info;
+
+use crate::{
+    data::{Data, LOGO_FILENAME},
+    plot::{color::Color, img, locale::Locale, output::Output, size::PlotSize},
+};
+
+const DEFAULT_SIZE: PlotSize = PlotSize::new(2048, 1024);
+const MARGIN: u32 = 64;
+const X_LABEL_AREA_SIZE: u32 = 56;
+const Y_LABEL_AREA_SIZE: u32 = 96;
+const X_TICK_SPACING: usize = 10;
+const LEGEND_WIDTH: u32 = 256;
+const LEGEND_ROW_HEIGHT: u32 = 48;
+const LEGEND_SAMPLE_SIZE: i32 = 32;
+const LEGEND_TEXT_GAP: i32 = 16;
+const LOGO_WIDTH: u32 = 170;
+const LOGO_HEIGHT: u32 = 90;
+
+pub fn era_breakdown<'a, O>(
+    output: O,
+    size: Option<PlotSize>,
+    locale: &Locale,
+    data: &Data,
+) -> Result<()>
+where
+    O: Into<Output<'a>>,
+{
+    let output = output.into();
+    let name = output.to_string();
+    info!("Generating visualization {name}");
+
+    draw_era_breakdown(output, size.unwrap_or(DEFAULT_SIZE), locale, data)
+        .with_context(|| format!("Could not generate era breakdown visualization {name}"))?;
+
+    info!("Generated visualization {name}");
+
+    Ok(())
+}
+
+fn draw_era_breakdown(
+    output: Output<'_>,
+    size: PlotSize,
+    locale: &Locale,
+    data: &Data,
+) -> Result<()> {
+    let scale = size.scale_from(DEFAULT_SIZE);
+    let dates = data.dates();
+    if dates.is_empty() {
+        return Err(anyhow!("No lists available"));
+    }
+    let counts = dates
+        .iter()
+        .map(|date| data.games_released_in_decade(&data.lists.0[date]))
+        .collect::<Vec<_>>();
+    let decades = counts
+        .iter()
+        .flat_map(|counts| counts.keys().copied())
+        .collect::<BTreeSet<_>>();
+
+    // Cumulative percentage of games released up to the end of each decade, per episode
+    let mut bounds = vec![vec![0.0; dates.len()]];
+    for decade in &decades {
+        let lower = bounds.last().unwrap();
+        let upper = counts
+            .iter()
+            .zip(lower)
+            .map(|(counts, lower)| {
+                let total = counts.values().sum::<usize>().max(1);
+                lower
+                    + 100.0 * counts.get(decade).copied().unwrap_or_default() as f64 / total as f64
+            })
+            .collect();
+        bounds.push(upper);
+    }
+
+    let description = format!(
+        "Release decades of the games on The Bonus Points list over {} episodes",
+        dates.len()
+    );
+    output.render((size.width, size.height), &description, |root| {
+        root.fill(&Color::BG_PRIMARY)?;
+        let (chart_area, legend_area) =
+            root.split_horizontally(size.width.saturating_sub(scale.text(LEGEND_WIDTH)));
+
+        let mut chart = ChartBuilder::on(&chart_area)
+            .x_label_area_size(scale.text(X_LABEL_AREA_SIZE))
+            .y_label_area_size(scale.text(Y_LABEL_AREA_SIZE))
+            .margin(scale.margin(MARGIN))
+            .build_cartesian_2d(1..dates.len().max(2), 0.0..100.0)?;
+
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            // Plotters overflows when asked for no labels, as with fewer lists than the spacing
+            .x_labels((dates.len() / X_TICK_SPACING).max(1))
+            .y_label_formatter(&|percent| format!("{percent:.0}"))
+            .x_label_formatter(&|episode| locale.number(*episode as u64))
+            .x_desc(&locale.episode)
+            .y_desc(&locale.games_share_axis)
+            .label_style(scale.font())
+            .axis_style(Color::FONT_PRIMARY)
+            .draw()?;
+
+        for (i, (lower, upper)) in bounds.iter().zip(&bounds[1..]).enumerate() {
+            let episodes = 1..=dates.len();
+            chart.draw_series(iter::once(Polygon::new(
+                episodes
+                    .clone()
+                    .zip(upper.iter().copied())
+                    .chain(episodes.rev().zip(lower.iter().copied().rev()))
+                    .collect::<Vec<_>>(),
+                Color::ACCENTS[i % Color::ACCENTS.len()],
+            )))?;
+        }
+
+        let logo = img::load(
+            &fs::read(LOGO_FILENAME)?,
+            scale.px(LOGO_WIDTH),
+            scale.px(LOGO_HEIGHT),
+            Color::BG_PRIMARY,
+            scale.filter(),
+        )?;
+        legend_area.draw(&BitMapElement::from((
+            (0, scale.margin(MARGIN) as i32),
+            logo,
+        )))?;
+
+        // Newest decade first, matching the top-to-bottom order of the stacked areas
+        let legend_top = (scale.margin(MARGIN) + scale.px(LOGO_HEIGHT)) as i32
+            + scale.text(LEGEND_ROW_HEIGHT) as i32;
+        let sample_size = scale.offset(LEGEND_SAMPLE_SIZE);
+        for (row, (i, decade)) in decades.iter().enumerate().rev().enumerate() {
+            let y = legend_top + row as i32 * scale.text(LEGEND_ROW_HEIGHT) as i32;
+            legend_area.draw(&Rectangle::new(
+                [(0, y - sample_size / 2), (sample_size, y + sample_size / 2)],
+                ShapeStyle::from(Color::ACCENTS[i % Color::ACCENTS.len()]).filled(),
+            ))?;
+            legend_area.draw_text(
+                &locale.decade(*decade),
+                &scale
+                    .font()
+                    .with_anchor::<Color>(Pos {
+                        h_pos: HPos::Left,
+                        v_pos: VPos::Center,
+                    })
+                    .into_text_style(&legend_area),
+                (sample_size + scale.offset(LEGEND_TEXT_GAP), y),
+            )?;
+        }
+
+        Ok(())
+    })
+}
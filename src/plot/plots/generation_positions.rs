@@ -0,0 +1,178 @@
+use std::fs;
+
+use anyhow::{Context, Result, anyhow};
+use plotters::{
+    chart::ChartBuilder,
+    coord::ranged1d::{IntoSegmentedCoord, SegmentValue},
+    prelude::{BitMapElement, PathElement, Rectangle},
+    style::ShapeStyle,
+};
+use tracing::info;
+
+use crate::{
+    data::{Data, LOGO_FILENAME, stats},
+    plot::{color::Color, img, locale::Locale, output::Output, size::PlotSize},
+};
+
+const DEFAULT_SIZE: PlotSize = PlotSize::new(2048, 1556);
+const MARGIN: u32 = 64;
+const LOGO_MARGIN: i32 = 16;
+const LOGO_WIDTH: u32 = 170;
+const LOGO_HEIGHT: u32 = 90;
+const X_LABEL_AREA_SIZE: u32 = 96;
+const Y_LABEL_AREA_SIZE: u32 = 96;
+const BOX_MARGIN: u32 = 48;
+const WHISKER_MARGIN: u32 = 96;
+const STROKE_WIDTH: u32 = 3;
+
+pub fn generation_positions<'a, O>(
+    output: O,
+    size: Option<PlotSize>,
+    locale: &Locale,
+    data: &Data,
+) -> Result<()>
+where
+    O: Into<Output<'a>>,
+{
+    let output = output.into();
+    let name = output.to_string();
+    info!("Generating visualization {name}");
+
+    draw_generation_positions(output, size.unwrap_or(DEFAULT_SIZE), locale, data)
+        .with_context(|| format!("Could not generate generation positions visualization {name}"))?;
+
+    info!("Generated visualization {name}");
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_lines)]
+fn draw_generation_positions(
+    output: Output<'_>,
+    size: PlotSize,
+    locale: &Locale,
+    data: &Data,
+) -> Result<()> {
+    let scale = size.scale_from(DEFAULT_SIZE);
+    let num_games = data
+        .latest()
+        .ok_or_else(|| anyhow!("Latest list doesn't exist"))?
+        .0
+        .len();
+    let groups = data
+        .positions_by_generation()
+        .into_iter()
+        .filter_map(|(generation, positions)| {
+            stats::quartiles(
+                positions
+                    .iter()
+                    .map(|&position| position as f64)
+                    .collect::<Vec<_>>()
+                    .as_slice(),
+            )
+            .map(|quartiles| {
+                (
+                    generation.map_or_else(
+                        || locale.pc_os.clone(),
+                        |generation| locale.generation(generation),
+                    ),
+                    quartiles,
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    if groups.is_empty() {
+        return Err(anyhow!("No games on the latest list have metadata"));
+    }
+
+    let description =
+        format!("Bonus Points ranking of {num_games} games by earliest console generation");
+    output.render((size.width, size.height), &description, |root| {
+        root.fill(&Color::BG_PRIMARY)?;
+
+        let logo = img::load(
+            &fs::read(LOGO_FILENAME)?,
+            scale.px(LOGO_WIDTH),
+            scale.px(LOGO_HEIGHT),
+            Color::BG_PRIMARY,
+            scale.filter(),
+        )?;
+        let logo_margin = scale.offset(LOGO_MARGIN);
+        root.draw(&BitMapElement::from(((logo_margin, logo_margin), logo)))?;
+
+        let mut chart = ChartBuilder::on(root)
+            .x_label_area_size(scale.text(X_LABEL_AREA_SIZE))
+            .y_label_area_size(scale.text(Y_LABEL_AREA_SIZE))
+            .margin(scale.margin(MARGIN))
+            .build_cartesian_2d(
+                (0..groups.len() - 1).into_segmented(),
+                (num_games.saturating_sub(1) as f64)..0.0,
+            )?;
+
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .x_labels(groups.len())
+            .x_label_formatter(&|value| match value {
+                SegmentValue::CenterOf(i) => groups[*i].0.clone(),
+                _ => String::new(),
+            })
+            .x_desc(&locale.console_generation)
+            .y_label_formatter(&|position| locale.number(position.round() as u64 + 1))
+            .y_desc(&locale.ranking)
+            .label_style(scale.font())
+            .axis_style(Color::FONT_PRIMARY)
+            .draw()?;
+
+        let line_style = ShapeStyle::from(Color::FONT_PRIMARY).stroke_width(scale.px(STROKE_WIDTH));
+        for (i, (_, quartiles)) in groups.iter().enumerate() {
+            let horizontal = |y: f64, margin: u32, style: ShapeStyle| {
+                let mut line = Rectangle::new(
+                    [(SegmentValue::Exact(i), y), (SegmentValue::Exact(i + 1), y)],
+                    style,
+                );
+                line.set_margin(0, 0, scale.px(margin), scale.px(margin));
+                line
+            };
+
+            chart.draw_series([
+                PathElement::new(
+                    vec![
+                        (SegmentValue::CenterOf(i), quartiles.min),
+                        (SegmentValue::CenterOf(i), quartiles.q1),
+                    ],
+                    line_style,
+                ),
+                PathElement::new(
+                    vec![
+                        (SegmentValue::CenterOf(i), quartiles.q3),
+                        (SegmentValue::CenterOf(i), quartiles.max),
+                    ],
+                    line_style,
+                ),
+            ])?;
+
+            let mut quartile_box = Rectangle::new(
+                [
+                    (SegmentValue::Exact(i), quartiles.q1),
+                    (SegmentValue::Exact(i + 1), quartiles.q3),
+                ],
+                ShapeStyle::from(Color::ACCENT_BLUE).filled(),
+            );
+            quartile_box.set_margin(0, 0, scale.px(BOX_MARGIN), scale.px(BOX_MARGIN));
+
+            chart.draw_series([
+                quartile_box,
+                horizontal(quartiles.min, WHISKER_MARGIN, line_style),
+                horizontal(quartiles.max, WHISKER_MARGIN, line_style),
+                horizontal(
+                    quartiles.median,
+                    BOX_MARGIN,
+                    ShapeStyle::from(Color::ACCENT_YELLOW).stroke_width(scale.px(STROKE_WIDTH)),
+                ),
+            ])?;
+        }
+
+        Ok(())
+    })
+}
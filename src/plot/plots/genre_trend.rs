@@ -0,0 +1,143 @@
+use std::{fs, iter};
+
+use anyhow::{Context, Result, anyhow};
+use plotters::{
+    chart::ChartBuilder,
+    coord::ranged1d::{IntoSegmentedCoord, SegmentValue},
+    prelude::{BitMapElement, PathElement, Rectangle},
+    style::ShapeStyle,
+};
+use tracing::info;
+
+use crate::{
+    data::{Data, LOGO_FILENAME},
+    plot::{color::Color, img, locale::Locale, output::Output, size::PlotSize},
+};
+
+const DEFAULT_SIZE: PlotSize = PlotSize::new(2048, 1556);
+const MARGIN: u32 = 64;
+const LOGO_MARGIN: i32 = 16;
+const LOGO_WIDTH: u32 = 170;
+const LOGO_HEIGHT: u32 = 90;
+const X_LABEL_AREA_SIZE: u32 = 96;
+const Y_LABEL_AREA_SIZE: u32 = 384;
+const BAR_MARGIN: u32 = 8;
+const STROKE_WIDTH: u32 = 3;
+/// Number of genres shown, those with the steepest trends
+const MAX_GENRES: usize = 20;
+
+/// Trend in the share of each genre in the top half of the list, as bars diverging from zero
+pub fn genre_trend<'a, O>(
+    output: O,
+    size: Option<PlotSize>,
+    locale: &Locale,
+    data: &Data,
+) -> Result<()>
+where
+    O: Into<Output<'a>>,
+{
+    let output = output.into();
+    let name = output.to_string();
+    info!("Generating visualization {name}");
+
+    draw_genre_trend(output, size.unwrap_or(DEFAULT_SIZE), locale, data)
+        .with_context(|| format!("Could not generate genre trend visualization {name}"))?;
+
+    info!("Generated visualization {name}");
+
+    Ok(())
+}
+
+fn draw_genre_trend(
+    output: Output<'_>,
+    size: PlotSize,
+    locale: &Locale,
+    data: &Data,
+) -> Result<()> {
+    let scale = size.scale_from(DEFAULT_SIZE);
+    let mut trends = data.genre_trend().into_iter().collect::<Vec<_>>();
+    if trends.is_empty() {
+        return Err(anyhow!("No genre has a trend over several lists"));
+    }
+    trends.sort_by(|(a_genre, a), (b_genre, b)| {
+        b.abs()
+            .total_cmp(&a.abs())
+            .then_with(|| a_genre.cmp(b_genre))
+    });
+    trends.truncate(MAX_GENRES);
+    // Rising genres at the top, falling ones at the bottom
+    trends.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    let max_slope = trends
+        .iter()
+        .map(|(_, slope)| slope.abs())
+        .fold(0.0, f64::max)
+        .max(f64::EPSILON);
+
+    output.render(
+        (size.width, size.height),
+        "Trends in the genres of the games in the top half of The Bonus Points list",
+        |root| {
+            root.fill(&Color::BG_PRIMARY)?;
+
+            let logo = img::load(
+                &fs::read(LOGO_FILENAME)?,
+                scale.px(LOGO_WIDTH),
+                scale.px(LOGO_HEIGHT),
+                Color::BG_PRIMARY,
+                scale.filter(),
+            )?;
+            let logo_margin = scale.offset(LOGO_MARGIN);
+            root.draw(&BitMapElement::from(((logo_margin, logo_margin), logo)))?;
+
+            let mut chart = ChartBuilder::on(root)
+                .x_label_area_size(scale.text(X_LABEL_AREA_SIZE))
+                .y_label_area_size(scale.text(Y_LABEL_AREA_SIZE))
+                .margin(scale.margin(MARGIN))
+                .build_cartesian_2d(
+                    -max_slope..max_slope,
+                    (0..trends.len() - 1).into_segmented(),
+                )?;
+
+            chart
+                .configure_mesh()
+                .disable_mesh()
+                .y_labels(trends.len())
+                .y_label_formatter(&|value| match value {
+                    SegmentValue::CenterOf(i) => trends[*i].0.clone(),
+                    _ => String::new(),
+                })
+                .x_label_formatter(&|slope| format!("{:+.1}", slope * 100.0))
+                .x_desc(&locale.genre_trend_axis)
+                .label_style(scale.font())
+                .axis_style(Color::FONT_PRIMARY)
+                .draw()?;
+
+            chart.draw_series(trends.iter().enumerate().map(|(i, (_, slope))| {
+                let color = if *slope < 0.0 {
+                    Color::ACCENT_PINK
+                } else {
+                    Color::ACCENT_BLUE
+                };
+                let mut bar = Rectangle::new(
+                    [
+                        (0.0, SegmentValue::Exact(i)),
+                        (*slope, SegmentValue::Exact(i + 1)),
+                    ],
+                    ShapeStyle::from(color).filled(),
+                );
+                bar.set_margin(scale.px(BAR_MARGIN), scale.px(BAR_MARGIN), 0, 0);
+                bar
+            }))?;
+
+            chart.draw_series(iter::once(PathElement::new(
+                vec![
+                    (0.0, SegmentValue::Exact(0)),
+                    (0.0, SegmentValue::Exact(trends.len())),
+                ],
+                ShapeStyle::from(Color::FONT_PRIMARY).stroke_width(scale.px(STROKE_WIDTH)),
+            )))?;
+
+            Ok(())
+        },
+    )
+}
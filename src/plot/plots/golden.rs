@@ -0,0 +1,316 @@
+//! Golden-image tests, rendering every plot of the fixture and comparing it to a reference PNG
+//!
+//! The references are regenerated by running the tests with `UPDATE_GOLDENS` set. Differing
+//! renders are written to `target/golden` for inspection.
+
+use std::{env, fs, path::PathBuf, sync::Arc};
+
+use image::RgbImage;
+
+use super::{
+    EpisodeLabels, ListOverTimeOptions, RankingDifferenceOptions, SmoothingParams, SummarySegment,
+    bubble_chart, coop, episode_summary, era_breakdown, generation_positions, genre_trend,
+    list_over_time, list_over_time_legend, ranking_difference, release_dates, summary,
+};
+use crate::{
+    data::{Data, GameId, RatingKind, RatingWeighting, fixture},
+    plot::{Locale, Output, PlotSize, Preset},
+};
+
+const UPDATE_VAR: &str = "UPDATE_GOLDENS";
+/// Difference in any channel beyond which pixels count as differing, allowing for antialiasing
+const CHANNEL_TOLERANCE: u8 = 48;
+/// Fraction of pixels which may differ, allowing for differently rendered fonts
+const PIXEL_TOLERANCE: f64 = 0.01;
+const STROKE_WIDTH: u32 = 2;
+const LIST_OVER_TIME: ListOverTimeOptions = ListOverTimeOptions {
+    size: None,
+    scale: false,
+    labels: EpisodeLabels {
+        count: None,
+        angle: 0.0,
+        dated: false,
+    },
+    stroke_width: STROKE_WIDTH,
+    mark_extremes: false,
+    predict: false,
+    thumbnails: None,
+    highlight: None,
+};
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/plot/plots/golden")
+        .join(format!("{name}.png"))
+}
+
+/// Compares `png` to the reference of `name`, or replaces the reference if `UPDATE_GOLDENS` is set
+fn check(name: &str, png: &[u8]) {
+    let path = golden_path(name);
+    if env::var_os(UPDATE_VAR).is_some() {
+        fs::write(&path, png).unwrap();
+        return;
+    }
+    let expected = fs::read(&path).unwrap_or_else(|err| {
+        panic!(
+            "Could not read {}, run with {UPDATE_VAR}=1 to create it: {err}",
+            path.display()
+        )
+    });
+    let expected = decode(&expected);
+    let actual = decode(png);
+    if let Err(msg) = compare(&expected, &actual) {
+        let actual_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("target/golden")
+            .join(format!("{name}.png"));
+        fs::create_dir_all(actual_path.parent().unwrap()).unwrap();
+        fs::write(&actual_path, png).unwrap();
+        panic!(
+            "{name} differs from {}: {msg}. Rendered as {}, run with {UPDATE_VAR}=1 if intended",
+            path.display(),
+            actual_path.display()
+        );
+    }
+}
+
+fn decode(png: &[u8]) -> RgbImage {
+    image::load_from_memory(png).unwrap().to_rgb8()
+}
+
+fn compare(expected: &RgbImage, actual: &RgbImage) -> Result<(), String> {
+    if expected.dimensions() != actual.dimensions() {
+        return Err(format!(
+            "size is {:?} instead of {:?}",
+            actual.dimensions(),
+            expected.dimensions()
+        ));
+    }
+    let differing = expected
+        .pixels()
+        .zip(actual.pixels())
+        .filter(|(expected, actual)| {
+            expected
+                .0
+                .iter()
+                .zip(actual.0)
+                .any(|(&expected, actual)| expected.abs_diff(actual) > CHANNEL_TOLERANCE)
+        })
+        .count();
+    let fraction = differing as f64 / f64::from(expected.width() * expected.height());
+    if fraction > PIXEL_TOLERANCE {
+        return Err(format!("{:.2}% of pixels differ", fraction * 100.0));
+    }
+    Ok(())
+}
+
+/// Renders a plot of the fixture with `draw` and compares it to the reference of `name`
+fn check_plot<F>(name: &str, draw: F)
+where
+    F: FnOnce(Output<'_>, &Locale, &Data) -> anyhow::Result<()>,
+{
+    let mut png = Vec::new();
+    draw(Output::from(&mut png), &Locale::default(), &fixture::data()).unwrap();
+    check(name, &png);
+}
+
+#[test]
+fn list_over_time_golden() {
+    check_plot("list_over_time", |output, locale, data| {
+        list_over_time(output, &LIST_OVER_TIME, locale, data)
+    });
+}
+
+#[test]
+fn list_over_time_scaled_golden() {
+    check_plot("list_over_time_scaled", |output, locale, data| {
+        list_over_time(
+            output,
+            &ListOverTimeOptions {
+                scale: true,
+                labels: EpisodeLabels {
+                    dated: true,
+                    ..LIST_OVER_TIME.labels
+                },
+                mark_extremes: true,
+                predict: true,
+                highlight: Some(&GameId::Igdb(102)),
+                ..LIST_OVER_TIME
+            },
+            locale,
+            data,
+        )
+    });
+}
+
+/// Size of the preset of `name`
+fn preset(name: &str) -> PlotSize {
+    name.parse::<Preset>().unwrap().size
+}
+
+#[test]
+fn list_over_time_preset_goldens() {
+    for name in ["twitter", "square"] {
+        check_plot(&format!("list_over_time_{name}"), |output, locale, data| {
+            list_over_time(
+                output,
+                &ListOverTimeOptions {
+                    size: Some(preset(name)),
+                    ..LIST_OVER_TIME
+                },
+                locale,
+                data,
+            )
+        });
+    }
+}
+
+#[test]
+fn list_over_time_legend_golden() {
+    check_plot("list_over_time_legend", |output, _, data| {
+        list_over_time_legend(output, None, STROKE_WIDTH, data)
+    });
+}
+
+#[test]
+fn release_dates_golden() {
+    check_plot("release_dates", |output, locale, data| {
+        release_dates(
+            output,
+            None,
+            locale,
+            data,
+            SmoothingParams::default(),
+            |_| 1.0,
+        )
+    });
+}
+
+#[test]
+fn coop_golden() {
+    check_plot("coop", |output, locale, data| {
+        coop(output, None, locale, data)
+    });
+}
+
+#[test]
+fn coop_preset_goldens() {
+    for name in ["twitter", "square"] {
+        check_plot(&format!("coop_{name}"), |output, locale, data| {
+            coop(output, Some(preset(name)), locale, data)
+        });
+    }
+}
+
+#[test]
+fn era_breakdown_golden() {
+    check_plot("era_breakdown", |output, locale, data| {
+        era_breakdown(output, None, locale, data)
+    });
+}
+
+#[test]
+fn bubble_chart_golden() {
+    check_plot("bubble_chart", |output, locale, data| {
+        bubble_chart(output, None, locale, data)
+    });
+}
+
+#[test]
+fn generation_positions_golden() {
+    check_plot("generation_positions", |output, locale, data| {
+        generation_positions(output, None, locale, data)
+    });
+}
+
+#[test]
+fn genre_trend_golden() {
+    check_plot("genre_trend", |output, locale, data| {
+        genre_trend(output, None, locale, data)
+    });
+}
+
+#[test]
+fn ranking_difference_golden() {
+    check_plot("ranking_difference", |output, locale, data| {
+        ranking_difference(
+            Some(output),
+            &RankingDifferenceOptions {
+                size: None,
+                kind: RatingKind::User,
+                weighting: RatingWeighting::Raw,
+                distinct_colors: false,
+                stroke_width: STROKE_WIDTH,
+                thumbnails: None,
+            },
+            locale,
+            data,
+        )
+    });
+}
+
+#[test]
+fn ranking_difference_weighted_golden() {
+    check_plot("ranking_difference_weighted", |output, locale, data| {
+        ranking_difference(
+            Some(output),
+            &RankingDifferenceOptions {
+                size: None,
+                kind: RatingKind::Total,
+                weighting: RatingWeighting::Bayesian,
+                distinct_colors: true,
+                stroke_width: STROKE_WIDTH,
+                thumbnails: None,
+            },
+            locale,
+            data,
+        )
+    });
+}
+
+#[tokio::test]
+async fn summary_golden() {
+    let mut png = Vec::new();
+    summary(
+        &mut png,
+        None,
+        &[],
+        &Locale::default(),
+        Arc::new(fixture::data()),
+    )
+    .await
+    .unwrap();
+    check("summary", &png);
+}
+
+#[tokio::test]
+async fn summary_custom_segments_golden() {
+    let segments = "normalized-list-toppers:3,risers:2,fallers:2,genres,developers,full-ranking"
+        .split(',')
+        .map(|segment| segment.parse().unwrap())
+        .collect::<Vec<SummarySegment>>();
+    let mut png = Vec::new();
+    summary(
+        &mut png,
+        None,
+        &segments,
+        &Locale::default(),
+        Arc::new(fixture::data()),
+    )
+    .await
+    .unwrap();
+    check("summary_custom_segments", &png);
+}
+
+#[tokio::test]
+async fn episode_summary_golden() {
+    let mut png = Vec::new();
+    episode_summary(
+        &mut png,
+        None,
+        &Locale::default(),
+        Arc::new(fixture::data()),
+    )
+    .await
+    .unwrap();
+    check("episode_summary", &png);
+}
@@ -0,0 +1,263 @@
+use std::{fs, sync::Arc};
+
+use anyhow::{Context, Result, anyhow};
+use plotters::{
+    prelude::{BitMapElement, Polygon, Rectangle},
+    style::{IntoTextStyle, ShapeStyle},
+};
+use plotters_backend::text_anchor::{HPos, Pos, VPos};
+use tracing::{info, warn};
+
+use crate::{
+    data::{Data, Iso8601Date, LOGO_FILENAME},
+    plot::{color::Color, font::Font, img, locale::Locale, output::Output, text},
+    request::resource::ImageSize,
+};
+
+const MARGIN: u32 = 32;
+const HEADER_HEIGHT: u32 = 128;
+const COLUMN_WIDTH: u32 = 768;
+const COLUMN_GAP: u32 = 32;
+const MAX_ROWS: usize = 35;
+const MAX_COLUMNS: usize = 3;
+const ROW_HEIGHT: u32 = 72;
+const RANK_WIDTH: u32 = 72;
+const THUMB_WIDTH: u32 = 48;
+const THUMB_HEIGHT: u32 = 64;
+const ITEM_GAP: u32 = 16;
+const DELTA_WIDTH: u32 = 96;
+const DELTA_MARKER_SIZE: u32 = 8;
+const LOGO_WIDTH: u32 = 170;
+const LOGO_HEIGHT: u32 = 90;
+const TITLE_FONT_SIZE: u32 = 64;
+const FONT_SIZE: u32 = 32;
+const LINE_SPACING: f64 = 1.0;
+const MAX_NAME_LINES: usize = 2;
+
+#[derive(Debug, Clone, Copy)]
+enum Delta {
+    New,
+    Moved(i32),
+}
+
+/// Card listing the full ranking on `date`, with position changes since the previous list
+#[allow(clippy::similar_names)]
+pub async fn list_card<'a, O>(
+    output: O,
+    date: Iso8601Date,
+    locale: &Locale,
+    data: Arc<Data>,
+) -> Result<()>
+where
+    O: Into<Output<'a>>,
+{
+    let output = output.into();
+    let name = output.to_string();
+    info!("Generating visualization {name}");
+
+    draw_list_card(output, date, locale, data)
+        .await
+        .with_context(|| format!("Could not generate list card visualization {name}"))?;
+
+    info!("Generated visualization {name}");
+
+    Ok(())
+}
+
+/// Images drawn in the list card of `date`
+#[allow(clippy::similar_names)]
+pub fn resource_urls(data: &Data, date: Iso8601Date) -> Vec<(ImageSize, String)> {
+    data.list_at(date)
+        .into_iter()
+        .flat_map(|list| list.0.iter())
+        .filter_map(|id| data.metas.0.get(id)?.cover.as_ref())
+        .map(|cover| (ImageSize::CoverSmall, cover.url.clone()))
+        .collect()
+}
+
+#[allow(clippy::too_many_lines, clippy::similar_names)]
+async fn draw_list_card(
+    output: Output<'_>,
+    date: Iso8601Date,
+    locale: &Locale,
+    data: Arc<Data>,
+) -> Result<()> {
+    let list = data
+        .list_at(date)
+        .ok_or_else(|| anyhow!("List for {date} doesn't exist"))?;
+    let previous_list = data.previous_list(date);
+    let num_games = list.0.len();
+    let num_columns = num_games.div_ceil(MAX_ROWS).clamp(1, MAX_COLUMNS);
+    let num_rows = num_games.div_ceil(num_columns);
+
+    let mut covers = data.res.get_all(resource_urls(&data, date)).await?;
+    let covers = list
+        .0
+        .iter()
+        .map(|id| {
+            let url = data.metas.0.get(id)?.cover.as_ref()?.url.clone();
+            match covers.remove(&(ImageSize::CoverSmall, url.clone()))? {
+                Ok(cover) => Some((url, cover)),
+                Err(err) => {
+                    warn!("Using placeholder for cover of {id}: {err:#}");
+                    None
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let width = num_columns as u32 * (COLUMN_WIDTH + COLUMN_GAP) - COLUMN_GAP + 2 * MARGIN;
+    let height = HEADER_HEIGHT + num_rows as u32 * ROW_HEIGHT + 2 * MARGIN;
+    let description = format!("The Bonus Points list of {num_games} games on {date}");
+    output.render((width, height), &description, |root| {
+        root.fill(&Color::BG_PRIMARY)?;
+        let root = root.margin(MARGIN, MARGIN, MARGIN, MARGIN);
+
+        root.draw_text(
+            &locale.list_card_title(date.0),
+            &Font::new(TITLE_FONT_SIZE)
+                .with_anchor::<Color>(Pos {
+                    h_pos: HPos::Left,
+                    v_pos: VPos::Center,
+                })
+                .into_text_style(&root),
+            (0, (HEADER_HEIGHT / 2) as i32),
+        )?;
+
+        let logo = img::load(
+            &fs::read(LOGO_FILENAME)?,
+            LOGO_WIDTH,
+            LOGO_HEIGHT,
+            Color::BG_PRIMARY,
+            None,
+        )?;
+        root.draw(&BitMapElement::from((
+            (
+                (width - 2 * MARGIN - LOGO_WIDTH) as i32,
+                ((HEADER_HEIGHT - LOGO_HEIGHT) / 2) as i32,
+            ),
+            logo,
+        )))?;
+
+        for (i, id) in list.0.iter().enumerate() {
+            let x = ((i / num_rows) as u32 * (COLUMN_WIDTH + COLUMN_GAP)) as i32;
+            let y = (HEADER_HEIGHT + (i % num_rows) as u32 * ROW_HEIGHT) as i32;
+            let center_y = y + (ROW_HEIGHT / 2) as i32;
+            let font = |h_pos, color| {
+                Font::new(FONT_SIZE)
+                    .with_color(color)
+                    .with_anchor::<Color>(Pos {
+                        h_pos,
+                        v_pos: VPos::Center,
+                    })
+                    .into_text_style(&root)
+            };
+
+            if i % 2 == 1 {
+                root.draw(&Rectangle::new(
+                    [(x, y), (x + COLUMN_WIDTH as i32, y + ROW_HEIGHT as i32)],
+                    ShapeStyle::from(Color::BG_SECONDARY).filled(),
+                ))?;
+            }
+            let bg = if i % 2 == 1 {
+                Color::BG_SECONDARY
+            } else {
+                Color::BG_PRIMARY
+            };
+
+            root.draw_text(
+                &locale.number(i as u64 + 1),
+                &font(HPos::Right, Color::FONT_PRIMARY),
+                (x + RANK_WIDTH as i32, center_y),
+            )?;
+
+            let thumb_x = x + (RANK_WIDTH + ITEM_GAP) as i32;
+            let thumb_y = y + ((ROW_HEIGHT - THUMB_HEIGHT) / 2) as i32;
+            let image = match &covers[i] {
+                Some((url, cover)) => data.res.images().load(
+                    ImageSize::CoverSmall,
+                    url,
+                    cover,
+                    THUMB_WIDTH,
+                    THUMB_HEIGHT,
+                    bg,
+                    None,
+                )?,
+                None => img::placeholder(
+                    THUMB_WIDTH,
+                    THUMB_HEIGHT,
+                    data.metas.0.get(id).map_or("", |meta| meta.name.as_str()),
+                    if i % 2 == 1 {
+                        Color::BG_PRIMARY
+                    } else {
+                        Color::BG_SECONDARY
+                    },
+                    Color::FONT_PRIMARY,
+                )?,
+            };
+            root.draw(&BitMapElement::from((
+                (
+                    thumb_x + ((THUMB_WIDTH - image.width()) / 2) as i32,
+                    thumb_y + ((THUMB_HEIGHT - image.height()) / 2) as i32,
+                ),
+                image,
+            )))?;
+
+            let name_x = thumb_x + (THUMB_WIDTH + ITEM_GAP) as i32;
+            text::draw_wrapped_text(
+                &root,
+                data.metas.0.get(id).map_or("", |meta| meta.name.as_str()),
+                &font(HPos::Left, Color::FONT_PRIMARY),
+                (name_x, center_y),
+                (x + (COLUMN_WIDTH - DELTA_WIDTH - 2 * ITEM_GAP) as i32 - name_x) as u32,
+                MAX_NAME_LINES,
+                LINE_SPACING,
+            )?;
+
+            let delta = previous_list.map(|previous_list| {
+                previous_list
+                    .0
+                    .iter()
+                    .position(|x| x == id)
+                    .map_or(Delta::New, |previous| {
+                        Delta::Moved(previous as i32 - i as i32)
+                    })
+            });
+            let delta_x = x + COLUMN_WIDTH as i32 - ITEM_GAP as i32;
+            match delta {
+                Some(Delta::New) => {
+                    root.draw_text(
+                        &locale.new_entry,
+                        &font(HPos::Right, Color::ACCENT_YELLOW),
+                        (delta_x, center_y),
+                    )?;
+                }
+                Some(Delta::Moved(delta)) if delta != 0 => {
+                    let color = if delta > 0 {
+                        Color::ACCENT_BLUE
+                    } else {
+                        Color::ACCENT_PINK
+                    };
+                    let marker_x = delta_x - DELTA_WIDTH as i32 + DELTA_MARKER_SIZE as i32;
+                    let tip = DELTA_MARKER_SIZE as i32 * delta.signum();
+                    root.draw(&Polygon::new(
+                        vec![
+                            (marker_x, center_y - tip),
+                            (marker_x - DELTA_MARKER_SIZE as i32, center_y + tip),
+                            (marker_x + DELTA_MARKER_SIZE as i32, center_y + tip),
+                        ],
+                        ShapeStyle::from(color).filled(),
+                    ))?;
+                    root.draw_text(
+                        &locale.number(delta.unsigned_abs().into()),
+                        &font(HPos::Right, color),
+                        (delta_x, center_y),
+                    )?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    })
+}
@@ -1,29 +1,47 @@
-use std::{fs, iter, path::Path};
+use std::{
+    collections::HashMap,
+    f64::consts::{FRAC_PI_2, FRAC_PI_4},
+    fs, iter,
+};
 
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
 use plotters::{
     chart::ChartBuilder,
-    prelude::{BitMapBackend, BitMapElement, IntoDrawingArea, Polygon},
-    series::LineSeries,
+    prelude::{BindKeyPoints, BitMapElement, PathElement, Polygon},
+    series::{DashedLineSeries, LineSeries},
+    style::{Color as _, IntoTextStyle, ShapeStyle},
 };
+use plotters_backend::text_anchor::{HPos, Pos, VPos};
 use tracing::info;
 
 use crate::{
-    data::{Data, LOGO_FILENAME},
+    data::{Data, GameId, LOGO_FILENAME, predict},
     plot::{
+        annotate::{self, Annotation, Direction},
         color::{Color, ColorIterator},
         font::Font,
         img,
+        locale::Locale,
         marker::{Marker, MarkerKind},
+        output::Output,
+        size::{PlotSize, Scale},
+        text,
+        thumbnail::{LABEL_DISTANCE, Side, Thumbnails},
     },
 };
 
-const WIDTH: u32 = 2048;
-const HEIGHT: u32 = 1556;
+const DEFAULT_SIZE: PlotSize = PlotSize::new(2048, 1556);
 const MARGIN: u32 = 32;
 const X_LABEL_AREA_SIZE: u32 = 56;
 const Y_LABEL_AREA_SIZE: u32 = 384;
+/// Height of the label area above the plot when it also holds a row of rotated dates
+const DATED_X_LABEL_AREA_SIZE: u32 = 176;
+/// Number of episodes between labelled ones, unless a number of labels is given
 const X_TICK_SPACING: usize = 10;
+/// Angle of the dates above the episode numbers, counterclockwise from horizontal
+const DATE_ANGLE: f64 = FRAC_PI_4;
+/// Gap between the episode numbers and the dates above them
+const DATE_GAP: i32 = 16;
 const LOGO_WIDTH_SCALE: u32 = 204;
 const LOGO_WIDTH_NOSCALE: u32 = 510;
 const LOGO_HEIGHT_SCALE: u32 = 188;
@@ -33,21 +51,126 @@ const LOGO_X_NOSCALE: usize = 0;
 const LOGO_Y_SCALE: f64 = 0.01;
 const LOGO_Y_NOSCALE: f64 = 0.8;
 const FINAL_WIDTH: usize = 5;
+const ANNOTATION_DASH: u32 = 8;
+const ANNOTATION_GAP: u32 = 8;
+/// Distance of the annotations' labels from their lines and the bottom of the plot
+const ANNOTATION_LABEL_GAP: i32 = 8;
+/// Width, in episodes, of the stretch past the latest episode holding the predictions
+const PREDICTION_WIDTH: usize = 5;
+const PREDICTION_DASH: u32 = 8;
+const PREDICTION_GAP: u32 = 6;
+/// Opacity of the confidence bands of the predictions, relative to their lines
+const PREDICTION_OPACITY: f64 = 0.2;
 
 const COLOR_SPACING: usize = 4;
+/// Fraction of the way from the background to the text color of the lines of games not
+/// highlighted
+const MUTED_LIGHTNESS: f64 = 0.4;
+const DIMMED_OPACITY: f64 = 0.6;
+const MARKER_SIZE: u32 = 5;
+/// Size of the markers around the first and last games of every list
+const EXTREME_MARKER_SIZE: u32 = 10;
+/// Multiple of the stroke width with which the highlighted line is drawn
+const HIGHLIGHT_STROKE_FACTOR: u32 = 2;
 
-#[allow(clippy::too_many_lines)]
-pub fn list_over_time<P>(path: P, scale: bool, data: &Data) -> Result<()>
+const LEGEND_DEFAULT_SIZE: PlotSize = PlotSize::new(2048, 0);
+const LEGEND_MARGIN: u32 = 32;
+const LEGEND_COLUMNS: usize = 4;
+const LEGEND_ROW_HEIGHT: u32 = 40;
+const LEGEND_SAMPLE_WIDTH: i32 = 48;
+const LEGEND_TEXT_GAP: i32 = 16;
+
+/// Labelling of the episodes on the x axis of the list over time
+#[derive(Debug, Clone, Copy)]
+pub struct EpisodeLabels {
+    /// Approximate number of labelled episodes, instead of one every `X_TICK_SPACING` episodes
+    pub count: Option<usize>,
+    /// Angle of the episode numbers in radians, counterclockwise from horizontal
+    pub angle: f64,
+    /// Whether the dates of the lists are drawn above the numbers
+    pub dated: bool,
+}
+
+fn marker_kind(i: usize) -> MarkerKind {
+    match (i / COLOR_SPACING) % MarkerKind::COUNT {
+        0 => MarkerKind::Triangle,
+        1 => MarkerKind::Circle,
+        2 => MarkerKind::Cross,
+        _ => unreachable!(),
+    }
+}
+
+/// Options of the list over time
+#[derive(Clone, Copy)]
+pub struct ListOverTimeOptions<'a> {
+    /// Size of the image, instead of the default size
+    pub size: Option<PlotSize>,
+    /// Whether the positions are scaled to the length of each list, instead of being absolute
+    pub scale: bool,
+    /// Labelling of the episodes on the x axis
+    pub labels: EpisodeLabels,
+    /// Width of the lines and markers in pixels at the default size
+    pub stroke_width: u32,
+    /// Whether the first and last games of every list are ringed
+    pub mark_extremes: bool,
+    /// Whether the positions on the next list are predicted
+    pub predict: bool,
+    /// Thumbnails labelling the games, instead of their names
+    pub thumbnails: Option<&'a Thumbnails>,
+    /// Game whose line is emphasized, with all other lines dimmed
+    pub highlight: Option<&'a GameId>,
+}
+
+/// Position of every game on every list, with the line of the highlighted game, if any,
+/// emphasized and all other lines dimmed
+pub fn list_over_time<'a, O>(
+    output: O,
+    options: &ListOverTimeOptions<'_>,
+    locale: &Locale,
+    data: &Data,
+) -> Result<()>
 where
-    P: AsRef<Path>,
+    O: Into<Output<'a>>,
 {
-    info!(
-        "Generating visualization {}",
-        path.as_ref().to_string_lossy()
-    );
+    let output = output.into();
+    let name = output.to_string();
+    info!("Generating visualization {name}");
+
+    draw_list_over_time(output, options, locale, data)
+        .with_context(|| format!("Could not generate list over time visualization {name}"))?;
+
+    info!("Generated visualization {name}");
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_lines)]
+fn draw_list_over_time(
+    output: Output<'_>,
+    options: &ListOverTimeOptions<'_>,
+    locale: &Locale,
+    data: &Data,
+) -> Result<()> {
+    let &ListOverTimeOptions {
+        size,
+        scale,
+        labels,
+        stroke_width,
+        mark_extremes,
+        predict,
+        thumbnails,
+        highlight,
+    } = options;
+    let size = size.unwrap_or(DEFAULT_SIZE);
+    let sizing = size.scale_from(DEFAULT_SIZE);
     let latest_list = data
         .latest()
         .ok_or_else(|| anyhow!("Latest list doesn't exist"))?;
+    if let Some(highlight) = highlight
+        && !latest_list.0.contains(highlight)
+    {
+        return Err(anyhow!("Game \"{highlight}\" is not on the latest list"));
+    }
     let penultimate_list = data
         .penultimate()
         .ok_or_else(|| anyhow!("Penultimate list doesn't exist"))?;
@@ -55,128 +178,665 @@ where
     let num_games = latest_list.0.len();
     let num_lists = data.lists.0.len();
     let dates = data.dates();
+    let ticks = episode_ticks(
+        num_lists,
+        labels
+            .count
+            .map_or(X_TICK_SPACING, |count| num_lists.div_ceil(count.max(1))),
+    );
+    let end = num_lists + FINAL_WIDTH + if predict { PREDICTION_WIDTH } else { 0 };
+    let predictions = if predict {
+        predict::predict(
+            &dates
+                .iter()
+                .map(|date| &data.lists.0[date])
+                .collect::<Vec<_>>(),
+        )
+        .into_iter()
+        .map(|(id, position, confidence)| (id, (position, confidence)))
+        .collect()
+    } else {
+        HashMap::new()
+    };
 
-    let root = BitMapBackend::new(&path, (WIDTH, HEIGHT)).into_drawing_area();
-    root.fill(&Color::BG_PRIMARY)?;
-
-    let mut chart = ChartBuilder::on(&root)
-        .top_x_label_area_size(X_LABEL_AREA_SIZE)
-        .right_y_label_area_size(Y_LABEL_AREA_SIZE)
-        .margin(MARGIN)
-        .build_cartesian_2d(1..(num_lists + FINAL_WIDTH), 1.0..0.0)?
-        .set_secondary_coord(1..(num_lists + FINAL_WIDTH), (num_games - 1)..0);
-
-    chart
-        .configure_secondary_axes()
-        .y_labels(num_games)
-        .y_label_formatter(&|i| data.metas.0[&latest_list.0[*i]].name.clone())
-        .y_desc("Bonus Points Ranking")
-        .x_labels(num_lists / X_TICK_SPACING)
-        .x_label_formatter(&|i| format!("{}", i.min(&num_games)))
-        .x_desc("Episode")
-        .label_style(Font::default())
-        .axis_style(Color::FONT_PRIMARY)
-        .draw()?;
-
-    chart.draw_series(iter::once(Polygon::new(
-        if scale {
-            vec![
-                (1, 0.0),
-                (num_lists + FINAL_WIDTH, 0.0),
-                (num_lists + FINAL_WIDTH, 1.0),
-                (1, 1.0),
-            ]
+    let description = format!(
+        "{}Bonus Points ranking of {num_games} games over {num_lists} episodes{}",
+        if scale { "Scaled " } else { "" },
+        highlight
+            .map(|id| format!(", highlighting {}", data.metas.0[id].name))
+            .unwrap_or_default()
+    );
+    output.render((size.width, size.height), &description, |root| {
+        root.fill(&Color::BG_PRIMARY)?;
+
+        let font = sizing.font().into_text_style(root);
+        let font_height = sizing.text(Font::DEFAULT_SIZE);
+        // Height taken up by the episode numbers, which grows as they are rotated
+        let number_height = if labels.angle > 0.0 {
+            let width = ticks
+                .iter()
+                .map(|&episode| root.estimate_text_size(&locale.number(episode as u64), &font))
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .map(|(width, _)| width)
+                .max()
+                .unwrap_or(0);
+            f64::from(width)
+                .mul_add(
+                    labels.angle.sin(),
+                    f64::from(font_height) * labels.angle.cos(),
+                )
+                .ceil() as u32
         } else {
-            vec![
-                (1, 0.0),
-                (num_lists + FINAL_WIDTH, 0.0),
-                (num_lists + FINAL_WIDTH, 1.0),
-                (
-                    num_lists - 1,
-                    (penultimate_num_games - 1) as f64 / (num_games - 1) as f64,
+            font_height
+        };
+
+        let mut chart = ChartBuilder::on(root)
+            .top_x_label_area_size(
+                sizing.text(if labels.dated {
+                    DATED_X_LABEL_AREA_SIZE
+                } else {
+                    X_LABEL_AREA_SIZE
+                }) + number_height.saturating_sub(font_height),
+            )
+            .right_y_label_area_size(sizing.text(Y_LABEL_AREA_SIZE))
+            .margin(sizing.margin(MARGIN))
+            .build_cartesian_2d(1..end, 1.0..0.0)?
+            .set_secondary_coord(
+                (1..end).with_key_points(
+                    ticks
+                        .iter()
+                        .map(|&episode| episode_x(episode - 1, num_lists))
+                        .collect(),
                 ),
-            ]
-        },
-        Color::BG_SECONDARY,
-    )))?;
+                (num_games - 1)..0,
+            );
 
-    let logo = img::load(
-        &fs::read(LOGO_FILENAME)?,
-        if scale {
-            LOGO_WIDTH_SCALE
-        } else {
-            LOGO_WIDTH_NOSCALE
-        },
-        if scale {
-            LOGO_HEIGHT_SCALE
-        } else {
-            LOGO_HEIGHT_NOSCALE
-        },
-        if scale {
-            Color::BG_SECONDARY
-        } else {
-            Color::BG_PRIMARY
-        },
-    )?;
-
-    chart.draw_series(iter::once(BitMapElement::from((
-        (
-            if scale { LOGO_X_SCALE } else { LOGO_X_NOSCALE },
-            if scale { LOGO_Y_SCALE } else { LOGO_Y_NOSCALE },
-        ),
-        logo,
-    ))))?;
-
-    let mut colors = ColorIterator::new(COLOR_SPACING, num_games);
-
-    for (i, id) in latest_list.0.iter().enumerate() {
-        let color = colors.next().unwrap();
-        let points = dates
-            .iter()
-            .enumerate()
-            .filter_map(|(idx, date)| {
-                let list = &data.lists.0[date];
-                list.0.iter().position(|x| x == id).map(|position| {
-                    (
-                        if idx == dates.len() - 1 {
-                            num_lists + FINAL_WIDTH
-                        } else {
-                            idx + 1
-                        },
-                        if scale {
-                            if idx == 0 {
-                                0.5
-                            } else {
-                                position as f64 / (list.0.len() - 1) as f64
-                            }
-                        } else {
-                            position as f64 / (num_games - 1) as f64
-                        },
-                    )
-                })
+        chart
+            .configure_secondary_axes()
+            .y_labels(num_games)
+            .y_label_formatter(&|i| {
+                let id = &latest_list.0[*i];
+                // The highlighted game's label is drawn separately, in bold
+                if highlight == Some(id) {
+                    String::new()
+                } else {
+                    data.metas.0[id].name.clone()
+                }
             })
-            .collect::<Vec<_>>();
-        chart.draw_series(points.iter().copied().map(|coord| {
-            Marker::new(
-                match (i / COLOR_SPACING) % MarkerKind::COUNT {
-                    0 => MarkerKind::Triangle,
-                    1 => MarkerKind::Circle,
-                    2 => MarkerKind::Cross,
-                    _ => unreachable!(),
+            .y_desc(&locale.ranking)
+            .x_label_formatter(&|i| {
+                // Rotated numbers are drawn separately
+                if labels.angle > 0.0 {
+                    String::new()
+                } else {
+                    locale.number(episode_at(*i, num_lists) as u64)
+                }
+            })
+            .x_desc(&locale.episode)
+            .label_style(sizing.font())
+            .axis_style(Color::FONT_PRIMARY)
+            .draw()?;
+
+        let axis_y = chart.plotting_area().get_pixel_range().1.start;
+        let (base_x, base_y) = root.get_base_pixel();
+        let tick_x = |episode: usize| {
+            chart
+                .borrow_secondary()
+                .backend_coord(&(episode_x(episode - 1, num_lists), 0))
+                .0
+        };
+        if labels.angle > 0.0 {
+            let y = axis_y - LABEL_DISTANCE - font_height as i32 / 2 - base_y;
+            for &episode in &ticks {
+                text::draw_rotated_text(
+                    root,
+                    &locale.number(episode as u64),
+                    &font,
+                    (tick_x(episode) - base_x, y),
+                    labels.angle,
+                )?;
+            }
+        }
+        if labels.dated {
+            // The chart's axis has a single row of labels, so the dates are drawn above it
+            let y =
+                axis_y - LABEL_DISTANCE - number_height as i32 - sizing.offset(DATE_GAP) - base_y;
+            for &episode in &ticks {
+                text::draw_rotated_text(
+                    root,
+                    &locale.date(dates[episode - 1].0),
+                    &font,
+                    (tick_x(episode) - base_x, y),
+                    DATE_ANGLE,
+                )?;
+            }
+        }
+
+        chart.draw_series(iter::once(Polygon::new(
+            if scale {
+                vec![
+                    (1, 0.0),
+                    (num_lists + FINAL_WIDTH, 0.0),
+                    (num_lists + FINAL_WIDTH, 1.0),
+                    (1, 1.0),
+                ]
+            } else {
+                vec![
+                    (1, 0.0),
+                    (num_lists + FINAL_WIDTH, 0.0),
+                    (num_lists + FINAL_WIDTH, 1.0),
+                    (
+                        num_lists - 1,
+                        (penultimate_num_games - 1) as f64 / (num_games - 1) as f64,
+                    ),
+                ]
+            },
+            Color::BG_SECONDARY,
+        )))?;
+
+        let logo = img::load(
+            &fs::read(LOGO_FILENAME)?,
+            sizing.px(if scale {
+                LOGO_WIDTH_SCALE
+            } else {
+                LOGO_WIDTH_NOSCALE
+            }),
+            sizing.px(if scale {
+                LOGO_HEIGHT_SCALE
+            } else {
+                LOGO_HEIGHT_NOSCALE
+            }),
+            if scale {
+                Color::BG_SECONDARY
+            } else {
+                Color::BG_PRIMARY
+            },
+            sizing.filter(),
+        )?;
+
+        chart.draw_series(iter::once(BitMapElement::from((
+            (
+                if scale { LOGO_X_SCALE } else { LOGO_X_NOSCALE },
+                if scale { LOGO_Y_SCALE } else { LOGO_Y_NOSCALE },
+            ),
+            logo,
+        ))))?;
+
+        let mut colors = ColorIterator::new(COLOR_SPACING, num_games);
+        let muted = Color::BG_SECONDARY
+            .lerp(Color::FONT_PRIMARY, MUTED_LIGHTNESS)
+            .mix(DIMMED_OPACITY);
+        let mut highlighted = None;
+        let marker_size = sizing.px(MARKER_SIZE);
+
+        // Point of the game at `position` on the list of the `idx`th date, which is `len` long
+        let point = |idx: usize, position: usize, len: usize| {
+            (
+                episode_x(idx, num_lists),
+                if scale {
+                    if idx == 0 {
+                        0.5
+                    } else {
+                        position as f64 / (len - 1) as f64
+                    }
+                } else {
+                    position as f64 / (num_games - 1) as f64
                 },
-                coord,
-                color,
             )
-        }))?;
-        chart.draw_series(LineSeries::new(points.iter().copied(), color))?;
+        };
+
+        // Dashed line from the latest point of `id` to its predicted position, within a translucent
+        // band as wide as the prediction is uncertain
+        let prediction = |id: &GameId, latest: (usize, f64), style: ShapeStyle| {
+            let &(position, confidence) = predictions.get(id)?;
+            let max_position = (num_games - 1).max(1) as f64;
+            let y = |position: f64| (position / max_position).clamp(0.0, 1.0);
+            Some((
+                Polygon::new(
+                    vec![
+                        latest,
+                        (end, y(position - confidence)),
+                        (end, y(position + confidence)),
+                    ],
+                    ShapeStyle::from(style.color.mix(PREDICTION_OPACITY)).filled(),
+                ),
+                DashedLineSeries::new(
+                    [latest, (end, y(position))],
+                    sizing.px(PREDICTION_DASH),
+                    sizing.px(PREDICTION_GAP),
+                    style,
+                ),
+            ))
+        };
+
+        for (i, id) in latest_list.0.iter().enumerate() {
+            let color = colors.next().unwrap();
+            let points = dates
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, date)| {
+                    let list = &data.lists.0[date];
+                    list.0
+                        .iter()
+                        .position(|x| x == id)
+                        .map(|position| point(idx, position, list.0.len()))
+                })
+                .collect::<Vec<_>>();
+            let style = match highlight {
+                Some(highlight) if highlight == id => {
+                    // Drawn last, so that it isn't covered by other lines
+                    highlighted = Some((i, points));
+                    continue;
+                }
+                Some(_) => ShapeStyle::from(muted),
+                None => ShapeStyle::from(color),
+            }
+            .stroke_width(sizing.px(stroke_width));
+            if let Some((band, line)) = points
+                .last()
+                .and_then(|&latest| prediction(id, latest, style))
+            {
+                chart.draw_series(iter::once(band))?;
+                chart.draw_series(line)?;
+            }
+            chart.draw_series(
+                points
+                    .iter()
+                    .copied()
+                    .map(|coord| Marker::new(marker_kind(i), coord, marker_size, style)),
+            )?;
+            chart.draw_series(LineSeries::new(points.iter().copied(), style))?;
+        }
+
+        if mark_extremes {
+            // Rings around the first and last games of every list, showing who held either end
+            for (idx, date) in dates.iter().enumerate() {
+                let len = data.lists.0[date].0.len();
+                if len == 0 {
+                    continue;
+                }
+                for (position, color) in [(0, Color::ACCENT_YELLOW), (len - 1, Color::ACCENT_PINK)]
+                {
+                    chart.draw_series(iter::once(Marker::new(
+                        MarkerKind::Circle,
+                        point(idx, position, len),
+                        sizing.px(EXTREME_MARKER_SIZE),
+                        ShapeStyle::from(color).stroke_width(sizing.px(stroke_width)),
+                    )))?;
+                }
+            }
+        }
+
+        // Dashed lines at the annotated episodes, labelled upwards from the bottom of the plot
+        let (_, y_range) = chart.plotting_area().get_pixel_range();
+        let annotation_style = ShapeStyle::from(Color::FONT_PRIMARY);
+        for (x, annotation) in annotated_episodes(data) {
+            chart.draw_series(DashedLineSeries::new(
+                [(x, 0.0), (x, 1.0)],
+                sizing.px(ANNOTATION_DASH),
+                sizing.px(ANNOTATION_GAP),
+                annotation_style,
+            ))?;
+            text::draw_rotated_text(
+                root,
+                annotation,
+                &font,
+                (
+                    chart.backend_coord(&(x, 0.0)).0
+                        + sizing.offset(ANNOTATION_LABEL_GAP)
+                        + font_height as i32 / 2
+                        - base_x,
+                    y_range.end - sizing.offset(ANNOTATION_LABEL_GAP) - base_y,
+                ),
+                FRAC_PI_2,
+            )?;
+        }
+
+        if let Some((i, points)) = highlighted {
+            let style = ShapeStyle::from(Color::ACCENT_YELLOW)
+                .stroke_width(sizing.px(HIGHLIGHT_STROKE_FACTOR * stroke_width));
+            if let Some((band, line)) = points
+                .last()
+                .and_then(|&latest| prediction(highlight.unwrap(), latest, style))
+            {
+                chart.draw_series(iter::once(band))?;
+                chart.draw_series(line)?;
+            }
+            chart.draw_series(
+                points
+                    .iter()
+                    .copied()
+                    .map(|coord| Marker::new(marker_kind(i), coord, marker_size, style)),
+            )?;
+            chart.draw_series(LineSeries::new(points.iter().copied(), style))?;
+
+            let mut annotations =
+                highlight_annotations(&points, highlight.unwrap(), sizing, locale, data);
+            annotate::spread(&mut annotations, |coord| chart.backend_coord(coord));
+            chart.draw_series(annotations)?;
+        }
+
+        let axis_x = chart.plotting_area().get_pixel_range().0.end;
+        let label_y = |i: usize| chart.borrow_secondary().backend_coord(&(1, i)).1;
+        let bold_font = sizing
+            .font()
+            .bold()
+            .with_anchor::<Color>(Pos {
+                h_pos: HPos::Left,
+                v_pos: VPos::Center,
+            })
+            .into_text_style(root);
+        if let Some(highlight) = highlight {
+            let i = latest_list.0.iter().position(|id| id == highlight).unwrap();
+            let (base_x, base_y) = root.get_base_pixel();
+            root.draw_text(
+                &data.metas.0[highlight].name,
+                &bold_font,
+                (axis_x + LABEL_DISTANCE - base_x, label_y(i) - base_y),
+            )?;
+        }
+
+        if let Some(thumbnails) = thumbnails {
+            let font = sizing.font().into_text_style(root);
+            let labels = latest_list
+                .0
+                .iter()
+                .enumerate()
+                .map(|(i, id)| {
+                    let font = if highlight == Some(id) {
+                        &bold_font
+                    } else {
+                        &font
+                    };
+                    (id, data.metas.0[id].name.clone(), font, label_y(i))
+                })
+                .collect::<Vec<_>>();
+            thumbnails.draw(root, axis_x, Side::Right, &labels, sizing)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// X coordinate of the `idx`th of `num_lists` lists, the latest being set apart at the right edge
+const fn episode_x(idx: usize, num_lists: usize) -> usize {
+    if idx + 1 == num_lists {
+        num_lists + FINAL_WIDTH
+    } else {
+        idx + 1
     }
+}
 
-    root.present()?;
+/// X coordinates of the annotated episodes, placed as by `episode_x`, with their annotations
+fn annotated_episodes(data: &Data) -> Vec<(usize, &str)> {
+    let dates = data.dates();
+    dates
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, &date)| Some((episode_x(idx, dates.len()), data.annotation(date)?)))
+        .collect()
+}
 
-    info!(
-        "Generated visualization {}",
-        path.as_ref().to_string_lossy()
+/// Episode, numbered from 1, drawn at the x coordinate `x` of `episode_x`, with coordinates
+/// beyond the latest of the `num_lists` lists clamped to it
+const fn episode_at(x: usize, num_lists: usize) -> usize {
+    if x < 1 {
+        1
+    } else if x > num_lists {
+        num_lists
+    } else {
+        x
+    }
+}
+
+/// Episodes labelled on the x axis: the first, the last, and every `spacing`th one not too close
+/// to either. They are numbered from 1, and placed at their `episode_x`.
+fn episode_ticks(num_lists: usize, spacing: usize) -> Vec<usize> {
+    let spacing = spacing.max(1);
+    let mut ticks = vec![1];
+    ticks.extend(
+        // The first episode is always labelled, even at a spacing of 1
+        (spacing.max(2)..num_lists)
+            .step_by(spacing)
+            .filter(|&i| i > spacing / 2 && num_lists - i >= spacing / 2),
     );
+    if num_lists > 1 {
+        ticks.push(num_lists);
+    }
+    ticks
+}
+
+/// Callouts on the first and last `points` of the highlighted game, with its positions on the
+/// lists at those points
+fn highlight_annotations(
+    points: &[(usize, f64)],
+    id: &GameId,
+    sizing: Scale,
+    locale: &Locale,
+    data: &Data,
+) -> Vec<Annotation<(usize, f64)>> {
+    let positions = data
+        .dates()
+        .iter()
+        .filter_map(|date| data.lists.0[date].0.iter().position(|x| x == id))
+        .collect::<Vec<_>>();
+    let (Some(first), Some(last), Some(entry), Some(current)) = (
+        points.first(),
+        points.last(),
+        positions.first(),
+        positions.last(),
+    ) else {
+        return Vec::new();
+    };
+    vec![
+        Annotation::new(*first, locale.enters_at(entry + 1), Direction::Up, sizing),
+        Annotation::new(*last, locale.now_at(current + 1), Direction::Left, sizing),
+    ]
+}
+
+/// Legend mapping each line color and marker of `list_over_time` to its game. Only the width of
+/// `size` is used, the height follows from the number of games.
+pub fn list_over_time_legend<'a, O>(
+    output: O,
+    size: Option<PlotSize>,
+    stroke_width: u32,
+    data: &Data,
+) -> Result<()>
+where
+    O: Into<Output<'a>>,
+{
+    let output = output.into();
+    let name = output.to_string();
+    info!("Generating visualization {name}");
+
+    draw_list_over_time_legend(
+        output,
+        size.unwrap_or(LEGEND_DEFAULT_SIZE),
+        stroke_width,
+        data,
+    )
+    .with_context(|| format!("Could not generate list over time legend visualization {name}"))?;
+
+    info!("Generated visualization {name}");
 
     Ok(())
 }
+
+fn draw_list_over_time_legend(
+    output: Output<'_>,
+    size: PlotSize,
+    stroke_width: u32,
+    data: &Data,
+) -> Result<()> {
+    let scale = size.width_scale_from(LEGEND_DEFAULT_SIZE);
+    let margin = scale.margin(LEGEND_MARGIN);
+    let row_height = scale.text(LEGEND_ROW_HEIGHT);
+    let sample_width = scale.offset(LEGEND_SAMPLE_WIDTH);
+    let latest_list = data
+        .latest()
+        .ok_or_else(|| anyhow!("Latest list doesn't exist"))?;
+    let num_games = latest_list.0.len();
+    let num_rows = num_games.div_ceil(LEGEND_COLUMNS);
+    let column_width = size.width.saturating_sub(2 * margin) / LEGEND_COLUMNS as u32;
+
+    let description =
+        format!("Legend of the {num_games} games in the Bonus Points ranking over time");
+    output.render(
+        (size.width, 2 * margin + num_rows as u32 * row_height),
+        &description,
+        |root| {
+            root.fill(&Color::BG_PRIMARY)?;
+            let root = root.margin(margin, margin, margin, margin);
+
+            let mut colors = ColorIterator::new(COLOR_SPACING, num_games);
+
+            for (i, id) in latest_list.0.iter().enumerate() {
+                let style =
+                    ShapeStyle::from(colors.next().unwrap()).stroke_width(scale.px(stroke_width));
+                let x = ((i / num_rows) as u32 * column_width) as i32;
+                let y = ((i % num_rows) as u32 * row_height + row_height / 2) as i32;
+
+                root.draw(&PathElement::new(
+                    vec![(x, y), (x + sample_width, y)],
+                    style,
+                ))?;
+                root.draw(&Marker::new(
+                    marker_kind(i),
+                    (x + sample_width / 2, y),
+                    scale.px(MARKER_SIZE),
+                    style,
+                ))?;
+                root.draw_text(
+                    &data.metas.0[id].name,
+                    &scale
+                        .font()
+                        .with_anchor::<Color>(Pos {
+                            h_pos: HPos::Left,
+                            v_pos: VPos::Center,
+                        })
+                        .into_text_style(&root),
+                    (x + sample_width + scale.offset(LEGEND_TEXT_GAP), y),
+                )?;
+            }
+
+            Ok(())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::fixture;
+
+    #[test]
+    fn labels_episodes_at_their_x() {
+        for num_lists in [1, 2, 6, 40] {
+            for episode in 1..=num_lists {
+                assert_eq!(
+                    episode_at(episode_x(episode - 1, num_lists), num_lists),
+                    episode,
+                    "episode {episode} of {num_lists}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn clamps_episode_labels_to_lists() {
+        assert_eq!(episode_at(0, 6), 1);
+        // Past the latest list, in the gap setting it apart and the predictions beyond
+        for x in 7..=6 + FINAL_WIDTH + PREDICTION_WIDTH {
+            assert_eq!(episode_at(x, 6), 6, "x {x}");
+        }
+    }
+
+    #[test]
+    fn ticks_first_last_and_spaced_episodes() {
+        assert_eq!(episode_ticks(1, X_TICK_SPACING), [1]);
+        assert_eq!(episode_ticks(6, X_TICK_SPACING), [1, 6]);
+        assert_eq!(episode_ticks(25, X_TICK_SPACING), [1, 10, 20, 25]);
+        // Too close to the last episode to be labelled as well
+        assert_eq!(episode_ticks(23, X_TICK_SPACING), [1, 10, 23]);
+        assert_eq!(episode_ticks(5, 0), [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn places_annotations_at_their_episodes() {
+        let mut data = fixture::data();
+        // The 4th of 6 episodes
+        assert_eq!(annotated_episodes(&data), [(4, "Live episode")]);
+
+        let dates = data.dates();
+        data.lists.0.remove(&dates[0]);
+        assert_eq!(annotated_episodes(&data), [(3, "Live episode")]);
+        // Set apart with the latest list
+        for date in &dates[4..] {
+            data.lists.0.remove(date);
+        }
+        assert_eq!(
+            annotated_episodes(&data),
+            [(3 + FINAL_WIDTH, "Live episode")]
+        );
+        data.lists.0.remove(&dates[3]);
+        assert_eq!(annotated_episodes(&data), []);
+    }
+
+    /// Pixels of the fixture's plot with `highlight`, counted by color
+    fn color_counts(highlight: Option<&GameId>) -> HashMap<[u8; 3], usize> {
+        let mut png = Vec::new();
+        list_over_time(
+            Output::from(&mut png),
+            &ListOverTimeOptions {
+                size: None,
+                scale: false,
+                labels: EpisodeLabels {
+                    count: None,
+                    angle: 0.0,
+                    dated: false,
+                },
+                stroke_width: 2,
+                mark_extremes: false,
+                predict: false,
+                thumbnails: None,
+                highlight,
+            },
+            &Locale::default(),
+            &fixture::data(),
+        )
+        .unwrap();
+        let mut counts = HashMap::new();
+        for pixel in image::load_from_memory(&png).unwrap().to_rgb8().pixels() {
+            *counts.entry(pixel.0).or_default() += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn draws_highlighted_game_in_accent_color() {
+        let count = |counts: &HashMap<[u8; 3], usize>, color: Color| {
+            counts
+                .get(&[color.0, color.1, color.2])
+                .copied()
+                .unwrap_or(0)
+        };
+        let num_games = fixture::data().latest().unwrap().0.len();
+        let palette = ColorIterator::new(COLOR_SPACING, num_games)
+            .take(num_games)
+            .collect::<Vec<_>>();
+
+        let plain = color_counts(None);
+        for &color in &palette {
+            assert!(count(&plain, color) > 0, "{color:?} missing");
+        }
+
+        // Every other game is muted rather than drawn in its own color
+        let highlighted = color_counts(Some(&GameId::Igdb(102)));
+        // More of it than other parts of the plot are drawn in
+        assert!(count(&highlighted, Color::ACCENT_YELLOW) > count(&plain, Color::ACCENT_YELLOW));
+        for &color in &palette {
+            assert_eq!(count(&highlighted, color), 0, "{color:?} drawn");
+        }
+    }
+}
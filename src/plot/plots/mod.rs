@@ -1,9 +1,49 @@
+use std::collections::HashSet;
+
+use crate::{data::Data, plot::thumbnail, request::resource::ImageSize};
+
+mod bubble_chart;
+mod coop;
+mod era_breakdown;
+mod generation_positions;
+mod genre_trend;
+#[cfg(test)]
+mod golden;
+mod list_card;
 mod list_over_time;
+mod race_frames;
 mod ranking_difference;
 mod release_dates;
 mod summary;
 
-pub use list_over_time::list_over_time;
-pub use ranking_difference::ranking_difference;
-pub use release_dates::release_dates;
-pub use summary::summary;
+pub use bubble_chart::bubble_chart;
+pub use coop::coop;
+pub use era_breakdown::era_breakdown;
+pub use generation_positions::generation_positions;
+pub use genre_trend::genre_trend;
+pub use list_card::list_card;
+pub use list_over_time::{
+    EpisodeLabels, ListOverTimeOptions, list_over_time, list_over_time_legend,
+};
+pub use race_frames::race_frames;
+pub use ranking_difference::{
+    RankingDifferenceOptions, ranking_difference, ranking_difference_path,
+};
+pub use release_dates::{SmoothingParams, release_dates};
+pub use summary::{SummarySegment, episode_summary, summary};
+
+/// Distinct images drawn in any plot, including the list cards of every date
+pub fn resource_urls(data: &Data) -> Vec<(ImageSize, String)> {
+    let mut seen = HashSet::new();
+    summary::resource_urls(data)
+        .into_iter()
+        .chain(race_frames::resource_urls(data))
+        .chain(thumbnail::resource_urls(data))
+        .chain(
+            data.dates()
+                .into_iter()
+                .flat_map(|date| list_card::resource_urls(data, date)),
+        )
+        .filter(|resource| seen.insert(resource.clone()))
+        .collect()
+}
@@ -0,0 +1,312 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result, anyhow};
+use image::DynamicImage;
+use plotters::{
+    prelude::{BitMapElement, Rectangle},
+    style::{IntoTextStyle, ShapeStyle},
+};
+use plotters_backend::text_anchor::{HPos, Pos, VPos};
+use tracing::{info, warn};
+
+use crate::{
+    data::{Data, GameId, LOGO_FILENAME},
+    plot::{
+        color::{Color, ColorIterator},
+        easing::ease_in_out_cubic,
+        font::Font,
+        img,
+        locale::Locale,
+        output::Output,
+        progress::{self, Stage},
+        size::PlotSize,
+    },
+    request::resource::ImageSize,
+};
+
+const DEFAULT_SIZE: PlotSize = PlotSize::new(1920, 1080);
+const MARGIN: u32 = 48;
+const HEADER_HEIGHT: u32 = 96;
+const LABEL_AREA_SIZE: u32 = 480;
+const NUM_BARS: usize = 15;
+const BAR_GAP: u32 = 8;
+const THUMB_WIDTH: u32 = 40;
+const ITEM_GAP: i32 = 16;
+const LOGO_WIDTH: u32 = 170;
+const LOGO_HEIGHT: u32 = 90;
+const TITLE_FONT_SIZE: u32 = 64;
+const FONT_SIZE: u32 = 32;
+const COLOR_SPACING: usize = 4;
+const FRAME_RATE: usize = 30;
+
+/// Position and bar length of a game at a given list date
+#[derive(Debug, Clone, Copy)]
+struct BarState {
+    position: f64,
+    length: f64,
+}
+
+impl BarState {
+    fn lerp(self, other: Self, t: f64) -> Self {
+        Self {
+            position: t.mul_add(other.position - self.position, self.position),
+            length: t.mul_add(other.length - self.length, self.length),
+        }
+    }
+}
+
+fn frame_filename(i: usize) -> String {
+    format!("frame_{:05}.png", i + 1)
+}
+
+/// Renders bar chart race frames into `dir`, interpolating between consecutive lists
+pub async fn race_frames<P>(
+    dir: P,
+    size: Option<PlotSize>,
+    locale: &Locale,
+    data: &Data,
+    frames_per_transition: usize,
+) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    info!(
+        "Generating bar chart race frames in {}",
+        dir.as_ref().to_string_lossy()
+    );
+
+    let num_frames = draw_race_frames(
+        dir.as_ref(),
+        size.unwrap_or(DEFAULT_SIZE),
+        locale,
+        data,
+        frames_per_transition,
+    )
+    .await
+    .with_context(|| {
+        format!(
+            "Could not generate bar chart race frames in {}",
+            dir.as_ref().to_string_lossy()
+        )
+    })?;
+
+    info!(
+        "Generated {num_frames} bar chart race frames in {}",
+        dir.as_ref().to_string_lossy()
+    );
+    info!(
+        "Encode with: ffmpeg -framerate {FRAME_RATE} -i {} -c:v libx264 -pix_fmt yuv420p race.mp4",
+        dir.as_ref().join("frame_%05d.png").to_string_lossy()
+    );
+
+    Ok(())
+}
+
+/// Images drawn in the bar chart race frames
+pub fn resource_urls(data: &Data) -> Vec<(ImageSize, String)> {
+    data.lists
+        .0
+        .values()
+        .flat_map(|list| list.0.iter())
+        .filter_map(|id| data.metas.0.get(id)?.cover.as_ref())
+        .map(|cover| (ImageSize::CoverSmall, cover.url.clone()))
+        .collect()
+}
+
+/// Renders the frames, returning their number
+#[allow(clippy::too_many_lines, clippy::similar_names)]
+async fn draw_race_frames(
+    dir: &Path,
+    size: PlotSize,
+    locale: &Locale,
+    data: &Data,
+    frames_per_transition: usize,
+) -> Result<usize> {
+    let scale = size.scale_from(DEFAULT_SIZE);
+    let margin = scale.margin(MARGIN);
+    let header_height = scale.text(HEADER_HEIGHT);
+    let label_area_size = scale.text(LABEL_AREA_SIZE);
+    let bar_height =
+        (size.height.saturating_sub(2 * margin + header_height) / NUM_BARS as u32).max(1);
+    let bar_gap = scale.px(BAR_GAP).min(bar_height - 1);
+    let thumb_width = scale.px(THUMB_WIDTH);
+    let thumb_height = bar_height - bar_gap;
+
+    fs::create_dir_all(dir)?;
+
+    let dates = data.dates();
+    if dates.len() < 2 {
+        return Err(anyhow!(
+            "At least two lists are needed for a bar chart race"
+        ));
+    }
+    let frames_per_transition = frames_per_transition.max(1);
+
+    let mut ids = Vec::<&GameId>::new();
+    for date in &dates {
+        for id in &data.lists.0[date].0 {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    let colors = ids
+        .iter()
+        .copied()
+        .zip(ColorIterator::new(COLOR_SPACING, ids.len()))
+        .collect::<HashMap<_, _>>();
+
+    let mut covers = data.res.get_all(resource_urls(data)).await?;
+    let mut thumbs = HashMap::<GameId, DynamicImage>::new();
+    for id in &ids {
+        let Some(cover) = data.metas.0.get(id).and_then(|meta| meta.cover.as_ref()) else {
+            continue;
+        };
+        let cover_url = cover.url.clone();
+        match covers.remove(&(ImageSize::CoverSmall, cover_url.clone())) {
+            Some(Ok(cover)) => {
+                let thumb = data.res.images().load(
+                    ImageSize::CoverSmall,
+                    &cover_url,
+                    &cover,
+                    thumb_width,
+                    thumb_height,
+                    colors[id],
+                    scale.filter(),
+                )?;
+                thumbs.insert((*id).clone(), thumb);
+            }
+            Some(Err(err)) => warn!("Using placeholder for cover of {id}: {err:#}"),
+            None => {}
+        }
+    }
+    for id in &ids {
+        if !thumbs.contains_key(*id) {
+            let thumb = img::placeholder(
+                thumb_width,
+                thumb_height,
+                data.metas.0.get(*id).map_or("", |meta| meta.name.as_str()),
+                colors[id],
+                Color::BG_PRIMARY,
+            )?;
+            thumbs.insert((*id).clone(), thumb);
+        }
+    }
+
+    let logo = img::load(
+        &fs::read(LOGO_FILENAME)?,
+        scale.px(LOGO_WIDTH),
+        scale.px(LOGO_HEIGHT),
+        Color::BG_PRIMARY,
+        scale.filter(),
+    )?;
+
+    let states = |i: usize| -> HashMap<&GameId, BarState> {
+        let list = &data.lists.0[&dates[i]].0;
+        list.iter()
+            .enumerate()
+            .map(|(position, id)| {
+                (
+                    id,
+                    BarState {
+                        position: position as f64,
+                        length: 1.0 - position as f64 / list.len() as f64,
+                    },
+                )
+            })
+            .collect()
+    };
+
+    let bar_area_width = f64::from(size.width.saturating_sub(2 * margin + label_area_size));
+    let num_frames = (dates.len() - 1) * frames_per_transition + 1;
+
+    progress::report(&dir.display(), Stage::Drawing);
+    for frame in 0..num_frames {
+        let transition = (frame / frames_per_transition).min(dates.len() - 2);
+        let t = if frame == num_frames - 1 {
+            1.0
+        } else {
+            ease_in_out_cubic((frame % frames_per_transition) as f64 / frames_per_transition as f64)
+        };
+        let from = states(transition);
+        let to = states(transition + 1);
+        let num_prev = from.len() as f64;
+
+        let path = dir.join(frame_filename(frame));
+        let date = dates[if t < 0.5 { transition } else { transition + 1 }];
+        let description = format!(
+            "Frame {} of {num_frames} of the bar chart race of The Bonus Points list over {} \
+             episodes, showing {date}",
+            frame + 1,
+            dates.len()
+        );
+        Output::from(&path)
+            .without_progress()
+            .render((size.width, size.height), &description, |root| {
+                root.fill(&Color::BG_PRIMARY)?;
+                let root = root.margin(margin, margin, margin, margin);
+
+                root.draw_text(
+                    &locale.date(date.0),
+                    &Font::new(scale.text(TITLE_FONT_SIZE))
+                        .with_anchor::<Color>(Pos {
+                            h_pos: HPos::Left,
+                            v_pos: VPos::Center,
+                        })
+                        .into_text_style(&root),
+                    (0, (header_height / 2) as i32),
+                )?;
+                root.draw(&BitMapElement::from((
+                    (
+                        size.width.saturating_sub(2 * margin + scale.px(LOGO_WIDTH)) as i32,
+                        0,
+                    ),
+                    logo.clone(),
+                )))?;
+
+                for (id, end) in &to {
+                    let start = from.get(id).copied().unwrap_or(BarState {
+                        position: num_prev,
+                        length: 0.0,
+                    });
+                    let state = start.lerp(*end, t);
+                    if state.position > (NUM_BARS - 1) as f64 {
+                        continue;
+                    }
+
+                    let y = state
+                        .position
+                        .mul_add(f64::from(bar_height), f64::from(header_height))
+                        as i32;
+                    let x = label_area_size as i32;
+                    let bar_end = x + (state.length * bar_area_width) as i32;
+                    root.draw(&Rectangle::new(
+                        [(x, y), (bar_end, y + thumb_height as i32)],
+                        ShapeStyle::from(colors[id]).filled(),
+                    ))?;
+                    root.draw_text(
+                        data.metas.0.get(*id).map_or("", |meta| meta.name.as_str()),
+                        &Font::new(scale.text(FONT_SIZE))
+                            .with_anchor::<Color>(Pos {
+                                h_pos: HPos::Right,
+                                v_pos: VPos::Center,
+                            })
+                            .into_text_style(&root),
+                        (x - scale.offset(ITEM_GAP), y + (thumb_height / 2) as i32),
+                    )?;
+                    if let Some(thumb) = thumbs.get(*id) {
+                        root.draw(&BitMapElement::from((
+                            ((bar_end - thumb.width() as i32).max(x), y),
+                            thumb.clone(),
+                        )))?;
+                    }
+                }
+
+                Ok(())
+            })
+            .with_context(|| format!("Could not write frame {}", path.to_string_lossy()))?;
+    }
+    progress::report(&dir.display(), Stage::Written);
+
+    Ok(num_frames)
+}
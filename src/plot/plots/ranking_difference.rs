@@ -1,125 +1,363 @@
-use std::{fs, iter, path::Path};
+use std::{fs, iter};
 
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
 use plotters::{
     chart::ChartBuilder,
-    prelude::{BitMapBackend, BitMapElement, IntoDrawingArea, Polygon},
-    series::LineSeries,
+    prelude::{BitMapElement, Polygon},
+    series::{DashedLineSeries, LineSeries},
+    style::{IntoTextStyle, ShapeStyle},
 };
 use tracing::info;
 
 use crate::{
-    data::{Data, LOGO_FILENAME, RatingKind},
+    data::{Data, LOGO_FILENAME, RatingKind, RatingWeighting},
     plot::{
         color::{Color, ColorIterator},
-        font::Font,
+        easing::ease_in_out_cubic,
         img,
+        locale::Locale,
+        output::Output,
+        size::PlotSize,
+        thumbnail::{Side, Thumbnails},
     },
 };
 
-const WIDTH: u32 = 2048;
-const HEIGHT: u32 = 1556;
+const DEFAULT_SIZE: PlotSize = PlotSize::new(2048, 1556);
 const COLOR_SPACING: usize = 10;
-const CURVE_POINTS: usize = (WIDTH - 2 * (MARGIN + Y_LABEL_AREA_SIZE)) as usize;
 const MARGIN: u32 = 64;
 const LOGO_MARGIN: i32 = 16;
+const LOGO_WIDTH: u32 = 170;
+const LOGO_HEIGHT: u32 = 90;
 const Y_LABEL_AREA_SIZE: u32 = 416;
+/// Fraction of the way to the most extreme difference color which the smallest difference takes
+const MIN_INTENSITY: f64 = 0.25;
+const ARROW_LENGTH: u32 = 12;
+const ARROW_WIDTH: u32 = 10;
+/// Fraction of the plot's width spanned by the stubs of games in only one of the rankings
+const STUB_LENGTH: f64 = 0.1;
+const STUB_DASH: u32 = 6;
+const STUB_GAP: u32 = 4;
 
-fn ease_in_out_cubic(x: f64) -> f64 {
-    if x < 0.5 {
-        4.0 * x.powi(3)
-    } else {
-        1.0 - (-2.0f64).mul_add(x, 2.0).powi(3) / 2.0
-    }
+/// Options of the ranking difference
+#[derive(Clone, Copy)]
+pub struct RankingDifferenceOptions<'a> {
+    /// Size of the image, instead of the default size
+    pub size: Option<PlotSize>,
+    /// Kind of IGDB rating which the list is compared to
+    pub kind: RatingKind,
+    /// Weighting of the ratings ordering the IGDB ranking
+    pub weighting: RatingWeighting,
+    /// Whether every game has a distinct color, instead of one by the direction of its difference
+    pub distinct_colors: bool,
+    /// Width of the lines and arrows in pixels at the default size
+    pub stroke_width: u32,
+    /// Thumbnails labelling the games, instead of their names
+    pub thumbnails: Option<&'a Thumbnails>,
+}
+
+/// Differences between the list's ranking and the IGDB ranking by the kind of rating, colored by
+/// their direction and size, or with a distinct color for every game if `distinct_colors`,
+/// written to `ranking_difference_path` unless another `output` is given
+pub fn ranking_difference(
+    output: Option<Output<'_>>,
+    options: &RankingDifferenceOptions<'_>,
+    locale: &Locale,
+    data: &Data,
+) -> Result<()> {
+    let default_path = ranking_difference_path(options.kind, options.weighting);
+    let output = output.unwrap_or_else(|| Output::from(&default_path));
+    let name = output.to_string();
+
+    info!("Generating visualization {name}");
+
+    draw_ranking_difference(output, options, locale, data)
+        .with_context(|| format!("Could not generate ranking difference visualization {name}"))?;
+
+    info!("Generated visualization {name}");
+
+    Ok(())
 }
 
-pub fn ranking_difference<P>(path: P, kind: RatingKind, data: &Data) -> Result<()>
-where
-    P: AsRef<Path>,
-{
-    info!(
-        "Generating visualization {}",
-        path.as_ref().to_string_lossy()
-    );
+/// Default path of the ranking difference by `kind` of rating, ordered by `weighting`
+pub fn ranking_difference_path(kind: RatingKind, weighting: RatingWeighting) -> String {
+    format!(
+        "out/rating_differences_{}{}.png",
+        format!("{kind:?}").to_lowercase(),
+        match weighting {
+            RatingWeighting::Raw => "",
+            RatingWeighting::Bayesian => "_weighted",
+        }
+    )
+}
 
+#[allow(clippy::too_many_lines)]
+fn draw_ranking_difference(
+    output: Output<'_>,
+    options: &RankingDifferenceOptions<'_>,
+    locale: &Locale,
+    data: &Data,
+) -> Result<()> {
+    let &RankingDifferenceOptions {
+        size,
+        kind,
+        weighting,
+        distinct_colors,
+        stroke_width,
+        thumbnails,
+    } = options;
+    let size = size.unwrap_or(DEFAULT_SIZE);
+    let scale = size.scale_from(DEFAULT_SIZE);
     let latest_list = data
         .latest()
         .ok_or_else(|| anyhow!("Latest list doesn't exist"))?;
     let num_games = latest_list.0.len();
-    let igdb_list = data.igdb_list(kind);
-
-    let root = BitMapBackend::new(&path, (WIDTH, HEIGHT)).into_drawing_area();
-    root.fill(&Color::BG_PRIMARY)?;
-
-    let logo = img::load(&fs::read(LOGO_FILENAME)?, 170, 90, Color::BG_PRIMARY)?;
-    root.draw(&BitMapElement::from(((LOGO_MARGIN, LOGO_MARGIN), logo)))?;
-
-    let mut chart = ChartBuilder::on(&root)
-        .y_label_area_size(Y_LABEL_AREA_SIZE)
-        .right_y_label_area_size(Y_LABEL_AREA_SIZE)
-        .margin(MARGIN)
-        .build_cartesian_2d(0.0..1.0, ((num_games - 1) as f64)..0.0)?
-        .set_secondary_coord(0..0, (igdb_list.len() - 1)..0);
-
-    chart
-        .configure_mesh()
-        .disable_mesh()
-        .y_labels(num_games)
-        .y_label_formatter(&|i| {
-            data.metas.0[&latest_list.0[i.round() as usize]]
-                .name
-                .clone()
-        })
-        .y_desc("Bonus Points Ranking")
-        .label_style(Font::default())
-        .axis_style(Color::FONT_PRIMARY)
-        .draw()?;
-
-    chart
-        .configure_secondary_axes()
-        .y_labels(igdb_list.len())
-        .y_label_formatter(&|i| {
-            format!("({:.0}) {}", igdb_list[*i].0.round(), igdb_list[*i].1.name)
-        })
-        .y_desc(kind.to_string())
-        .label_style(Font::default())
-        .axis_style(Color::FONT_PRIMARY)
-        .draw()?;
-
-    chart.draw_series(iter::once(Polygon::new(
-        vec![
-            (1.0, 0.0),
-            (0.0, 0.0),
-            (0.0, (num_games - 1) as f64),
-            (1.0, (num_games - 1) as f64),
-        ],
-        Color::BG_SECONDARY,
-    )))?;
-
-    let mut colors = ColorIterator::new(COLOR_SPACING, num_games);
-
-    for (i, id) in latest_list.0.iter().enumerate() {
-        let color = colors.next().unwrap();
-        if let Some(igdb_pos) = igdb_list.iter().position(|meta| meta.1.id == *id) {
-            let start = i as f64;
-            let end = igdb_pos as f64 * (num_games - 1) as f64 / (igdb_list.len() - 1) as f64;
+    let latest_metas = data
+        .top_n(num_games)
+        .ok_or_else(|| anyhow!("Missing metadata for the latest list"))?;
+    if !data.metas.0.values().any(|meta| meta.has_rating(kind)) {
+        return Err(anyhow!("No games have an {kind}"));
+    }
+    let igdb_list = data.igdb_list(kind, weighting);
+    if igdb_list.is_empty() {
+        return Err(anyhow!(
+            "No games have an {kind} with at least {} votes",
+            data.min_rating_count()
+        ));
+    }
+    let igdb_desc = match weighting {
+        RatingWeighting::Raw => kind.to_string(),
+        RatingWeighting::Bayesian => format!("{kind} (vote-weighted)"),
+    };
+    let igdb_label = locale.igdb_ranking(kind, weighting);
+    let igdb_name = |i: usize| {
+        let (rating, meta) = igdb_list[i.min(igdb_list.len().saturating_sub(1))];
+        format!("({:.0}) {}", rating.round(), meta.name)
+    };
+
+    let description =
+        format!("Bonus Points ranking of {num_games} games compared to the {igdb_desc}");
+    output.render((size.width, size.height), &description, |root| {
+        root.fill(&Color::BG_PRIMARY)?;
+
+        let logo = img::load(
+            &fs::read(LOGO_FILENAME)?,
+            scale.px(LOGO_WIDTH),
+            scale.px(LOGO_HEIGHT),
+            Color::BG_PRIMARY,
+            scale.filter(),
+        )?;
+        let logo_margin = scale.offset(LOGO_MARGIN);
+        root.draw(&BitMapElement::from(((logo_margin, logo_margin), logo)))?;
+
+        let mut chart = ChartBuilder::on(root)
+            .y_label_area_size(scale.text(Y_LABEL_AREA_SIZE))
+            .right_y_label_area_size(scale.text(Y_LABEL_AREA_SIZE))
+            .margin(scale.margin(MARGIN))
+            .build_cartesian_2d(0.0..1.0, ((num_games - 1) as f64)..0.0)?
+            .set_secondary_coord(0..0, igdb_list.len().saturating_sub(1)..0);
+
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .y_labels(num_games)
+            .y_label_formatter(&|i| {
+                latest_metas[(i.round() as usize).min(num_games - 1)]
+                    .name
+                    .clone()
+            })
+            .y_desc(&locale.ranking)
+            .label_style(scale.font())
+            .axis_style(Color::FONT_PRIMARY)
+            .draw()?;
+
+        chart
+            .configure_secondary_axes()
+            .y_labels(igdb_list.len())
+            .y_label_formatter(&|i| igdb_name(*i))
+            .y_desc(&igdb_label)
+            .label_style(scale.font())
+            .axis_style(Color::FONT_PRIMARY)
+            .draw()?;
+
+        chart.draw_series(iter::once(Polygon::new(
+            vec![
+                (1.0, 0.0),
+                (0.0, 0.0),
+                (0.0, (num_games - 1) as f64),
+                (1.0, (num_games - 1) as f64),
+            ],
+            Color::BG_SECONDARY,
+        )))?;
+
+        // One curve point per horizontal pixel of the plotting area
+        let curve_points = size
+            .width
+            .saturating_sub(2 * (scale.margin(MARGIN) + scale.text(Y_LABEL_AREA_SIZE)))
+            .max(2) as usize;
+        let igdb_position = |igdb_pos: usize| {
+            igdb_pos as f64 * (num_games - 1) as f64
+                / igdb_list.len().saturating_sub(1).max(1) as f64
+        };
+        let curves = latest_list
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, id)| {
+                let igdb_pos = igdb_list.iter().position(|meta| meta.1.id == *id)?;
+                Some((i as f64, igdb_position(igdb_pos)))
+            })
+            .collect::<Vec<_>>();
+        let max_diff = curves
+            .iter()
+            .flatten()
+            .map(|(start, end)| (end - start).abs())
+            .fold(0.0, f64::max);
+        let neutral = Color::BG_SECONDARY.lerp(Color::FONT_PRIMARY, 0.5);
+        let mut colors = ColorIterator::new(COLOR_SPACING, num_games);
+        let (x_range, _) = chart.plotting_area().get_pixel_range();
+        let (base_x, base_y) = root.get_base_pixel();
+
+        for (curve, distinct_color) in curves.iter().zip(&mut colors) {
+            let Some((start, end)) = *curve else {
+                continue;
+            };
+            let diff = end - start;
+            let color = if distinct_colors {
+                distinct_color
+            } else {
+                // IGDB ranking a game higher moves its curve up, towards the top of the list
+                Color::diverging(
+                    diff.signum() * MIN_INTENSITY.max(diff.abs() / max_diff.max(1.0)),
+                    1.0,
+                    Color::ACCENT_PINK,
+                    neutral,
+                    Color::ACCENT_BLUE,
+                )
+            };
 
             chart.draw_series(LineSeries::new(
-                (0..CURVE_POINTS).map(|i| {
-                    let x = i as f64 / CURVE_POINTS as f64;
+                (0..curve_points).map(|i| {
+                    let x = i as f64 / curve_points as f64;
                     (x, ease_in_out_cubic(x).mul_add(end - start, start))
                 }),
-                color,
+                ShapeStyle::from(color).stroke_width(scale.px(stroke_width)),
+            ))?;
+
+            // Arrowhead pointing at the IGDB ranking's label
+            let (_, y) = chart.backend_coord(&(1.0, end));
+            let (length, half_width) = (
+                scale.px(ARROW_LENGTH) as i32,
+                scale.px(ARROW_WIDTH) as i32 / 2,
+            );
+            let tip = x_range.end - base_x;
+            let y = y - base_y;
+            root.draw(&Polygon::new(
+                vec![
+                    (tip, y),
+                    (tip - length, y - half_width),
+                    (tip - length, y + half_width),
+                ],
+                ShapeStyle::from(color).filled(),
             ))?;
         }
+
+        // Stubs of games rated on only one side, pointing away from their label
+        let stub_style = ShapeStyle::from(neutral);
+        let stub = |from: f64, to: f64, y: f64| {
+            DashedLineSeries::new(
+                [(from, y), (to, y)],
+                scale.px(STUB_DASH),
+                scale.px(STUB_GAP),
+                stub_style,
+            )
+        };
+        for (i, _) in curves
+            .iter()
+            .enumerate()
+            .filter(|(_, curve)| curve.is_none())
+        {
+            chart.draw_series(stub(0.0, STUB_LENGTH, i as f64))?;
+        }
+        for (igdb_pos, (_, meta)) in igdb_list.iter().enumerate() {
+            if !latest_list.0.contains(&meta.id) {
+                chart.draw_series(stub(1.0 - STUB_LENGTH, 1.0, igdb_position(igdb_pos)))?;
+            }
+        }
+
+        if let Some(thumbnails) = thumbnails {
+            let font = scale.font().into_text_style(root);
+            let labels = latest_metas
+                .iter()
+                .enumerate()
+                .map(|(i, meta)| {
+                    let y = chart.backend_coord(&(0.0, i as f64)).1;
+                    (&meta.id, meta.name.clone(), &font, y)
+                })
+                .collect::<Vec<_>>();
+            thumbnails.draw(root, x_range.start, Side::Left, &labels, scale)?;
+            let labels = igdb_list
+                .iter()
+                .enumerate()
+                .map(|(i, (_, meta))| {
+                    let y = chart.borrow_secondary().backend_coord(&(0, i)).1;
+                    (&meta.id, igdb_name(i), &font, y)
+                })
+                .collect::<Vec<_>>();
+            thumbnails.draw(root, x_range.end, Side::Right, &labels, scale)?;
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{GameId, fixture};
+
+    /// Fixture in which only the games of `rated` have a user rating
+    fn rated(rated: &[u32]) -> Data {
+        let mut data = fixture::data();
+        for meta in data.metas.0.values_mut() {
+            if !matches!(meta.id, GameId::Igdb(id) if rated.contains(&id)) {
+                meta.rating = None;
+            }
+        }
+        data
     }
 
-    root.present()?;
+    fn draw(data: &Data) -> Result<Vec<u8>> {
+        let mut png = Vec::new();
+        ranking_difference(
+            Some(Output::from(&mut png)),
+            &RankingDifferenceOptions {
+                size: None,
+                kind: RatingKind::User,
+                weighting: RatingWeighting::Raw,
+                distinct_colors: false,
+                stroke_width: 2,
+                thumbnails: None,
+            },
+            &Locale::default(),
+            data,
+        )?;
+        Ok(png)
+    }
 
-    info!(
-        "Generated visualization {}",
-        path.as_ref().to_string_lossy()
-    );
+    #[test]
+    fn draws_igdb_list_shorter_than_latest_list() {
+        for ids in [&[101, 102][..], &[103]] {
+            let data = rated(ids);
+            assert!(
+                data.igdb_list(RatingKind::User, RatingWeighting::Raw).len()
+                    < data.latest().unwrap().0.len()
+            );
+            assert!(!draw(&data).unwrap().is_empty());
+        }
+    }
 
-    Ok(())
+    #[test]
+    fn fails_without_igdb_list() {
+        assert!(draw(&rated(&[])).is_err());
+    }
 }
@@ -1,116 +1,323 @@
-use std::{f64::consts::PI, fs, path::Path, time::Duration};
+use std::{fs, iter, time::Duration};
 
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result};
 use plotters::{
     chart::ChartBuilder,
-    prelude::{BitMapBackend, BitMapElement, Circle, IntoDrawingArea},
-    series::AreaSeries,
-    style::ShapeStyle,
+    coord::Shift,
+    prelude::{BitMapBackend, BitMapElement, Circle, DrawingArea},
+    series::LineSeries,
+    style::{Color as _, IntoTextStyle, ShapeStyle},
 };
-use tracing::info;
+use plotters_backend::text_anchor::{HPos, Pos, VPos};
+use tracing::{info, warn};
 
 use crate::{
-    data::{Data, LOGO_FILENAME},
-    plot::{color::Color, font::Font, img, range::OffsetDateTimeRange},
+    data::{Data, LOGO_FILENAME, Meta, kde},
+    plot::{
+        color::Color,
+        gradient::GradientArea,
+        img,
+        locale::Locale,
+        output::Output,
+        range::OffsetDateTimeRange,
+        size::{PlotSize, Scale},
+    },
 };
 
-const WIDTH: u32 = 2048;
-const HEIGHT: u32 = 389;
+const DEFAULT_SIZE: PlotSize = PlotSize::new(2048, 389);
 const MARGIN: u32 = 64;
 const Y_MARGIN_LOGO: i32 = 16;
 const LOGO_WIDTH: u32 = 425;
 const LOGO_HEIGHT: u32 = 225;
 const X_LABEL_AREA_SIZE: u32 = 56;
-const BUCKET_WIDTH: Duration = Duration::from_secs(60 * 60 * 24);
-const KERNEL_SIGMA: f64 = 150.0;
-
-fn gaussian_kernel(sigma: f64) -> Vec<f64> {
-    let num_points = (2 * (3.0 * sigma).ceil() as usize) + 1;
-    (0..num_points)
-        .map(|i| {
-            (-0.5 * ((i as f64 - num_points as f64 / 2.0) / sigma).powi(2)).exp()
-                / (sigma * (2.0 * PI).sqrt())
-        })
-        .collect()
+const MARKER_RADIUS: u32 = 4;
+/// Opacity of the density fill at the baseline, fading from opaque at its peak
+const BASELINE_OPACITY: f64 = 0.0;
+const DAY: Duration = Duration::from_hours(24);
+const DEFAULT_MAX_BUCKETS: f64 = 4096.0;
+/// Limit on the number of buckets even for a requested bucket width, which is coarsened to fit
+const MAX_BUCKETS: f64 = 65536.0;
+const DEFAULT_SIGMA_FRACTION: f64 = 0.01;
+const MIN_SIGMA_DAYS: f64 = 7.0;
+const DEGENERATE_RANGE_PADDING: time::Duration = time::Duration::days(183);
+
+/// Density estimation parameters, derived from the release date range where unspecified
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SmoothingParams {
+    pub bucket_width: Option<Duration>,
+    pub sigma_days: Option<f64>,
 }
 
-pub fn release_dates<P>(path: P, data: &Data) -> Result<()>
+impl SmoothingParams {
+    /// Bucket width and kernel standard deviation in days for a range of `span_days` days
+    fn resolve(self, span_days: f64) -> (Duration, f64) {
+        let min_bucket_width = DAY.mul_f64(span_days / MAX_BUCKETS);
+        let bucket_width = match self.bucket_width {
+            Some(bucket_width) if bucket_width < min_bucket_width => {
+                warn!(
+                    "Bucket width of {bucket_width:?} would need more than {MAX_BUCKETS} buckets, using {min_bucket_width:?}"
+                );
+                min_bucket_width
+            }
+            Some(bucket_width) => bucket_width,
+            None => DAY * (span_days / DEFAULT_MAX_BUCKETS).ceil().max(1.0) as u32,
+        };
+        let sigma_days = self
+            .sigma_days
+            .unwrap_or_else(|| (span_days * DEFAULT_SIGMA_FRACTION).max(MIN_SIGMA_DAYS));
+        (bucket_width, sigma_days)
+    }
+}
+
+/// Density of release dates, each game contributing according to `weight`
+pub fn release_dates<'a, O, W>(
+    output: O,
+    size: Option<PlotSize>,
+    locale: &Locale,
+    data: &Data,
+    smoothing: SmoothingParams,
+    weight: W,
+) -> Result<()>
 where
-    P: AsRef<Path>,
+    O: Into<Output<'a>>,
+    W: Fn(&Meta) -> f64,
 {
-    info!(
-        "Generating visualization {}",
-        path.as_ref().to_string_lossy()
-    );
+    let output = output.into();
+    let name = output.to_string();
+    info!("Generating visualization {name}");
+
+    draw_release_dates(
+        output,
+        size.unwrap_or(DEFAULT_SIZE),
+        locale,
+        data,
+        smoothing,
+        &weight,
+    )
+    .with_context(|| format!("Could not generate release dates visualization {name}"))?;
+
+    info!("Generated visualization {name}");
+
+    Ok(())
+}
+
+fn draw_release_dates(
+    output: Output<'_>,
+    size: PlotSize,
+    locale: &Locale,
+    data: &Data,
+    smoothing: SmoothingParams,
+    weight: &dyn Fn(&Meta) -> f64,
+) -> Result<()> {
+    let scale = size.scale_from(DEFAULT_SIZE);
+    let Some((mut start_date, mut end_date)) = data.release_date_range() else {
+        return draw_empty(output, size, locale);
+    };
+    if start_date == end_date {
+        start_date -= DEGENERATE_RANGE_PADDING;
+        end_date += DEGENERATE_RANGE_PADDING;
+    }
+    let (bucket_width, sigma_days) = smoothing.resolve((end_date - start_date) / DAY);
+    let kernel = kde::gaussian_kernel(sigma_days / bucket_width.div_duration_f64(DAY));
+
+    let description = format!("Release dates of {} games", data.metas.0.len());
+    output.render((size.width, size.height), &description, |root| {
+        draw_background(root, scale)?;
+
+        let metas = data.metas_sorted_by_date();
+        let mut densities =
+            vec![0.0; ((end_date - start_date) / bucket_width).ceil().max(1.0) as usize];
+        kde::accumulate(
+            &mut densities,
+            &kernel,
+            metas.iter().map(|meta| {
+                (
+                    (meta.first_release_date - start_date) / bucket_width,
+                    weight(meta),
+                )
+            }),
+        );
+        let buckets = densities
+            .into_iter()
+            .enumerate()
+            .map(|(i, density)| {
+                (
+                    start_date + bucket_width * i as u32 + bucket_width / 2,
+                    density,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let max_bucket = buckets.iter().fold(0.0, |acc, (_, x)| x.max(acc));
+        let max_bucket = if max_bucket > 0.0 { max_bucket } else { 1.0 };
+        let mut chart = ChartBuilder::on(root)
+            .x_label_area_size(scale.text(X_LABEL_AREA_SIZE))
+            .margin(scale.margin(MARGIN))
+            .build_cartesian_2d(
+                OffsetDateTimeRange {
+                    start: start_date,
+                    end: end_date,
+                    locale,
+                },
+                0.0..max_bucket,
+            )?;
+
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .x_desc(&locale.release_date)
+            .label_style(scale.font())
+            .axis_style(Color::FONT_PRIMARY)
+            .draw()?;
 
-    let kernel = gaussian_kernel(KERNEL_SIGMA);
-    let (start_date, end_date) = data
-        .release_date_range()
-        .ok_or_else(|| anyhow!("Could not calculate release date range."))?;
+        chart.draw_series(iter::once(GradientArea::new(
+            buckets.iter().copied(),
+            0.0,
+            &Color::ACCENT_BLUE,
+            &Color::ACCENT_BLUE.mix(BASELINE_OPACITY),
+        )))?;
+        chart.draw_series(LineSeries::new(buckets, Color::FONT_PRIMARY))?;
 
-    let root = BitMapBackend::new(&path, (WIDTH, HEIGHT)).into_drawing_area();
+        chart.draw_series(metas.iter().map(|meta| {
+            Circle::new(
+                (meta.first_release_date, 0.0),
+                scale.px(MARKER_RADIUS),
+                ShapeStyle::from(Color::ACCENT_YELLOW).filled(),
+            )
+        }))?;
+
+        Ok(())
+    })
+}
+
+/// Background with the logo, shared by the empty chart
+fn draw_background(root: &DrawingArea<BitMapBackend<'_>, Shift>, scale: Scale) -> Result<()> {
     root.fill(&Color::BG_PRIMARY)?;
 
     let logo = img::load(
         &fs::read(LOGO_FILENAME)?,
-        LOGO_WIDTH,
-        LOGO_HEIGHT,
+        scale.px(LOGO_WIDTH),
+        scale.px(LOGO_HEIGHT),
         Color::BG_PRIMARY,
+        scale.filter(),
     )?;
-    root.draw(&BitMapElement::from(((MARGIN as i32, Y_MARGIN_LOGO), logo)))?;
-
-    let mut buckets = (0..((end_date - start_date) / BUCKET_WIDTH).ceil() as usize)
-        .map(|i| (start_date + BUCKET_WIDTH * i as u32 + BUCKET_WIDTH / 2, 0.0))
-        .collect::<Vec<_>>();
-
-    for meta in data.metas.0.values() {
-        let i = ((meta.first_release_date - start_date) / BUCKET_WIDTH - kernel.len() as f64 / 2.0)
-            .round() as i32;
-        for (d, &s) in buckets
-            .iter_mut()
-            .skip(i.max(0) as usize)
-            .zip(kernel.iter().skip((-i).max(0) as usize))
-        {
-            d.1 += s;
-        }
+    root.draw(&BitMapElement::from((
+        (scale.margin(MARGIN) as i32, scale.offset(Y_MARGIN_LOGO)),
+        logo,
+    )))?;
+
+    Ok(())
+}
+
+/// Chart stating that there are no games to draw the release dates of
+fn draw_empty(output: Output<'_>, size: PlotSize, locale: &Locale) -> Result<()> {
+    let scale = size.scale_from(DEFAULT_SIZE);
+    output.render(
+        (size.width, size.height),
+        "Release dates of 0 games",
+        |root| {
+            draw_background(root, scale)?;
+            let (width, height) = root.dim_in_pixel();
+            root.draw_text(
+                &locale.no_release_dates,
+                &scale
+                    .font()
+                    .with_anchor::<Color>(Pos {
+                        h_pos: HPos::Center,
+                        v_pos: VPos::Center,
+                    })
+                    .into_text_style(root),
+                ((width / 2) as i32, (height / 2) as i32),
+            )?;
+            Ok(())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::fixture;
+
+    /// Number of buckets of `bucket_width` covering `span_days`
+    fn num_buckets(span_days: f64, bucket_width: Duration) -> f64 {
+        (span_days / bucket_width.div_duration_f64(DAY)).ceil()
     }
 
-    let max_bucket = buckets.iter().fold(0.0, |acc, (_, x)| x.max(acc));
-    let mut chart = ChartBuilder::on(&root)
-        .x_label_area_size(X_LABEL_AREA_SIZE)
-        .margin(MARGIN)
-        .build_cartesian_2d(
-            OffsetDateTimeRange {
-                start: start_date,
-                end: end_date,
-            },
-            0.0..max_bucket,
-        )?;
-
-    chart
-        .configure_mesh()
-        .disable_mesh()
-        .x_desc("Release Date")
-        .label_style(Font::default())
-        .axis_style(Color::FONT_PRIMARY)
-        .draw()?;
-
-    chart.draw_series(
-        AreaSeries::new(buckets, 0.0, Color::ACCENT_BLUE).border_style(Color::FONT_PRIMARY),
-    )?;
+    #[test]
+    fn draws_empty_chart_without_games() {
+        let mut png = Vec::new();
+        release_dates(
+            &mut png,
+            None,
+            &Locale::default(),
+            &fixture::data_without_metas(),
+            SmoothingParams::default(),
+            |_| 1.0,
+        )
+        .unwrap();
+        let image = image::load_from_memory(&png).unwrap();
+        assert_eq!(
+            (image.width(), image.height()),
+            (DEFAULT_SIZE.width, DEFAULT_SIZE.height)
+        );
+    }
 
-    chart.draw_series(data.metas.0.values().map(|meta| {
-        Circle::new(
-            (meta.first_release_date, 0.0),
-            4,
-            ShapeStyle::from(Color::ACCENT_YELLOW).filled(),
+    #[test]
+    fn draws_games_released_on_one_date() {
+        let mut data = fixture::data();
+        let released = data.metas_sorted_by_date()[0].first_release_date;
+        for meta in data.metas.0.values_mut() {
+            meta.first_release_date = released;
+        }
+        assert_eq!(data.release_date_range(), Some((released, released)));
+
+        let mut png = Vec::new();
+        release_dates(
+            &mut png,
+            None,
+            &Locale::default(),
+            &data,
+            SmoothingParams::default(),
+            |_| 1.0,
         )
-    }))?;
+        .unwrap();
+        let mut empty = Vec::new();
+        draw_empty(Output::from(&mut empty), DEFAULT_SIZE, &Locale::default()).unwrap();
+        // Compared by pixels, as the metadata of the files differs anyway
+        let pixels = |png: &[u8]| image::load_from_memory(png).unwrap().to_rgb8();
+        assert_ne!(pixels(&png), pixels(&empty));
+    }
 
-    info!(
-        "Generated visualization {}",
-        path.as_ref().to_string_lossy()
-    );
+    #[test]
+    fn caps_buckets_of_wide_spans() {
+        for years in [1.0, 40.0, 1000.0, 100_000.0] {
+            let span_days = years * 365.25;
+            let (bucket_width, _) = SmoothingParams::default().resolve(span_days);
+            assert!(
+                num_buckets(span_days, bucket_width) <= DEFAULT_MAX_BUCKETS,
+                "{years} years in buckets of {bucket_width:?}"
+            );
+        }
+    }
 
-    Ok(())
+    #[test]
+    fn coarsens_requested_buckets_of_wide_spans() {
+        let span_days = 1000.0 * 365.25;
+        let (bucket_width, _) = SmoothingParams {
+            bucket_width: Some(DAY),
+            sigma_days: None,
+        }
+        .resolve(span_days);
+        assert!(bucket_width > DAY);
+        assert!(num_buckets(span_days, bucket_width) <= MAX_BUCKETS);
+
+        // Left as requested where it fits
+        let (bucket_width, _) = SmoothingParams {
+            bucket_width: Some(DAY),
+            sigma_days: None,
+        }
+        .resolve(40.0 * 365.25);
+        assert_eq!(bucket_width, DAY);
+    }
 }
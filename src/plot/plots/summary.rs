@@ -1,387 +1,1029 @@
-use std::{fs, path::Path, sync::Arc};
+use std::{fs, str::FromStr, sync::Arc};
 
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Error, Result, anyhow};
+use image::DynamicImage;
 use plotters::{
     coord::Shift,
-    prelude::{BitMapBackend, BitMapElement, DrawingArea, IntoDrawingArea, Rectangle},
+    prelude::{BitMapElement, DrawingArea, Rectangle},
     style::{IntoTextStyle, ShapeStyle},
 };
 use plotters_backend::{
     DrawingBackend,
     text_anchor::{HPos, Pos, VPos},
 };
-use tokio::task::{JoinSet, LocalSet};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
-    data::{Data, LOGO_FILENAME},
-    join_local,
-    plot::{color::Color, font::Font, img},
-    request::resource::{ImageSize, ResourceRequestor},
+    data::{CompanyRole, Data, LOGO_FILENAME, Meta, UrlField},
+    plot::{
+        color::Color,
+        font::Font,
+        img,
+        locale::Locale,
+        output::Output,
+        size::{PlotSize, Scale},
+        text,
+    },
+    request::resource::{self, ImageSize, ResourceRequestor, Resources},
 };
 
-const WIDTH: u32 = 4096;
-const HEIGHT: u32 = 1556;
-const NUM_SEGMENTS: u32 = 7;
-const SEGMENT_WIDTH: u32 = WIDTH / NUM_SEGMENTS;
+const SEGMENT_SIZE: PlotSize = PlotSize::new(585, 1556);
 const MARGIN: u32 = 16;
 const TITLE_HEIGHT: u32 = 98;
 const SUBTITLE_HEIGHT: u32 = 28;
 const ITEM_GAP: u32 = 16;
 const ITEM_TITLE_HEIGHT: u32 = 32;
-const NUM_OVERRATED: usize = 5;
-const NUM_UNDERRATED: usize = 5;
-const NUM_GAME_ENGINES: usize = 4;
-const NUM_COMPANIES: usize = 7;
-const NUM_PLATFORMS: usize = 5;
+const MIN_IMAGE_HEIGHT: u32 = 128;
 const LOGO_WIDTH: u32 = 170;
 const LOGO_HEIGHT: u32 = 90;
 const TITLE_FONT_SIZE: u32 = 96;
 const FONT_SIZE: u32 = 32;
+const LIST_FONT_SIZE: u32 = 24;
+const LIST_LINE_SPACING: f64 = 1.0;
+const LIST_COLUMN_GAP: u32 = 8;
+const LIST_MAX_ROW_HEIGHT: u32 = 72;
+/// Lists of more items than this are laid out in three columns instead of two
+const LIST_MAX_TWO_COLUMN_ITEMS: usize = 30;
+/// How far the top of a segment's background is lightened towards the font color
+const GRADIENT_HIGHLIGHT: f64 = 0.08;
 
-#[allow(clippy::too_many_lines)]
-pub async fn summary<P>(path: &'static P, data: Arc<Data>) -> Result<()>
-where
-    P: AsRef<Path> + ?Sized,
-{
-    info!(
-        "Generating visualization {}",
-        path.as_ref().to_string_lossy()
-    );
+/// Cell of the summary, showing up to `n` items
+struct SegmentSpec {
+    title: fn(&Locale) -> &str,
+    subtitle: Option<fn(&Locale) -> &str>,
+    n: usize,
+    /// Provides at most the given number of items, captioned in the locale
+    items: for<'a> fn(&'a Data, &Locale, usize) -> Result<Vec<Item<'a>>>,
+    bg: BackgroundStyle,
+    layout: Layout,
+}
 
-    let root = BitMapBackend::new(path, (WIDTH, HEIGHT)).into_drawing_area();
+/// How the items of a segment are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    /// Stacked images, each captioned with its text
+    Images,
+    /// Only the text of the items, in columns
+    List,
+}
 
-    let roots = root.split_evenly((1, NUM_SEGMENTS as usize));
+/// Fill of a segment
+#[derive(Debug, Clone, Copy)]
+enum BackgroundStyle {
+    Solid(Color),
+    /// Vertical gradient from the top color to the bottom color
+    Gradient(Color, Color),
+}
 
-    let mut tasks = JoinSet::new();
-    let local_tasks = LocalSet::new();
+impl BackgroundStyle {
+    /// Vertical gradient lightened at the top and settling on `color`
+    fn highlighted(color: Color) -> Self {
+        Self::Gradient(color.lerp(Color::FONT_PRIMARY, GRADIENT_HIGHLIGHT), color)
+    }
 
-    {
-        let root = roots[0].clone();
-        let data = data.clone();
-        tasks.spawn_local_on(
-            async move {
-                draw_segment(
-                    root,
-                    "List Toppers",
-                    None,
-                    data.extrema(true)
-                        .iter()
-                        .map(|(id, duration)| {
-                            let meta = &data.metas.0[id];
-                            (
-                                meta.cover.as_ref().map(|url_field| url_field.url.as_str()),
-                                format!("{} days", duration.whole_days()),
-                            )
-                        })
-                        .collect::<Vec<_>>()
-                        .as_slice(),
-                    &data.res,
-                    Color::BG_PRIMARY,
-                )
-                .await
-            },
-            &local_tasks,
-        );
+    /// Color which images and placeholders are drawn against
+    const fn base(self) -> Color {
+        match self {
+            Self::Solid(color) | Self::Gradient(_, color) => color,
+        }
     }
 
+    fn fill<DB>(self, root: &DrawingArea<DB, Shift>) -> Result<()>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
     {
-        let root = roots[1].clone();
-        let data = data.clone();
-        tasks.spawn_local_on(
-            async move {
-                draw_segment(
-                    root,
-                    "Barrel Bottoms",
-                    None,
-                    data.extrema(false)
-                        .iter()
-                        .map(|(id, duration)| {
-                            let meta = &data.metas.0[id];
-                            (
-                                meta.cover.as_ref().map(|url_field| url_field.url.as_str()),
-                                format!("{} days", duration.whole_days()),
-                            )
-                        })
-                        .collect::<Vec<_>>()
-                        .as_slice(),
-                    &data.res,
-                    Color::BG_SECONDARY,
-                )
-                .await
-            },
-            &local_tasks,
-        );
+        match self {
+            Self::Solid(color) => root.fill(&color)?,
+            Self::Gradient(top, bottom) => {
+                let (width, height) = root.dim_in_pixel();
+                for y in 0..height {
+                    let color = top.lerp(bottom, f64::from(y) / f64::from(height.max(2) - 1));
+                    root.draw(&Rectangle::new(
+                        [(0, y as i32), (width as i32, y as i32 + 1)],
+                        ShapeStyle::from(color).filled(),
+                    ))?;
+                }
+            }
+        }
+        Ok(())
     }
+}
 
-    {
-        let root = roots[2].clone();
-        let data = data.clone();
-        tasks.spawn_local_on(
-            async move {
-                draw_segment(
-                    root,
-                    "Overrated",
-                    Some("compared to IGDB ranking"),
-                    data.igdb_diffs()
-                        .ok_or_else(|| anyhow!("Could not generate IGDB rating differences."))?
-                        [..NUM_OVERRATED]
-                        .iter()
-                        .map(|(diff, meta)| {
-                            (
-                                meta.cover.as_ref().map(|url_field| url_field.url.as_str()),
-                                format!("{diff:+} positions"),
-                            )
-                        })
-                        .collect::<Vec<_>>()
-                        .as_slice(),
-                    &data.res,
-                    Color::BG_PRIMARY,
-                )
-                .await
-            },
-            &local_tasks,
-        );
+impl SegmentSpec {
+    const fn new(
+        title: fn(&Locale) -> &str,
+        n: usize,
+        items: for<'a> fn(&'a Data, &Locale, usize) -> Result<Vec<Item<'a>>>,
+    ) -> Self {
+        Self {
+            title,
+            subtitle: None,
+            n,
+            items,
+            bg: BackgroundStyle::Solid(Color::BG_PRIMARY),
+            layout: Layout::Images,
+        }
     }
 
-    {
-        let root = roots[3].clone();
-        let data = data.clone();
-        tasks.spawn_local_on(
-            async move {
-                let igdb_diffs = data
-                    .igdb_diffs()
-                    .ok_or_else(|| anyhow!("Could not generate IGDB rating differences."))?;
-                draw_segment(
-                    root,
-                    "Underrated",
-                    Some("compared to IGDB ranking"),
-                    igdb_diffs[igdb_diffs.len() - NUM_UNDERRATED..]
-                        .iter()
-                        .rev()
-                        .map(|(diff, meta)| {
-                            (
-                                meta.cover.as_ref().map(|url_field| url_field.url.as_str()),
-                                format!("{diff:+} positions"),
-                            )
-                        })
-                        .collect::<Vec<_>>()
-                        .as_slice(),
-                    &data.res,
-                    Color::BG_SECONDARY,
-                )
-                .await
-            },
-            &local_tasks,
-        );
+    const fn with_list_layout(mut self) -> Self {
+        self.layout = Layout::List;
+        self
     }
 
-    {
-        let root = roots[4].clone();
-        let data = data.clone();
-        tasks.spawn_local_on(
-            async move {
-                draw_segment(
-                    root,
-                    "Game Engines",
-                    None,
-                    data.most_common(
-                        |meta| meta.game_engines.iter(),
-                        |game_engine| game_engine.name.as_str(),
-                    )[..NUM_GAME_ENGINES]
-                        .iter()
+    const fn with_subtitle(mut self, subtitle: fn(&Locale) -> &str) -> Self {
+        self.subtitle = Some(subtitle);
+        self
+    }
+}
+
+/// Kind of segment which can be shown in the summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentKind {
+    ListToppers,
+    /// List toppers scored by their time at the top relative to the length of the list
+    NormalizedListToppers,
+    BarrelBottoms,
+    Risers,
+    Fallers,
+    Overrated,
+    Underrated,
+    Engines,
+    Companies,
+    Platforms,
+    Genres,
+    Developers,
+    Publishers,
+    Porters,
+    Supporters,
+    FullRanking,
+}
+
+impl SegmentKind {
+    const ALL: [Self; 16] = [
+        Self::ListToppers,
+        Self::NormalizedListToppers,
+        Self::BarrelBottoms,
+        Self::Risers,
+        Self::Fallers,
+        Self::Overrated,
+        Self::Underrated,
+        Self::Engines,
+        Self::Companies,
+        Self::Platforms,
+        Self::Genres,
+        Self::Developers,
+        Self::Publishers,
+        Self::Porters,
+        Self::Supporters,
+        Self::FullRanking,
+    ];
+    /// Segments shown unless others are selected
+    const DEFAULT: [Self; 7] = [
+        Self::ListToppers,
+        Self::BarrelBottoms,
+        Self::Overrated,
+        Self::Underrated,
+        Self::Engines,
+        Self::Companies,
+        Self::Platforms,
+    ];
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::ListToppers => "list-toppers",
+            Self::NormalizedListToppers => "normalized-list-toppers",
+            Self::BarrelBottoms => "barrel-bottoms",
+            Self::Risers => "risers",
+            Self::Fallers => "fallers",
+            Self::Overrated => "overrated",
+            Self::Underrated => "underrated",
+            Self::Engines => "engines",
+            Self::Companies => "companies",
+            Self::Platforms => "platforms",
+            Self::Genres => "genres",
+            Self::Developers => "developers",
+            Self::Publishers => "publishers",
+            Self::Porters => "porters",
+            Self::Supporters => "supporters",
+            Self::FullRanking => "full-ranking",
+        }
+    }
+
+    /// Segment with its default number of items
+    #[allow(clippy::too_many_lines)]
+    fn spec(self) -> SegmentSpec {
+        match self {
+            Self::ListToppers => SegmentSpec::new(
+                |locale| &locale.segments.list_toppers,
+                5,
+                |data, locale, n| Ok(extrema_items(data, locale, true, n)),
+            ),
+            Self::NormalizedListToppers => SegmentSpec::new(
+                |locale| &locale.segments.list_toppers,
+                5,
+                |data, locale, n| Ok(normalized_extrema_items(data, locale, true, n)),
+            )
+            .with_subtitle(|locale| &locale.normalized_by_list_length),
+            Self::BarrelBottoms => SegmentSpec::new(
+                |locale| &locale.segments.barrel_bottoms,
+                5,
+                |data, locale, n| Ok(extrema_items(data, locale, false, n)),
+            ),
+            Self::Risers => SegmentSpec::new(
+                |locale| &locale.segments.risers,
+                5,
+                |data, locale, n| change_items(data, locale, true, n),
+            )
+            .with_subtitle(|locale| &locale.since_last_episode),
+            Self::Fallers => SegmentSpec::new(
+                |locale| &locale.segments.fallers,
+                5,
+                |data, locale, n| change_items(data, locale, false, n),
+            )
+            .with_subtitle(|locale| &locale.since_last_episode),
+            Self::Overrated => SegmentSpec::new(
+                |locale| &locale.segments.overrated,
+                5,
+                |data, locale, n| igdb_items(data, locale, true, n),
+            )
+            .with_subtitle(|locale| &locale.compared_to_igdb),
+            Self::Underrated => SegmentSpec::new(
+                |locale| &locale.segments.underrated,
+                5,
+                |data, locale, n| igdb_items(data, locale, false, n),
+            )
+            .with_subtitle(|locale| &locale.compared_to_igdb),
+            Self::Engines => SegmentSpec::new(
+                |locale| &locale.segments.engines,
+                4,
+                |data, locale, n| {
+                    Ok(data
+                        .most_common(
+                            |meta| meta.game_engines.iter(),
+                            |game_engine| game_engine.name.as_str(),
+                        )
+                        .into_iter()
+                        .take(n)
                         .map(|(count, game_engine)| {
-                            (
-                                game_engine
-                                    .logo
-                                    .as_ref()
-                                    .map(|url_field| url_field.url.as_str()),
-                                format!("{count} games"),
+                            Item::new(
+                                game_engine.logo.as_ref(),
+                                &game_engine.name,
+                                locale.games(count),
                             )
                         })
-                        .collect::<Vec<_>>()
-                        .as_slice(),
-                    &data.res,
-                    Color::BG_PRIMARY,
-                )
-                .await
-            },
-            &local_tasks,
-        );
-    }
-
-    {
-        let root = roots[5].clone();
-        let data = data.clone();
-        tasks.spawn_local_on(
-            async move {
-                draw_segment(
-                    root,
-                    "Companies",
-                    None,
-                    data.most_common(
-                        |meta| meta.involved_companies.iter(),
-                        |involved_company| involved_company.company.name.as_str(),
-                    )[..NUM_COMPANIES]
-                        .iter()
+                        .collect())
+                },
+            ),
+            Self::Companies => SegmentSpec::new(
+                |locale| &locale.segments.companies,
+                7,
+                |data, locale, n| {
+                    Ok(data
+                        .most_common(
+                            |meta| meta.involved_companies.iter(),
+                            |involved_company| involved_company.company.name.as_str(),
+                        )
+                        .into_iter()
+                        .take(n)
                         .map(|(count, involved_company)| {
-                            (
-                                involved_company
-                                    .company
-                                    .logo
-                                    .as_ref()
-                                    .map(|url_field| url_field.url.as_str()),
-                                format!("{count} games"),
+                            Item::new(
+                                involved_company.company.logo.as_ref(),
+                                &involved_company.company.name,
+                                locale.games(count),
                             )
                         })
-                        .collect::<Vec<_>>()
-                        .as_slice(),
-                    &data.res,
-                    Color::BG_SECONDARY,
-                )
-                .await
-            },
-            &local_tasks,
-        );
-    }
-
-    {
-        let root = roots[6].clone();
-        let data = data.clone();
-        tasks.spawn_local_on(
-            async move {
-                draw_segment(
-                    root,
-                    "Platforms",
-                    None,
-                    data.most_common(
-                        |meta| meta.platforms.iter(),
-                        |platform| platform.name.as_str(),
-                    )[..NUM_PLATFORMS]
-                        .iter()
+                        .collect())
+                },
+            ),
+            Self::Platforms => SegmentSpec::new(
+                |locale| &locale.segments.platforms,
+                5,
+                |data, locale, n| {
+                    Ok(data
+                        .most_common(
+                            |meta| meta.platforms.iter(),
+                            |platform| platform.name.as_str(),
+                        )
+                        .into_iter()
+                        .take(n)
                         .map(|(count, platform)| {
-                            (
-                                platform
-                                    .platform_logo
-                                    .as_ref()
-                                    .map(|url_field| url_field.url.as_str()),
-                                format!("{count} games"),
+                            Item::new(
+                                platform.platform_logo.as_ref(),
+                                &platform.name,
+                                locale.games(count),
                             )
                         })
-                        .collect::<Vec<_>>()
-                        .as_slice(),
-                    &data.res,
-                    Color::BG_PRIMARY,
+                        .collect())
+                },
+            ),
+            Self::Genres => SegmentSpec::new(
+                |locale| &locale.segments.genres,
+                7,
+                |data, locale, n| {
+                    Ok(data
+                        .most_common(|meta| meta.genres.iter(), |genre| genre.name.as_str())
+                        .into_iter()
+                        .take(n)
+                        .map(|(count, genre)| Item::new(None, &genre.name, locale.games(count)))
+                        .collect())
+                },
+            ),
+            Self::Developers => SegmentSpec::new(
+                |locale| &locale.segments.developers,
+                7,
+                |data, locale, n| Ok(company_items(data, locale, CompanyRole::Developer, n)),
+            ),
+            Self::Publishers => SegmentSpec::new(
+                |locale| &locale.segments.publishers,
+                7,
+                |data, locale, n| Ok(company_items(data, locale, CompanyRole::Publisher, n)),
+            ),
+            Self::Porters => SegmentSpec::new(
+                |locale| &locale.segments.porters,
+                7,
+                |data, locale, n| Ok(company_items(data, locale, CompanyRole::Porter, n)),
+            ),
+            Self::Supporters => SegmentSpec::new(
+                |locale| &locale.segments.supporters,
+                7,
+                |data, locale, n| Ok(company_items(data, locale, CompanyRole::Supporter, n)),
+            ),
+            Self::FullRanking => SegmentSpec::new(
+                |locale| &locale.segments.full_ranking,
+                usize::MAX,
+                |data, locale, n| {
+                    Ok(data
+                        .latest()
+                        .ok_or_else(|| anyhow!("Latest list doesn't exist"))?
+                        .0
+                        .iter()
+                        .take(n)
+                        .enumerate()
+                        .map(|(i, id)| {
+                            let meta = &data.metas.0[id];
+                            Item::new(None, &meta.name, locale.ranked(i + 1, &meta.name))
+                        })
+                        .collect())
+                },
+            )
+            .with_list_layout(),
+        }
+    }
+}
+
+impl FromStr for SegmentKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown summary segment \"{s}\", expected one of {}",
+                    Self::ALL.map(Self::name).join(", ")
                 )
-                .await
+            })
+    }
+}
+
+/// Segment of the summary, showing `n` items instead of its default number if set
+#[derive(Debug, Clone, Copy)]
+pub struct SummarySegment {
+    pub kind: SegmentKind,
+    pub n: Option<usize>,
+}
+
+impl FromStr for SummarySegment {
+    type Err = Error;
+
+    /// Parses `<KIND>[:<N>]`
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, n) = s
+            .split_once(':')
+            .map_or((s, None), |(kind, n)| (kind, Some(n)));
+        Ok(Self {
+            kind: kind.parse()?,
+            n: n.map(|n| {
+                n.parse()
+                    .with_context(|| format!("Invalid number of items in summary segment \"{s}\""))
+            })
+            .transpose()?,
+        })
+    }
+}
+
+/// Specs of the `selected` segments, or the default ones if none are, in reading order, with
+/// alternating backgrounds
+fn segments(selected: &[SummarySegment]) -> Vec<SegmentSpec> {
+    let mut segments = if selected.is_empty() {
+        SegmentKind::DEFAULT.map(SegmentKind::spec).into()
+    } else {
+        selected
+            .iter()
+            .map(|segment| {
+                let mut spec = segment.kind.spec();
+                spec.n = segment.n.unwrap_or(spec.n);
+                spec
+            })
+            .collect::<Vec<_>>()
+    };
+    alternate_backgrounds(&mut segments);
+    segments
+}
+
+/// Segments of the episode summary, showing only what changed since the last episode, followed
+/// by the game at the top of the list
+fn episode_segments(data: &Data, locale: &Locale) -> Vec<SegmentSpec> {
+    let mut segments = [
+        SegmentSpec::new(
+            |locale| &locale.segments.new_entries,
+            5,
+            |data, locale, n| newcomer_items(data.debuts(), |i| locale.position(i + 1), n),
+        ),
+        SegmentSpec::new(
+            |locale| &locale.segments.departures,
+            5,
+            |data, locale, n| {
+                newcomer_items(data.departures(), |i| locale.former_position(i + 1), n)
             },
-            &local_tasks,
+        ),
+        SegmentSpec::new(
+            |locale| &locale.segments.top_riser,
+            1,
+            |data, locale, n| change_items(data, locale, true, n),
+        ),
+        SegmentSpec::new(
+            |locale| &locale.segments.top_faller,
+            1,
+            |data, locale, n| change_items(data, locale, false, n),
+        ),
+    ]
+    .into_iter()
+    .map(|segment| segment.with_subtitle(|locale| &locale.since_last_episode))
+    .filter(|segment| (segment.items)(data, locale, segment.n).is_ok_and(|items| !items.is_empty()))
+    .collect::<Vec<_>>();
+    if segments.is_empty() {
+        segments.push(
+            SegmentSpec::new(
+                |locale| &locale.segments.no_changes,
+                0,
+                |_, _, _| Ok(Vec::new()),
+            )
+            .with_subtitle(|locale| &locale.since_last_episode),
         );
     }
+    segments.push(SegmentSpec::new(
+        |locale| &locale.segments.number_one,
+        1,
+        |data, locale, _| {
+            let (meta, streak) = data
+                .top_streak()
+                .ok_or_else(|| anyhow!("Could not find the top of the latest list."))?;
+            let text = if streak.is_zero() {
+                locale.new_this_episode.clone()
+            } else {
+                locale.streak(streak.whole_days())
+            };
+            Ok(vec![
+                Item::cover(meta, text).with_color(Color::ACCENT_YELLOW),
+            ])
+        },
+    ));
+    alternate_backgrounds(&mut segments);
+    segments
+}
 
-    join_local(tasks, local_tasks).await?;
+/// Highlighted backgrounds alternating between the primary and secondary background colors
+fn alternate_backgrounds(segments: &mut [SegmentSpec]) {
+    for (i, segment) in segments.iter_mut().enumerate() {
+        segment.bg = BackgroundStyle::highlighted(if i % 2 == 1 {
+            Color::BG_SECONDARY
+        } else {
+            Color::BG_PRIMARY
+        });
+    }
+}
 
-    let logo = img::load(
-        &fs::read(LOGO_FILENAME)?,
-        LOGO_WIDTH,
-        LOGO_HEIGHT,
-        Color::BG_PRIMARY,
-    )?;
-    root.draw(&BitMapElement::from((
-        (
-            (WIDTH - MARGIN - LOGO_WIDTH) as i32,
-            (HEIGHT - MARGIN - LOGO_HEIGHT) as i32,
-        ),
-        logo,
-    )))?;
+/// Image and caption of an entry in a segment
+struct Item<'a> {
+    url: Option<&'a str>,
+    /// Whether the image is the cover of a game, replaced by a placeholder of its size if missing
+    cover: bool,
+    /// Name of the pictured entry, shown on a placeholder if the image is unavailable
+    name: &'a str,
+    text: String,
+    color: Color,
+}
 
-    root.present()?;
+impl<'a> Item<'a> {
+    fn new(url: Option<&'a UrlField>, name: &'a str, text: String) -> Self {
+        Self {
+            url: url.map(|url_field| url_field.url.as_str()),
+            cover: false,
+            name,
+            text,
+            color: Color::FONT_PRIMARY,
+        }
+    }
 
-    info!(
-        "Generated visualization {}",
-        path.as_ref().to_string_lossy()
+    fn cover(meta: &'a Meta, text: String) -> Self {
+        Self {
+            cover: true,
+            ..Self::new(meta.cover.as_ref(), &meta.name, text)
+        }
+    }
+
+    const fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// Games which spent the longest time at the top or bottom of the list
+fn extrema_items<'a>(data: &'a Data, locale: &Locale, top: bool, n: usize) -> Vec<Item<'a>> {
+    data.extrema_fractions(top, n)
+        .into_iter()
+        .map(|(id, duration, fraction)| {
+            let meta = &data.metas.0[id];
+            Item::cover(meta, locale.days_share(duration.whole_days(), fraction))
+        })
+        .collect()
+}
+
+/// Games which spent the longest time at the top or bottom of the list, relative to its length
+fn normalized_extrema_items<'a>(
+    data: &'a Data,
+    locale: &Locale,
+    top: bool,
+    n: usize,
+) -> Vec<Item<'a>> {
+    data.extrema_normalized(top)
+        .into_iter()
+        .take(n)
+        .map(|(id, score)| {
+            let meta = &data.metas.0[id];
+            Item::cover(meta, locale.share_per_game(score))
+        })
+        .collect()
+}
+
+/// Companies which most often had `role`
+fn company_items<'a>(
+    data: &'a Data,
+    locale: &Locale,
+    role: CompanyRole,
+    n: usize,
+) -> Vec<Item<'a>> {
+    data.most_common_by_role(role)
+        .into_iter()
+        .take(n)
+        .map(|(count, company)| {
+            Item::new(company.logo.as_ref(), &company.name, locale.games(count))
+        })
+        .collect()
+}
+
+/// Games which rose or fell the most since the last episode
+fn change_items<'a>(
+    data: &'a Data,
+    locale: &Locale,
+    improved: bool,
+    n: usize,
+) -> Result<Vec<Item<'a>>> {
+    Ok(if improved {
+        data.most_improved(n)
+    } else {
+        data.most_declined(n)
+    }
+    .ok_or_else(|| anyhow!("Could not compare the latest lists."))?
+    .into_iter()
+    .map(|(meta, delta)| {
+        Item::cover(meta, locale.positions(delta)).with_color(if delta > 0 {
+            Color::ACCENT_BLUE
+        } else {
+            Color::ACCENT_PINK
+        })
+    })
+    .collect())
+}
+
+/// Games which entered or left the list since the last episode, captioned with `text` of their
+/// position
+fn newcomer_items<F>(
+    newcomers: Option<Vec<(&Meta, usize)>>,
+    text: F,
+    n: usize,
+) -> Result<Vec<Item<'_>>>
+where
+    F: Fn(usize) -> String,
+{
+    Ok(newcomers
+        .ok_or_else(|| anyhow!("Could not compare the latest lists."))?
+        .into_iter()
+        .take(n)
+        .map(|(meta, i)| Item::cover(meta, text(i)))
+        .collect())
+}
+
+/// Games ranked furthest above or below their IGDB ranking
+fn igdb_items<'a>(
+    data: &'a Data,
+    locale: &Locale,
+    overrated: bool,
+    n: usize,
+) -> Result<Vec<Item<'a>>> {
+    let igdb_diffs = data
+        .igdb_diffs()
+        .ok_or_else(|| anyhow!("Could not generate IGDB rating differences."))?;
+    let item = |&(diff, meta): &(i32, &'a Meta)| Item::cover(meta, locale.positions(diff));
+    Ok(if overrated {
+        igdb_diffs.iter().take(n).map(item).collect()
+    } else {
+        igdb_diffs.iter().rev().take(n).map(item).collect()
+    })
+}
+
+/// Summary of the list, showing the `selected` segments, or the default ones if empty
+pub async fn summary<'a, O>(
+    output: O,
+    size: Option<PlotSize>,
+    selected: &[SummarySegment],
+    locale: &Locale,
+    data: Arc<Data>,
+) -> Result<()>
+where
+    O: Into<Output<'a>>,
+{
+    let output = output.into();
+    let name = output.to_string();
+    info!("Generating visualization {name}");
+
+    let description = format!(
+        "Summary of The Bonus Points list of {} games after {} episodes",
+        data.latest().map_or(0, |list| list.0.len()),
+        data.lists.0.len()
     );
+    draw_segments(
+        output,
+        size,
+        &segments(selected),
+        &description,
+        locale,
+        data,
+    )
+    .await
+    .with_context(|| format!("Could not generate summary visualization {name}"))?;
+
+    info!("Generated visualization {name}");
+
+    Ok(())
+}
+
+/// Summary of the changes to the list in the latest episode
+pub async fn episode_summary<'a, O>(
+    output: O,
+    size: Option<PlotSize>,
+    locale: &Locale,
+    data: Arc<Data>,
+) -> Result<()>
+where
+    O: Into<Output<'a>>,
+{
+    let output = output.into();
+    let name = output.to_string();
+    info!("Generating visualization {name}");
+
+    let description = format!(
+        "Changes to The Bonus Points list in episode {}",
+        data.lists.0.len()
+    );
+    draw_segments(
+        output,
+        size,
+        &episode_segments(&data, locale),
+        &description,
+        locale,
+        data,
+    )
+    .await
+    .with_context(|| format!("Could not generate episode summary visualization {name}"))?;
+
+    info!("Generated visualization {name}");
 
     Ok(())
 }
 
-async fn draw_segment<DB>(
-    root: DrawingArea<DB, Shift>,
-    title: &str,
-    subtitle: Option<&str>,
-    items: &[(Option<&str>, String)],
+/// Images drawn in any segment of the summary or episode summary
+pub fn resource_urls(data: &Data) -> Vec<(ImageSize, String)> {
+    // The images don't depend on the text of the captions
+    let locale = Locale::default();
+    let segments = SegmentKind::ALL
+        .map(SegmentKind::spec)
+        .into_iter()
+        .chain(episode_segments(data, &locale))
+        .collect::<Vec<_>>();
+    segment_urls(data, &locale, &segments)
+}
+
+/// Images drawn in `segments`
+fn segment_urls(
+    data: &Data,
+    locale: &Locale,
+    segments: &[SegmentSpec],
+) -> Vec<(ImageSize, String)> {
+    segments
+        .iter()
+        .filter_map(|segment| (segment.items)(data, locale, segment.n).ok())
+        .flatten()
+        .filter_map(|item| item.url.map(|url| (ImageSize::Hd, url.to_string())))
+        .collect()
+}
+
+/// Rows and columns of the grid whose cells are closest in aspect ratio to a segment of the
+/// default layout, widened as text is enlarged
+fn grid(size: PlotSize, num_segments: usize) -> (usize, usize) {
+    let target = f64::from(SEGMENT_SIZE.width) * size.font_scale / f64::from(SEGMENT_SIZE.height);
+    (1..=num_segments.max(1))
+        .map(|rows| (rows, num_segments.div_ceil(rows).max(1)))
+        .min_by(|&(rows_a, columns_a), &(rows_b, columns_b)| {
+            let mismatch = |rows: usize, columns: usize| {
+                let aspect = (f64::from(size.width) / columns as f64)
+                    / (f64::from(size.height) / rows as f64);
+                (aspect / target).ln().abs()
+            };
+            mismatch(rows_a, columns_a).total_cmp(&mismatch(rows_b, columns_b))
+        })
+        .unwrap_or((1, 1))
+}
+
+/// Draws `segments` on a grid, sized to fit them side by side by default
+async fn draw_segments(
+    output: Output<'_>,
+    size: Option<PlotSize>,
+    segments: &[SegmentSpec],
+    description: &str,
+    locale: &Locale,
+    data: Arc<Data>,
+) -> Result<()> {
+    let size = size.unwrap_or_else(|| {
+        PlotSize::new(
+            SEGMENT_SIZE.width * segments.len() as u32,
+            SEGMENT_SIZE.height,
+        )
+    });
+    let (num_rows, num_columns) = grid(size, segments.len());
+    // Each cell is laid out like a segment of the default layout
+    let scale = PlotSize {
+        width: size.width / num_columns as u32,
+        height: size.height / num_rows as u32,
+        ..size
+    }
+    .scale_from(SEGMENT_SIZE);
+    let images = data
+        .res
+        .get_all(segment_urls(&data, locale, segments))
+        .await?;
+
+    output.render((size.width, size.height), description, |root| {
+        root.fill(&Color::BG_PRIMARY)?;
+        let cells = root.split_evenly((num_rows, num_columns));
+
+        for (segment, cell) in segments.iter().zip(&cells) {
+            let (_, cell_height) = cell.dim_in_pixel();
+            let n = match segment.layout {
+                Layout::Images => segment.n.min(max_items(cell_height, scale)),
+                Layout::List => segment.n,
+            };
+            let items = (segment.items)(&data, locale, n)?;
+            draw_segment(cell, segment, locale, &items, &images, &data.res, scale)?;
+        }
+
+        let last_cell = &cells[segments.len().clamp(1, cells.len()) - 1];
+        let (cell_width, cell_height) = last_cell.dim_in_pixel();
+        let logo = img::load(
+            &fs::read(LOGO_FILENAME)?,
+            scale.px(LOGO_WIDTH),
+            scale.px(LOGO_HEIGHT),
+            Color::BG_PRIMARY,
+            scale.filter(),
+        )?;
+        last_cell.draw(&BitMapElement::from((
+            (
+                cell_width.saturating_sub(scale.px(MARGIN + LOGO_WIDTH)) as i32,
+                cell_height.saturating_sub(scale.px(MARGIN + LOGO_HEIGHT)) as i32,
+            ),
+            logo,
+        )))?;
+
+        Ok(())
+    })
+}
+
+/// Number of items which fit in a cell of `height` with legible images
+fn max_items(height: u32, scale: Scale) -> usize {
+    let available = height.saturating_sub(2 * scale.margin(MARGIN) + scale.text(TITLE_HEIGHT));
+    let item_height =
+        scale.px(ITEM_GAP) + scale.text(ITEM_TITLE_HEIGHT) + scale.px(MIN_IMAGE_HEIGHT);
+    (available / item_height).max(1) as usize
+}
+
+fn draw_segment<DB>(
+    root: &DrawingArea<DB, Shift>,
+    segment: &SegmentSpec,
+    locale: &Locale,
+    items: &[Item<'_>],
+    images: &Resources,
     res: &ResourceRequestor,
-    bg: Color,
+    scale: Scale,
 ) -> Result<()>
 where
     DB: DrawingBackend,
     DB::ErrorType: 'static,
 {
-    root.fill(&bg)?;
-    let root = root.margin(MARGIN, MARGIN, MARGIN, MARGIN);
+    let (width, height) = root.dim_in_pixel();
+    let margin = scale.margin(MARGIN);
+    let title_height = scale.text(TITLE_HEIGHT);
+    let item_gap = scale.px(ITEM_GAP);
+    let item_title_height = scale.text(ITEM_TITLE_HEIGHT);
+    let image_width = width.saturating_sub(2 * margin).max(1);
+    segment.bg.fill(root)?;
+    let bg = segment.bg.base();
+    let root = root.margin(margin, margin, margin, margin);
 
     root.draw_text(
-        title,
-        &Font::new(TITLE_FONT_SIZE)
+        (segment.title)(locale),
+        &Font::new(scale.text(TITLE_FONT_SIZE))
             .with_anchor::<Color>(Pos {
                 h_pos: HPos::Center,
                 v_pos: VPos::Top,
             })
             .into_text_style(&root),
-        (SEGMENT_WIDTH as i32 / 2, 0),
+        ((width / 2) as i32, 0),
     )?;
-    if let Some(subtitle) = subtitle {
+    if let Some(subtitle) = segment.subtitle {
         root.draw_text(
-            subtitle,
-            &Font::new(FONT_SIZE)
+            subtitle(locale),
+            &Font::new(scale.text(FONT_SIZE))
                 .with_anchor::<Color>(Pos {
                     h_pos: HPos::Center,
                     v_pos: VPos::Top,
                 })
                 .into_text_style(&root),
             (
-                SEGMENT_WIDTH as i32 / 2,
-                (TITLE_HEIGHT - SUBTITLE_HEIGHT).try_into().unwrap(),
+                (width / 2) as i32,
+                title_height.saturating_sub(scale.text(SUBTITLE_HEIGHT)) as i32,
             ),
         )?;
     }
     root.draw(&Rectangle::new(
         [
-            (MARGIN as i32, (TITLE_HEIGHT - 2) as i32),
-            ((SEGMENT_WIDTH - MARGIN) as i32, TITLE_HEIGHT as i32),
+            (
+                margin as i32,
+                title_height.saturating_sub(scale.px(2)) as i32,
+            ),
+            (width.saturating_sub(margin) as i32, title_height as i32),
         ],
         ShapeStyle::from(Color::FONT_PRIMARY).filled(),
     ))?;
 
-    let image_height =
-        (HEIGHT - 2 * MARGIN - TITLE_HEIGHT) / items.len() as u32 - ITEM_GAP - ITEM_TITLE_HEIGHT;
+    if segment.layout == Layout::List {
+        return draw_list(&root, items, title_height + item_gap, scale);
+    }
 
-    for (i, (url, text)) in items.iter().enumerate() {
-        let y = TITLE_HEIGHT + i as u32 * (image_height + ITEM_GAP + ITEM_TITLE_HEIGHT) + ITEM_GAP;
+    let image_height = (height.saturating_sub(2 * margin + title_height)
+        / items.len().max(1) as u32)
+        .saturating_sub(item_gap + item_title_height)
+        .max(1);
 
-        if let Some(url) = url {
-            let image = res.get(ImageSize::Hd, url).await?;
-            let image = img::load(&image, SEGMENT_WIDTH - 2 * MARGIN, image_height, bg)?;
-            root.draw(&BitMapElement::from((
-                (
-                    (((SEGMENT_WIDTH - 2 * MARGIN) - image.width()) / 2) as i32,
-                    (y + ITEM_TITLE_HEIGHT + (image_height - image.height() as u32) / 2) as i32,
-                ),
-                image,
-            )))?;
-        }
+    for (i, item) in items.iter().enumerate() {
+        let y = title_height + i as u32 * (image_height + item_gap + item_title_height) + item_gap;
+
+        let image = item_image(item, images, res, (image_width, image_height), bg, scale)?;
+        root.draw(&BitMapElement::from((
+            (
+                (image_width.saturating_sub(image.width()) / 2) as i32,
+                (y + item_title_height + image_height.saturating_sub(image.height()) / 2) as i32,
+            ),
+            image,
+        )))?;
 
         root.draw_text(
-            text,
-            &Font::new(FONT_SIZE)
+            &item.text,
+            &Font::new(scale.text(FONT_SIZE))
+                .with_color(item.color)
                 .with_anchor::<Color>(Pos {
                     h_pos: HPos::Center,
                     v_pos: VPos::Top,
                 })
                 .into_text_style(&root),
-            ((SEGMENT_WIDTH / 2) as i32, y as i32),
+            ((width / 2) as i32, y as i32),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Texts of `items` below `top`, numbered down the columns
+fn draw_list<DB>(
+    root: &DrawingArea<DB, Shift>,
+    items: &[Item<'_>],
+    top: u32,
+    scale: Scale,
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let (width, height) = root.dim_in_pixel();
+    let num_columns = if items.len() > LIST_MAX_TWO_COLUMN_ITEMS {
+        3
+    } else {
+        2
+    };
+    let num_rows = items.len().div_ceil(num_columns).max(1);
+    let column_width = width / num_columns as u32;
+    let row_height =
+        (height.saturating_sub(top) / num_rows as u32).min(scale.text(LIST_MAX_ROW_HEIGHT));
+    let font_size = scale.text(LIST_FONT_SIZE);
+
+    for (i, item) in items.iter().enumerate() {
+        let x = (i / num_rows) as u32 * column_width;
+        let y = top + (i % num_rows) as u32 * row_height + row_height / 2;
+        text::draw_wrapped_text(
+            root,
+            &item.text,
+            &Font::new(font_size)
+                .with_color(item.color)
+                .with_anchor::<Color>(Pos {
+                    h_pos: HPos::Left,
+                    v_pos: VPos::Center,
+                })
+                .into_text_style(root),
+            (x as i32, y as i32),
+            column_width.saturating_sub(scale.px(LIST_COLUMN_GAP)),
+            (f64::from(row_height) / (f64::from(font_size) * LIST_LINE_SPACING)) as usize,
+            LIST_LINE_SPACING,
         )?;
     }
 
     Ok(())
 }
+
+/// Image of `item` fitting within `(image_width, image_height)`, or a placeholder with its initials
+fn item_image(
+    item: &Item<'_>,
+    images: &Resources,
+    res: &ResourceRequestor,
+    (image_width, image_height): (u32, u32),
+    bg: Color,
+    scale: Scale,
+) -> Result<DynamicImage> {
+    let image = match item.url {
+        Some(url) => match images.get(&(ImageSize::Hd, url.to_string())) {
+            Some(Ok(image)) => Some(res.images().load(
+                ImageSize::Hd,
+                url,
+                image,
+                image_width,
+                image_height,
+                bg,
+                scale.filter(),
+            )?),
+            Some(Err(err)) if item.cover && ResourceRequestor::is_missing(ImageSize::Hd, err) => {
+                warn!("Using placeholder for missing cover {url}: {err:#}");
+                Some(res.images().load(
+                    ImageSize::Hd,
+                    url,
+                    &resource::placeholder(ImageSize::Hd)?,
+                    image_width,
+                    image_height,
+                    bg,
+                    scale.filter(),
+                )?)
+            }
+            Some(Err(err)) => {
+                warn!("Using placeholder for image {url}: {err:#}");
+                None
+            }
+            None => None,
+        },
+        None => None,
+    };
+    if let Some(image) = image {
+        return Ok(image);
+    }
+    let size = image_height.min(image_width);
+    img::placeholder(
+        size,
+        size,
+        item.name,
+        if bg == Color::BG_PRIMARY {
+            Color::BG_SECONDARY
+        } else {
+            Color::BG_PRIMARY
+        },
+        Color::FONT_PRIMARY,
+    )
+}
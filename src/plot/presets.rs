@@ -0,0 +1,50 @@
+//! Named sizes suited to where visualizations are posted
+
+use std::str::FromStr;
+
+use anyhow::{Error, Result, anyhow};
+
+use super::size::PlotSize;
+
+/// Named plot size, with margins and text enlarged to stay legible when viewed small
+#[derive(Debug, Clone, Copy)]
+pub struct Preset {
+    pub name: &'static str,
+    pub size: PlotSize,
+}
+
+pub const PRESETS: [Preset; 4] = [
+    Preset {
+        name: "twitter",
+        size: PlotSize::new(1200, 675).with_scales(1.0, 1.5),
+    },
+    Preset {
+        name: "og",
+        size: PlotSize::new(1200, 630).with_scales(1.0, 1.5),
+    },
+    Preset {
+        name: "square",
+        size: PlotSize::new(1080, 1080).with_scales(1.0, 2.0),
+    },
+    // A4 landscape at 300 DPI, leaving room for trimming
+    Preset {
+        name: "print",
+        size: PlotSize::new(3508, 2480).with_scales(2.0, 1.0),
+    },
+];
+
+impl FromStr for Preset {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        PRESETS
+            .into_iter()
+            .find(|preset| preset.name == s)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown preset \"{s}\", expected one of {}",
+                    PRESETS.map(|preset| preset.name).join(", ")
+                )
+            })
+    }
+}
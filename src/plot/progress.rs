@@ -0,0 +1,71 @@
+//! Progress reporting of visualizations
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{LazyLock, Mutex, PoisonError},
+};
+
+use indicatif::ProgressBar;
+use tracing::info;
+
+use crate::logging;
+
+/// Spinners of the visualizations being generated, keyed by their names
+static SPINNERS: LazyLock<Mutex<HashMap<String, ProgressBar>>> = LazyLock::new(Mutex::default);
+
+/// Coarse stage of generating a visualization, following data preparation
+#[derive(Debug, Clone, Copy)]
+pub enum Stage {
+    Drawing,
+    Encoding,
+    Written,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Drawing => "drawing",
+                Self::Encoding => "encoding",
+                Self::Written => "written",
+            }
+        )
+    }
+}
+
+/// Reports that the visualization `plot` reached `stage`, on a spinner per visualization if
+/// progress bars are drawn
+pub fn report<T>(plot: &T, stage: Stage)
+where
+    T: fmt::Display + ?Sized,
+{
+    let name = plot.to_string();
+    let written = matches!(stage, Stage::Written);
+    let Some(spinner) = spinner(&name, written) else {
+        info!(%plot, %stage, "Visualization {plot} reached the {stage} stage");
+        return;
+    };
+    if written {
+        spinner.finish_and_clear();
+    } else {
+        spinner.set_message(format!("{name}: {stage}"));
+    }
+}
+
+/// Spinner of the visualization `name` if progress bars are drawn, forgotten if it is `finished`
+fn spinner(name: &str, finished: bool) -> Option<ProgressBar> {
+    let mut spinners = SPINNERS.lock().unwrap_or_else(PoisonError::into_inner);
+    if finished {
+        return spinners.remove(name).or_else(logging::spinner);
+    }
+    if let Some(spinner) = spinners.get(name) {
+        return Some(spinner.clone());
+    }
+    let spinner = logging::spinner()?;
+    spinners.insert(name.to_string(), spinner.clone());
+    drop(spinners);
+    Some(spinner)
+}
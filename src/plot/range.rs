@@ -6,13 +6,17 @@ use plotters::{
 };
 use time::OffsetDateTime;
 
+use super::locale::Locale;
+
+/// Time axis labelled with years in the format of `locale`
 #[derive(Debug)]
-pub struct OffsetDateTimeRange {
+pub struct OffsetDateTimeRange<'a> {
     pub start: OffsetDateTime,
     pub end: OffsetDateTime,
+    pub locale: &'a Locale,
 }
 
-impl Ranged for OffsetDateTimeRange {
+impl Ranged for OffsetDateTimeRange<'_> {
     type FormatOption = NoDefaultFormatting;
 
     type ValueType = OffsetDateTime;
@@ -36,12 +40,12 @@ impl Ranged for OffsetDateTimeRange {
     }
 }
 
-impl ValueFormatter<OffsetDateTime> for OffsetDateTimeRange {
+impl ValueFormatter<OffsetDateTime> for OffsetDateTimeRange<'_> {
     fn format(value: &OffsetDateTime) -> String {
         format!("{}", value.year())
     }
 
     fn format_ext(&self, value: &OffsetDateTime) -> String {
-        Self::format(value)
+        self.locale.year(value.year())
     }
 }
@@ -0,0 +1,124 @@
+//! Plot dimensions
+
+use std::str::FromStr;
+
+use anyhow::{Error, Result, anyhow};
+use image::imageops::FilterType;
+
+use super::font::Font;
+
+/// Dimensions of a visualization in pixels, and how much its margins and text are enlarged
+/// beyond the proportional layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlotSize {
+    pub width: u32,
+    pub height: u32,
+    pub margin_scale: f64,
+    pub font_scale: f64,
+}
+
+impl PlotSize {
+    pub const fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            margin_scale: 1.0,
+            font_scale: 1.0,
+        }
+    }
+
+    /// Enlarges margins by `margin_scale` and text by `font_scale`
+    #[must_use]
+    pub const fn with_scales(mut self, margin_scale: f64, font_scale: f64) -> Self {
+        self.margin_scale = margin_scale;
+        self.font_scale = font_scale;
+        self
+    }
+
+    /// Scale of this size relative to `reference`, along the more constrained axis
+    pub fn scale_from(self, reference: Self) -> Scale {
+        self.scale(
+            (f64::from(self.width) / f64::from(reference.width))
+                .min(f64::from(self.height) / f64::from(reference.height)),
+        )
+    }
+
+    /// Scale of this size relative to `reference` along the horizontal axis, for visualizations
+    /// whose height follows from their contents
+    pub fn width_scale_from(self, reference: Self) -> Scale {
+        self.scale(f64::from(self.width) / f64::from(reference.width))
+    }
+
+    fn scale(self, length: f64) -> Scale {
+        Scale {
+            length,
+            margin: length * self.margin_scale,
+            font: length * self.font_scale,
+        }
+    }
+}
+
+impl FromStr for PlotSize {
+    type Err = Error;
+
+    /// Parses `<WIDTH>x<HEIGHT>`
+    fn from_str(s: &str) -> Result<Self> {
+        let (width, height) = s
+            .split_once('x')
+            .ok_or_else(|| anyhow!("Size \"{s}\" is not of the form <WIDTH>x<HEIGHT>"))?;
+        let size = Self::new(width.parse()?, height.parse()?);
+        if size.width == 0 || size.height == 0 {
+            return Err(anyhow!("Size \"{s}\" is empty"));
+        }
+        Ok(size)
+    }
+}
+
+/// Factors by which lengths tuned for a reference size are scaled
+#[derive(Debug, Clone, Copy)]
+pub struct Scale {
+    length: f64,
+    margin: f64,
+    font: f64,
+}
+
+impl Scale {
+    /// Scaled length in pixels, at least 1
+    pub fn px(self, value: u32) -> u32 {
+        round(value, self.length)
+    }
+
+    /// Scaled signed offset in pixels
+    pub fn offset(self, value: i32) -> i32 {
+        (f64::from(value) * self.length).round() as i32
+    }
+
+    pub fn len(self, value: f64) -> f64 {
+        value * self.length
+    }
+
+    /// Scaled margin in pixels, at least 1
+    pub fn margin(self, value: u32) -> u32 {
+        round(value, self.margin)
+    }
+
+    /// Scaled font size, or length of an area holding text, in pixels, at least 1
+    pub fn text(self, value: u32) -> u32 {
+        round(value, self.font)
+    }
+
+    /// Filter with which images are resized, smoothing them when drawn larger than at the
+    /// reference size
+    pub fn filter(self) -> Option<FilterType> {
+        (self.length > 1.0).then_some(FilterType::Lanczos3)
+    }
+
+    /// Default font, scaled
+    pub fn font(self) -> Font {
+        Font::new(self.text(Font::DEFAULT_SIZE))
+    }
+}
+
+fn round(value: u32, factor: f64) -> u32 {
+    ((f64::from(value) * factor).round() as u32).max(1)
+}
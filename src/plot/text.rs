@@ -0,0 +1,274 @@
+//! Measuring and wrapping text
+
+use std::{convert::Infallible, mem};
+
+use anyhow::{Result, anyhow};
+use plotters::{
+    coord::Shift,
+    prelude::DrawingArea,
+    style::{Color as _, RGBColor, TextStyle},
+};
+use plotters_backend::{BackendColor, DrawingBackend};
+
+/// Approximate width of a glyph relative to the font size, for when text can't be measured
+const GLYPH_WIDTH: f64 = 0.6;
+
+/// Approximate width of `text` in pixels at `font_size`, from its number of characters
+pub fn estimated_width(text: &str, font_size: u32) -> u32 {
+    (text.chars().count() as f64 * f64::from(font_size) * GLYPH_WIDTH) as u32
+}
+
+/// Greedily breaks `text` into lines at most `max_width` wide, on word boundaries where possible
+/// and with hyphens within words which don't fit on a line of their own
+///
+/// `measure` gives the width of a line, or `None` if it can't be measured, in which case the
+/// width is estimated from `font_size`.
+pub fn wrap<F>(text: &str, max_width: u32, font_size: u32, measure: F) -> Vec<String>
+where
+    F: Fn(&str) -> Option<u32>,
+{
+    let width = |line: &str| measure(line).unwrap_or_else(|| estimated_width(line, font_size));
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{line} {word}")
+        };
+        if width(&candidate) <= max_width {
+            line = candidate;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(mem::take(&mut line));
+        }
+        let mut rest = word;
+        while width(rest) > max_width {
+            // At least one character per line, even if it doesn't fit
+            let split = rest
+                .char_indices()
+                .skip(1)
+                .map(|(i, _)| i)
+                .take_while(|&i| width(&format!("{}-", &rest[..i])) <= max_width)
+                .last()
+                .unwrap_or_else(|| rest.chars().next().map_or(rest.len(), char::len_utf8));
+            lines.push(format!("{}-", &rest[..split]));
+            rest = &rest[split..];
+        }
+        line = rest.to_string();
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Draws `text` wrapped to `max_width`, with the lines spaced `line_spacing` times the font size
+/// apart and vertically centered on `pos`. Text beyond `max_lines` lines is cut off with an
+/// ellipsis.
+pub fn draw_wrapped_text<DB>(
+    area: &DrawingArea<DB, Shift>,
+    text: &str,
+    style: &TextStyle<'_>,
+    (x, y): (i32, i32),
+    max_width: u32,
+    max_lines: usize,
+    line_spacing: f64,
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let font_size = style.font.get_size();
+    let measure = |line: &str| {
+        area.estimate_text_size(line, style)
+            .ok()
+            .map(|(width, _)| width)
+    };
+    let mut lines = wrap(text, max_width, font_size as u32, measure);
+    if lines.len() > max_lines.max(1) {
+        lines.truncate(max_lines.max(1));
+        if let Some(last) = lines.pop() {
+            lines.push(ellipsize(&last, max_width, |line| {
+                measure(line).unwrap_or_else(|| estimated_width(line, font_size as u32))
+            }));
+        }
+    }
+    let line_height = font_size * line_spacing;
+    let first_offset = -(lines.len().saturating_sub(1) as f64) * line_height / 2.0;
+    for (i, line) in lines.iter().enumerate() {
+        area.draw_text(
+            line,
+            style,
+            (
+                x,
+                y + (i as f64).mul_add(line_height, first_offset).round() as i32,
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+/// `line` shortened to fit within `max_width` with an ellipsis appended
+fn ellipsize<F>(line: &str, max_width: u32, width: F) -> String
+where
+    F: Fn(&str) -> u32,
+{
+    let mut line = line.trim_end_matches('-');
+    loop {
+        let ellipsized = format!("{}\u{2026}", line.trim_end());
+        if line.is_empty() || width(&ellipsized) <= max_width {
+            return ellipsized;
+        }
+        line = &line[..line.char_indices().last().map_or(0, |(i, _)| i)];
+    }
+}
+
+/// Draws `text` rotated counterclockwise by `angle` radians about the left end of its vertical
+/// center, which is placed at `pos`
+///
+/// Plotters can only rotate text by multiples of 90 degrees, so the glyphs are rasterized upright
+/// and resampled.
+pub fn draw_rotated_text<DB>(
+    area: &DrawingArea<DB, Shift>,
+    text: &str,
+    style: &TextStyle<'_>,
+    (x, y): (i32, i32),
+    angle: f64,
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let mut glyph_pixels = Vec::new();
+    style
+        .font
+        .draw(text, (0, 0), |x, y, alpha| {
+            glyph_pixels.push((x, y, alpha));
+            Ok::<_, Infallible>(())
+        })
+        .map_err(|err| anyhow!("Could not rasterize \"{text}\": {err:?}"))?
+        .ok();
+    let (Some(min_x), Some(max_x), Some(min_y), Some(max_y)) = (
+        glyph_pixels.iter().map(|pixel| pixel.0).min(),
+        glyph_pixels.iter().map(|pixel| pixel.0).max(),
+        glyph_pixels.iter().map(|pixel| pixel.1).min(),
+        glyph_pixels.iter().map(|pixel| pixel.1).max(),
+    ) else {
+        return Ok(());
+    };
+    let (width, height) = ((max_x - min_x + 1) as usize, (max_y - min_y + 1) as usize);
+    let mut coverage = vec![0.0; width * height];
+    for (x, y, alpha) in glyph_pixels {
+        let i = (y - min_y) as usize * width + (x - min_x) as usize;
+        coverage[i] = f64::from(alpha).max(coverage[i]);
+    }
+    let coverage_at = |x: i64, y: i64| {
+        usize::try_from(x)
+            .ok()
+            .zip(usize::try_from(y).ok())
+            .filter(|&(x, y)| x < width && y < height)
+            .map_or(0.0, |(x, y)| coverage[y * width + x])
+    };
+
+    // Rotating offsets from the pivot, in backend coordinates whose y axis points down
+    let (sin, cos) = angle.sin_cos();
+    let pivot_y = height as f64 / 2.0;
+    let corners = [
+        (0.0, 0.0),
+        (width as f64, 0.0),
+        (0.0, height as f64),
+        (width as f64, height as f64),
+    ]
+    .map(|(u, v)| {
+        let v = v - pivot_y;
+        (u.mul_add(cos, v * sin), v.mul_add(cos, -u * sin))
+    });
+    let bound = |select: fn(&(f64, f64)) -> f64, fold: fn(f64, f64) -> f64, start| {
+        corners.iter().map(select).fold(start, fold)
+    };
+    let (left, right) = (
+        bound(|c| c.0, f64::min, f64::INFINITY).floor() as i32,
+        bound(|c| c.0, f64::max, f64::NEG_INFINITY).ceil() as i32,
+    );
+    let (top, bottom) = (
+        bound(|c| c.1, f64::min, f64::INFINITY).floor() as i32,
+        bound(|c| c.1, f64::max, f64::NEG_INFINITY).ceil() as i32,
+    );
+    let BackendColor { alpha, rgb } = style.color;
+    for dy in top..=bottom {
+        for dx in left..=right {
+            // Bilinearly sampled coverage of the upright glyphs at the unrotated offset
+            let (offset_x, offset_y) = (f64::from(dx), f64::from(dy));
+            let u = offset_x.mul_add(cos, -offset_y * sin) - 0.5;
+            let v = offset_x.mul_add(sin, offset_y * cos) + pivot_y - 0.5;
+            let (u0, v0) = (u.floor(), v.floor());
+            let (fu, fv) = (u - u0, v - v0);
+            let (u0, v0) = (u0 as i64, v0 as i64);
+            let top_row = coverage_at(u0, v0) * (1.0 - fu) + coverage_at(u0 + 1, v0) * fu;
+            let bottom_row =
+                coverage_at(u0, v0 + 1) * (1.0 - fu) + coverage_at(u0 + 1, v0 + 1) * fu;
+            let value = top_row * (1.0 - fv) + bottom_row * fv;
+            if value > 0.0 {
+                area.draw_pixel(
+                    (x + dx, y + dy),
+                    &RGBColor(rgb.0, rgb.1, rgb.2).mix(alpha * value),
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ten pixels per character
+    #[allow(clippy::unnecessary_wraps)]
+    fn measure(line: &str) -> Option<u32> {
+        Some(line.chars().count() as u32 * 10)
+    }
+
+    #[test]
+    fn wraps_on_word_boundaries() {
+        assert_eq!(
+            wrap("the quick brown fox jumps", 100, 0, measure),
+            ["the quick", "brown fox", "jumps"]
+        );
+        assert_eq!(wrap("  spaced   out  ", 100, 0, measure), ["spaced out"]);
+        assert!(wrap("", 100, 0, measure).is_empty());
+    }
+
+    #[test]
+    fn hyphenates_words_too_long_for_a_line() {
+        assert_eq!(
+            wrap("a supercalifragilistic word", 100, 0, measure),
+            ["a", "supercali-", "fragilist-", "ic word"]
+        );
+        // At least one character per line, even if it doesn't fit
+        assert_eq!(wrap("ab", 15, 0, measure), ["a-", "b"]);
+    }
+
+    #[test]
+    fn estimates_unmeasurable_lines() {
+        // Six pixels per character at a font size of ten
+        assert_eq!(
+            wrap("hello world again", 60, 10, |_| None),
+            ["hello", "world", "again"]
+        );
+        assert_eq!(
+            wrap("hello world again", 66, 10, |_| None),
+            ["hello world", "again"]
+        );
+    }
+
+    #[test]
+    fn ellipsizes_to_fit() {
+        let width = |line: &str| line.chars().count() as u32 * 10;
+        assert_eq!(ellipsize("supercali-", 50, width), "supe\u{2026}");
+        assert_eq!(ellipsize("the end ", 50, width), "the\u{2026}");
+        assert_eq!(ellipsize("fits", 50, width), "fits\u{2026}");
+    }
+}
@@ -0,0 +1,117 @@
+//! Cover thumbnails drawn beside the names labelling an axis
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use plotters::{
+    coord::Shift,
+    prelude::{BitMapBackend, BitMapElement, DrawingArea},
+    style::TextStyle,
+};
+use tracing::warn;
+
+use super::{color::Color, size::Scale};
+use crate::{
+    data::{Data, GameId},
+    request::resource::{ImageSize, ResourceRequestor},
+};
+
+/// Height of a thumbnail, unless the labels are closer together
+const HEIGHT: u32 = 28;
+const GAP: i32 = 8;
+/// Distance between an axis and its labels, as laid out by plotters for its default tick marks
+pub const LABEL_DISTANCE: i32 = 10;
+
+/// Side of the plotting area on which an axis is labelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// Covers of games, drawn as thumbnails beside their names
+#[derive(Debug)]
+pub struct Thumbnails {
+    res: ResourceRequestor,
+    /// URL and contents of the cover of each game
+    covers: HashMap<GameId, (String, Vec<u8>)>,
+}
+
+/// Images drawn as thumbnails
+pub fn resource_urls(data: &Data) -> Vec<(ImageSize, String)> {
+    data.metas
+        .0
+        .values()
+        .filter_map(|meta| meta.cover.as_ref())
+        .map(|cover| (ImageSize::CoverSmall, cover.url.clone()))
+        .collect()
+}
+
+impl Thumbnails {
+    /// Gets the covers of every game, leaving out those which can't be obtained
+    pub async fn fetch(data: &Data) -> Result<Self> {
+        let covers = data.res.get_all(resource_urls(data)).await?;
+        Ok(Self {
+            res: data.res.clone(),
+            covers: data
+                .metas
+                .0
+                .iter()
+                .filter_map(|(id, meta)| {
+                    let url = meta.cover.as_ref()?.url.clone();
+                    match covers.get(&(ImageSize::CoverSmall, url.clone()))? {
+                        Ok(cover) => Some((id.clone(), (url, cover.clone()))),
+                        Err(err) => {
+                            warn!("Leaving out thumbnail of {id}: {err:#}");
+                            None
+                        }
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    /// Draws the thumbnail of each game of `labels` just past its label, which is its text drawn
+    /// in its font at the backend row `y`, on the `side` of the axis at the backend column `axis_x`
+    pub fn draw(
+        &self,
+        root: &DrawingArea<BitMapBackend<'_>, Shift>,
+        axis_x: i32,
+        side: Side,
+        labels: &[(&GameId, String, &TextStyle, i32)],
+        sizing: Scale,
+    ) -> Result<()> {
+        // Thumbnails of neighbouring labels mustn't overlap
+        let pitch = labels
+            .windows(2)
+            .map(|pair| (pair[1].3 - pair[0].3).unsigned_abs())
+            .min()
+            .unwrap_or(u32::MAX);
+        let height = sizing.px(HEIGHT).min(pitch).max(1);
+        let (base_x, base_y) = root.get_base_pixel();
+
+        for (id, text, font, y) in labels {
+            let Some((url, cover)) = self.covers.get(*id) else {
+                continue;
+            };
+            let image = self.res.images().load(
+                ImageSize::CoverSmall,
+                url,
+                cover,
+                height,
+                height,
+                Color::BG_PRIMARY,
+                sizing.filter(),
+            )?;
+            let text_width = root.estimate_text_size(text, font)?.0 as i32;
+            let x = match side {
+                Side::Left => axis_x - LABEL_DISTANCE - text_width - GAP - image.width() as i32,
+                Side::Right => axis_x + LABEL_DISTANCE + text_width + GAP,
+            };
+            let y = y - (image.height() / 2) as i32;
+            root.draw(&BitMapElement::from(((x - base_x, y - base_y), image)))?;
+        }
+
+        Ok(())
+    }
+}
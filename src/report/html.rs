@@ -0,0 +1,264 @@
+//! Statistics of the list as a self-contained HTML page, its images embedded in it
+
+use std::fmt::Write as _;
+
+use anyhow::{Result, anyhow};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use tracing::warn;
+
+use crate::{
+    data::{Data, List, Meta, RatingKind},
+    plot::Color,
+    request::resource::{ImageSize, ResourceRequestor, Resources},
+};
+
+pub const HTML_REPORT_FILENAME: &str = "out/report.html";
+/// Number of the most common genres and platforms charted
+const NUM_MOST_COMMON: usize = 15;
+/// Width of the ratings grouped into each bar of their distributions
+const RATING_BUCKET_WIDTH: f64 = 10.0;
+/// Highest rating, falling into the bucket below it rather than its own
+const MAX_RATING: f64 = 100.0;
+const THUMBNAIL_HEIGHT: u32 = 48;
+const CHART_WIDTH: u32 = 640;
+const CHART_LABEL_WIDTH: u32 = 224;
+const CHART_BAR_HEIGHT: u32 = 20;
+const CHART_BAR_GAP: u32 = 4;
+const CHART_FONT_SIZE: u32 = 14;
+
+/// HTML page with the latest list and the covers of its games, bar charts of its most common
+/// genres and platforms, and the distributions of its ratings, embedding every image so that it
+/// can be shared as a single file
+pub async fn generate_html(data: &Data, res: &ResourceRequestor) -> Result<String> {
+    let list_date = *data
+        .dates()
+        .last()
+        .ok_or_else(|| anyhow!("Latest list doesn't exist"))?;
+    let latest_list = &data.lists.0[&list_date];
+    let metas = latest_list
+        .0
+        .iter()
+        .map(|id| data.metas.0.get(id))
+        .collect::<Vec<_>>();
+    let covers = res
+        .get_all(
+            metas
+                .iter()
+                .flatten()
+                .filter_map(|meta| Some((ImageSize::CoverSmall, meta.cover.as_ref()?.url.clone()))),
+        )
+        .await?;
+
+    let mut doc = String::new();
+    let title = format!("The Bonus Points list on {list_date}");
+    writeln!(doc, "<!DOCTYPE html>")?;
+    writeln!(doc, "<html lang=\"en\">")?;
+    writeln!(doc, "<head>")?;
+    writeln!(doc, "<meta charset=\"utf-8\">")?;
+    writeln!(doc, "<title>{title}</title>")?;
+    writeln!(doc, "<style>{}</style>", style())?;
+    writeln!(doc, "</head>")?;
+    writeln!(doc, "<body>")?;
+    writeln!(doc, "<h1>{title}</h1>")?;
+
+    write_ranking(&mut doc, latest_list, &metas, &covers)?;
+    write_most_common(&mut doc, data)?;
+    write_ratings(&mut doc, &metas)?;
+
+    writeln!(doc, "</body>")?;
+    writeln!(doc, "</html>")?;
+    Ok(doc)
+}
+
+fn write_ranking(
+    doc: &mut String,
+    list: &List,
+    metas: &[Option<&Meta>],
+    covers: &Resources,
+) -> Result<()> {
+    writeln!(doc, "<table>")?;
+    writeln!(
+        doc,
+        "<tr><th>#</th><th></th><th>Game</th><th>Released</th><th>IGDB rating</th></tr>"
+    )?;
+    for (i, (id, meta)) in list.0.iter().zip(metas).enumerate() {
+        let cover = meta
+            .and_then(|meta| meta.cover.as_ref())
+            .and_then(
+                |cover| match covers.get(&(ImageSize::CoverSmall, cover.url.clone()))? {
+                    Ok(cover) => Some(cover),
+                    Err(err) => {
+                        warn!("Leaving out cover of {id} from the report: {err:#}");
+                        None
+                    }
+                },
+            )
+            .map(|cover| {
+                format!(
+                    "<img src=\"{}\" height=\"{THUMBNAIL_HEIGHT}\" alt=\"\">",
+                    data_uri(cover)
+                )
+            })
+            .unwrap_or_default();
+        writeln!(
+            doc,
+            "<tr><td>{}</td><td>{cover}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            i + 1,
+            escape(&meta.map_or_else(|| id.to_string(), |meta| meta.name.clone())),
+            meta.map(|meta| meta.first_release_date.year().to_string())
+                .unwrap_or_default(),
+            meta.and_then(|meta| meta.total_rating)
+                .map(|rating| format!("{rating:.0}"))
+                .unwrap_or_default()
+        )?;
+    }
+    writeln!(doc, "</table>")?;
+    Ok(())
+}
+
+fn write_most_common(doc: &mut String, data: &Data) -> Result<()> {
+    let genres = data
+        .most_common(|meta| meta.genres.iter(), |genre| genre.name.as_str())
+        .into_iter()
+        .take(NUM_MOST_COMMON)
+        .map(|(count, genre)| (genre.name.clone(), count))
+        .collect::<Vec<_>>();
+    let platforms = data
+        .most_common(
+            |meta| meta.platforms.iter(),
+            |platform| platform.name.as_str(),
+        )
+        .into_iter()
+        .take(NUM_MOST_COMMON)
+        .map(|(count, platform)| (platform.name.clone(), count))
+        .collect::<Vec<_>>();
+    for (heading, values) in [("Genres", genres), ("Platforms", platforms)] {
+        writeln!(doc, "<h2>{heading}</h2>")?;
+        writeln!(doc, "{}", bar_chart(&values))?;
+    }
+    Ok(())
+}
+
+fn write_ratings(doc: &mut String, metas: &[Option<&Meta>]) -> Result<()> {
+    writeln!(doc, "<h2>Ratings</h2>")?;
+    for kind in RatingKind::ALL {
+        let ratings = metas
+            .iter()
+            .flatten()
+            .filter_map(|meta| meta.get_rating(kind))
+            .collect::<Vec<_>>();
+        if ratings.is_empty() {
+            continue;
+        }
+        writeln!(
+            doc,
+            "<h3>{}</h3>",
+            match kind {
+                RatingKind::User => "User",
+                RatingKind::Critic => "Critic",
+                RatingKind::Total => "Total",
+            }
+        )?;
+        writeln!(doc, "{}", bar_chart(&rating_distribution(&ratings)))?;
+    }
+    Ok(())
+}
+
+fn style() -> String {
+    format!(
+        "body {{ background: {}; color: {}; font-family: sans-serif; margin: 2em; }} \
+         table {{ border-collapse: collapse; }} \
+         td, th {{ padding: 0.25em 0.75em; text-align: left; }} \
+         tr:nth-child(even) {{ background: {}; }}",
+        String::from(Color::BG_PRIMARY),
+        String::from(Color::FONT_PRIMARY),
+        String::from(Color::BG_SECONDARY),
+    )
+}
+
+/// Number of `ratings` in each bucket of `RATING_BUCKET_WIDTH`, from the lowest to the highest
+/// bucket holding any
+fn rating_distribution(ratings: &[f64]) -> Vec<(String, u32)> {
+    let bucket = |rating: f64| {
+        (rating / RATING_BUCKET_WIDTH)
+            .floor()
+            .min(MAX_RATING / RATING_BUCKET_WIDTH - 1.0) as i64
+    };
+    let (Some(min), Some(max)) = (
+        ratings.iter().copied().map(bucket).min(),
+        ratings.iter().copied().map(bucket).max(),
+    ) else {
+        return Vec::new();
+    };
+    (min..=max)
+        .map(|i| {
+            let start = i as f64 * RATING_BUCKET_WIDTH;
+            (
+                format!("{start:.0}–{:.0}", start + RATING_BUCKET_WIDTH),
+                ratings
+                    .iter()
+                    .filter(|&&rating| bucket(rating) == i)
+                    .count() as u32,
+            )
+        })
+        .collect()
+}
+
+/// Inline SVG of horizontal bars, one per labelled value, scaled to the largest
+fn bar_chart(values: &[(String, u32)]) -> String {
+    let max = values
+        .iter()
+        .map(|(_, value)| *value)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let pitch = CHART_BAR_HEIGHT + CHART_BAR_GAP;
+    let bar_area = CHART_WIDTH - CHART_LABEL_WIDTH;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{CHART_WIDTH}\" height=\"{}\" \
+         font-size=\"{CHART_FONT_SIZE}\" fill=\"{}\">",
+        values.len() as u32 * pitch,
+        String::from(Color::FONT_PRIMARY)
+    );
+    for (i, (label, value)) in values.iter().enumerate() {
+        let y = i as u32 * pitch;
+        let center = y + CHART_BAR_HEIGHT / 2;
+        // Leaves room for the value beside the longest bar
+        let width = (bar_area - 4 * CHART_FONT_SIZE) * value / max;
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{center}\" text-anchor=\"end\" dominant-baseline=\"middle\">{}</text>\
+             <rect x=\"{CHART_LABEL_WIDTH}\" y=\"{y}\" width=\"{width}\" height=\"{CHART_BAR_HEIGHT}\" fill=\"{}\"/>\
+             <text x=\"{}\" y=\"{center}\" dominant-baseline=\"middle\">{value}</text>",
+            CHART_LABEL_WIDTH - CHART_BAR_GAP,
+            escape(label),
+            String::from(Color::ACCENT_BLUE),
+            CHART_LABEL_WIDTH + width + CHART_BAR_GAP,
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Image `data` as a `data:` URI, typed by its detected format
+fn data_uri(data: &[u8]) -> String {
+    let mime_type = image::guess_format(data)
+        .map_or("application/octet-stream", |format| format.to_mime_type());
+    format!("data:{mime_type};base64,{}", STANDARD.encode(data))
+}
+
+/// `text` with the characters HTML would interpret escaped
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
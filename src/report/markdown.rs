@@ -0,0 +1,211 @@
+//! Statistics of the list as a Markdown document, for sharing where images can't be
+
+use std::fmt::Write as _;
+
+use anyhow::{Result, anyhow};
+
+use crate::data::{Company, Data, GameId, Meta, RatingKind};
+
+pub const MARKDOWN_REPORT_FILENAME: &str = "out/report.md";
+/// Number of games listed as the longest at the top and bottom of the list
+const NUM_EXTREMA: usize = 5;
+/// Number of the most common genres, platforms and engines listed
+const NUM_MOST_COMMON: usize = 10;
+
+/// Markdown document with the latest list, the statistics of its ratings, the games which spent
+/// the longest at its top and bottom, the games which left it and returned, and its most common
+/// genres, platforms and engines
+pub fn generate_markdown(data: &Data) -> Result<String> {
+    let mut doc = String::new();
+    write_list(&mut doc, data)?;
+    write_ratings(&mut doc, data)?;
+    write_extrema(&mut doc, data)?;
+    write_recurrences(&mut doc, data)?;
+    write_most_common(&mut doc, data)?;
+    Ok(doc)
+}
+
+/// Escaped name of the game `id`, or the id itself if it has no metadata
+fn name(data: &Data, id: &GameId) -> String {
+    data.metas
+        .0
+        .get(id)
+        .map_or_else(|| id.to_string(), |meta| escape(&meta.name))
+}
+
+fn write_list(doc: &mut String, data: &Data) -> Result<()> {
+    let list_date = *data
+        .dates()
+        .last()
+        .ok_or_else(|| anyhow!("Latest list doesn't exist"))?;
+    writeln!(doc, "# The Bonus Points list on {list_date}")?;
+    writeln!(doc)?;
+    writeln!(doc, "| # | Game | Studio | Released | IGDB rating |")?;
+    writeln!(doc, "|--:|------|--------|---------:|------------:|")?;
+    for (i, id) in data.lists.0[&list_date].0.iter().enumerate() {
+        let meta = data.metas.0.get(id);
+        writeln!(
+            doc,
+            "| {} | {} | {} | {} | {} |",
+            i + 1,
+            name(data, id),
+            meta.and_then(studio)
+                .map(|company| escape(&company.name))
+                .unwrap_or_default(),
+            meta.map(|meta| meta.first_release_date.year().to_string())
+                .unwrap_or_default(),
+            meta.and_then(|meta| meta.total_rating)
+                .map(|rating| format!("{rating:.0}"))
+                .unwrap_or_default()
+        )?;
+    }
+    Ok(())
+}
+
+/// Company with the most significant role in making the game of `meta`, the first listed of
+/// several
+fn studio(meta: &Meta) -> Option<&Company> {
+    meta.involved_companies
+        .iter()
+        .min_by_key(|involved_company| involved_company.primary_role())
+        .map(|involved_company| &involved_company.company)
+}
+
+fn write_ratings(doc: &mut String, data: &Data) -> Result<()> {
+    writeln!(doc)?;
+    writeln!(doc, "## Ratings")?;
+    writeln!(doc)?;
+    writeln!(
+        doc,
+        "| Rating | Games | Mean | Minimum | Lower quartile | Median | Upper quartile | Maximum |"
+    )?;
+    writeln!(
+        doc,
+        "|--------|------:|-----:|--------:|---------------:|-------:|---------------:|--------:|"
+    )?;
+    for kind in RatingKind::ALL {
+        let Some(stats) = data.rating_stats(kind) else {
+            continue;
+        };
+        let quartiles = stats.quartiles;
+        writeln!(
+            doc,
+            "| {} | {} | {:.1} | {:.1} | {:.1} | {:.1} | {:.1} | {:.1} |",
+            match kind {
+                RatingKind::User => "User",
+                RatingKind::Critic => "Critic",
+                RatingKind::Total => "Total",
+            },
+            stats.count,
+            stats.mean,
+            quartiles.min,
+            quartiles.q1,
+            quartiles.median,
+            quartiles.q3,
+            quartiles.max
+        )?;
+    }
+    Ok(())
+}
+
+fn write_extrema(doc: &mut String, data: &Data) -> Result<()> {
+    for (title, top) in [("List toppers", true), ("Barrel bottoms", false)] {
+        writeln!(doc)?;
+        writeln!(doc, "## {title}")?;
+        writeln!(doc)?;
+        for (i, (id, duration)) in data.extrema(top, NUM_EXTREMA).into_iter().enumerate() {
+            writeln!(
+                doc,
+                "{}. {}: {}",
+                i + 1,
+                name(data, id),
+                days(duration.whole_days())
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write_recurrences(doc: &mut String, data: &Data) -> Result<()> {
+    let recurrences = data
+        .recurrences()
+        .into_iter()
+        .take_while(|&(_, stints)| stints > 1)
+        .collect::<Vec<_>>();
+    if recurrences.is_empty() {
+        return Ok(());
+    }
+    writeln!(doc)?;
+    writeln!(doc, "## Returning games")?;
+    writeln!(doc)?;
+    for (id, stints) in recurrences {
+        writeln!(doc, "- {}: {stints} stints", name(data, &id))?;
+    }
+    Ok(())
+}
+
+fn write_most_common(doc: &mut String, data: &Data) -> Result<()> {
+    let sections = [
+        (
+            "Most common genres",
+            data.most_common(|meta| meta.genres.iter(), |genre| genre.name.as_str())
+                .into_iter()
+                .map(|(count, genre)| (count, genre.name.as_str()))
+                .collect::<Vec<_>>(),
+        ),
+        (
+            "Most common platforms",
+            data.most_common(
+                |meta| meta.platforms.iter(),
+                |platform| platform.name.as_str(),
+            )
+            .into_iter()
+            .map(|(count, platform)| (count, platform.name.as_str()))
+            .collect(),
+        ),
+        (
+            "Most common engines",
+            data.most_common(
+                |meta| meta.game_engines.iter(),
+                |game_engine| game_engine.name.as_str(),
+            )
+            .into_iter()
+            .map(|(count, game_engine)| (count, game_engine.name.as_str()))
+            .collect(),
+        ),
+    ];
+    for (title, values) in sections {
+        writeln!(doc)?;
+        writeln!(doc, "## {title}")?;
+        writeln!(doc)?;
+        for (count, value) in values.into_iter().take(NUM_MOST_COMMON) {
+            writeln!(
+                doc,
+                "- {} ({} {})",
+                escape(value),
+                count,
+                if count == 1 { "game" } else { "games" }
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn days(days: i64) -> String {
+    format!("{days} {}", if days == 1 { "day" } else { "days" })
+}
+
+/// `text` with the characters Markdown would interpret, including table separators, escaped
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
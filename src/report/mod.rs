@@ -0,0 +1,7 @@
+//! Statistics of the list as documents, for sharing as text instead of images
+
+mod html;
+mod markdown;
+
+pub use html::{HTML_REPORT_FILENAME, generate_html};
+pub use markdown::{MARKDOWN_REPORT_FILENAME, generate_markdown};
@@ -1,23 +1,46 @@
 use std::time::Duration;
 
+use time::OffsetDateTime;
+
 use anyhow::{Result, anyhow};
+use async_stream::try_stream;
 use reqwest::{Client, Request, Response, StatusCode};
-use serde::Deserialize;
+use serde::{Deserialize, de::DeserializeOwned};
+use tokio_stream::Stream;
 use tracing::{info, warn};
 
 use crate::data::{GameId, Meta, Metas};
 
+/// Maximum number of results IGDB returns per request
+const MAX_BATCH_SIZE: usize = 500;
+/// Log in again if the access token expires within this time
+const TOKEN_EXPIRY_MARGIN: time::Duration = time::Duration::seconds(60);
+const API_URL: &str = "https://api.igdb.com/v4";
+const GAME_FIELDS: &str = "age_ratings.category,age_ratings.rating,age_ratings.rating_cover_url,aggregated_rating,aggregated_rating_count,cover.url,first_release_date,franchise.name,game_engines.name,game_engines.logo.url,game_modes.name,genres.name,involved_companies.developer,involved_companies.porting,involved_companies.publisher,involved_companies.supporting,involved_companies.company.country,involved_companies.company.logo.url,involved_companies.company.name,involved_companies.company.start_date,keywords.name,multiplayer_modes.campaigncoop,multiplayer_modes.lancoop,multiplayer_modes.offlinecoop,multiplayer_modes.onlinecoop,name,platforms.category,platforms.name,platforms.generation,platforms.platform_logo.url,player_perspectives.name,release_dates.date,themes.name,rating,rating_count,total_rating,total_rating_count";
+
 #[derive(Debug)]
 pub struct IgdbRequestor {
     client: Client,
     client_id: String,
     client_secret: String,
     access_token: Option<String>,
+    token_expires_at: Option<OffsetDateTime>,
+    batch_size: usize,
+    api_url: String,
 }
 
 #[derive(Debug, Deserialize)]
 struct LoginResponsePayload {
     access_token: String,
+    expires_in: u64,
+}
+
+/// Game identified only by its id, and when it last changed
+#[derive(Debug, Deserialize)]
+struct UpdatedGame {
+    id: u32,
+    #[serde(with = "time::serde::timestamp")]
+    updated_at: OffsetDateTime,
 }
 
 impl IgdbRequestor {
@@ -28,6 +51,28 @@ impl IgdbRequestor {
             client_id: client_id.to_string(),
             client_secret: client_secret.to_string(),
             access_token: None,
+            token_expires_at: None,
+            batch_size: MAX_BATCH_SIZE,
+            api_url: API_URL.to_string(),
+        }
+    }
+
+    /// Sends API requests to `api_url` instead of IGDB
+    #[cfg(test)]
+    #[must_use]
+    pub fn with_api_url(self, api_url: &str) -> Self {
+        Self {
+            api_url: api_url.to_string(),
+            ..self
+        }
+    }
+
+    /// Number of games requested at once, capped at the IGDB maximum of 500
+    #[must_use]
+    pub fn with_batch_size(self, batch_size: usize) -> Self {
+        Self {
+            batch_size: batch_size.clamp(1, MAX_BATCH_SIZE),
+            ..self
         }
     }
 
@@ -45,7 +90,7 @@ impl IgdbRequestor {
             return Err(anyhow!("Failed to clone request"));
         };
         warn!("Reached IGDB API rate limit. Sleeping.");
-        tokio::time::sleep(Duration::from_secs(60)).await;
+        tokio::time::sleep(Duration::from_mins(1)).await;
         let resp = self.client.execute(request).await?.error_for_status()?;
         Ok(resp)
     }
@@ -61,39 +106,57 @@ impl IgdbRequestor {
                 ("client_secret", &self.client_secret),
             ])
             .build()?;
-        self.access_token = Some(
-            self.request(req)
-                .await?
-                .json::<LoginResponsePayload>()
-                .await?
-                .access_token,
-        );
+        let resp = self
+            .request(req)
+            .await?
+            .json::<LoginResponsePayload>()
+            .await?;
+        self.access_token = Some(resp.access_token);
+        self.token_expires_at =
+            Some(OffsetDateTime::now_utc() + time::Duration::seconds(resp.expires_in as i64));
         info!("Logged in to IGDB API");
         Ok(())
     }
 
-    pub async fn games(&mut self, ids: &[GameId]) -> Result<Metas> {
-        info!("Fetching games from IGDB");
-        let access_token = if let Some(access_token) = self.access_token.as_ref() {
-            access_token
-        } else {
+    /// Access token, logging in again first if there is none or it is about to expire
+    async fn access_token(&mut self) -> Result<String> {
+        let token_expiring = self
+            .token_expires_at
+            .is_none_or(|expires_at| expires_at - OffsetDateTime::now_utc() < TOKEN_EXPIRY_MARGIN);
+        if self.access_token.is_none() || token_expiring {
             self.login().await?;
-            self.access_token
-                .as_ref()
-                .ok_or_else(|| anyhow!("Missing access token"))?
-        };
-        let limit = ids.len();
-        let ids = ids
+        }
+        self.access_token
+            .clone()
+            .ok_or_else(|| anyhow!("Missing access token"))
+    }
+
+    /// Metadata of the games of `ids`, requested and yielded in batches of at most `batch_size`
+    pub fn games<'a>(&'a mut self, ids: &'a [GameId]) -> impl Stream<Item = Result<Metas>> + 'a {
+        try_stream! {
+            info!("Fetching games from IGDB");
+            let access_token = self.access_token().await?;
+            let num_batches = ids.len().div_ceil(self.batch_size);
+            for (i, batch) in ids.chunks(self.batch_size).enumerate() {
+                info!("Fetching batch {} of {num_batches}", i + 1);
+                yield self.games_batch(&access_token, batch).await?;
+            }
+        }
+    }
+
+    async fn games_batch(&self, access_token: &str, ids: &[GameId]) -> Result<Metas> {
+        let ids_str = ids
             .iter()
             .map(ToString::to_string)
             .collect::<Vec<_>>()
             .join(",");
-        let req = self.client.post("https://api.igdb.com/v4/games").bearer_auth(access_token).header("Client-ID", &self.client_id).body(format!("fields age_ratings.category,age_ratings.rating,age_ratings.rating_cover_url,aggregated_rating,aggregated_rating_count,cover.url,first_release_date,franchise.name,game_engines.name,game_engines.logo.url,game_modes.name,genres.name,involved_companies.developer,involved_companies.porting,involved_companies.publisher,involved_companies.supporting,involved_companies.company.country,involved_companies.company.logo.url,involved_companies.company.name,involved_companies.company.start_date,keywords.name,multiplayer_modes.campaigncoop,multiplayer_modes.lancoop,multiplayer_modes.offlinecoop,multiplayer_modes.onlinecoop,name,platforms.category,platforms.name,platforms.generation,platforms.platform_logo.url,player_perspectives.name,release_dates.date,themes.name,rating,rating_count,total_rating,total_rating_count; where id=({ids}); limit {limit};")).build()?;
         let resp = self
-            .request(req)
-            .await?
-            .error_for_status()?
-            .json::<Vec<Meta>>()
+            .query_paged::<Meta>(
+                access_token,
+                "games",
+                &format!("fields {GAME_FIELDS}; where id=({ids_str});"),
+                Some(ids.len()),
+            )
             .await?;
         Ok(Metas(
             resp.into_iter()
@@ -101,4 +164,119 @@ impl IgdbRequestor {
                 .collect(),
         ))
     }
+
+    /// Every game changed on IGDB after `since`, and when it last changed
+    ///
+    /// Any number of games may have changed, so they are requested page by page.
+    pub async fn changed_since(
+        &mut self,
+        since: OffsetDateTime,
+    ) -> Result<Vec<(GameId, OffsetDateTime)>> {
+        info!("Fetching games changed on IGDB since {since}");
+        let access_token = self.access_token().await?;
+        let games = self
+            .query_paged::<UpdatedGame>(
+                &access_token,
+                "games",
+                &format!(
+                    "fields id,updated_at; where updated_at > {}; sort id asc;",
+                    since.unix_timestamp()
+                ),
+                None,
+            )
+            .await?;
+        Ok(games
+            .into_iter()
+            .map(|game| (GameId::Igdb(game.id), game.updated_at))
+            .collect())
+    }
+
+    /// Requests all rows matching `query` from `endpoint`, one page of `batch_size` rows at a time
+    ///
+    /// Paging stops once a page comes back incomplete, or `max_rows` rows have been received.
+    async fn query_paged<T>(
+        &self,
+        access_token: &str,
+        endpoint: &str,
+        query: &str,
+        max_rows: Option<usize>,
+    ) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let mut rows = Vec::new();
+        for page in 1.. {
+            let limit = max_rows.map_or(self.batch_size, |max_rows| {
+                (max_rows - rows.len()).min(self.batch_size)
+            });
+            let req = self
+                .client
+                .post(format!("{}/{endpoint}", self.api_url))
+                .bearer_auth(access_token)
+                .header("Client-ID", &self.client_id)
+                .body(format!("{query} limit {limit}; offset {};", rows.len()))
+                .build()?;
+            let resp = self
+                .request(req)
+                .await?
+                .error_for_status()?
+                .json::<Vec<T>>()
+                .await?;
+            let num_rows = resp.len();
+            rows.extend(resp);
+            info!("Fetched page {page} of {endpoint} with {num_rows} rows");
+            if num_rows < limit || max_rows.is_some_and(|max_rows| rows.len() >= max_rows) {
+                break;
+            }
+        }
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::request::mock::MockServer;
+
+    #[tokio::test]
+    async fn query_pages_until_incomplete() {
+        let server = MockServer::start(|request| {
+            let offset = request
+                .body
+                .rsplit_once("offset ")
+                .and_then(|(_, offset)| offset.trim_end_matches(';').parse::<u32>().ok())
+                .unwrap();
+            // Five rows, served two at a time
+            let rows = (offset..5.min(offset + 2))
+                .map(|id| json!({ "id": id, "updated_at": 1_700_000_000 }))
+                .collect::<Vec<_>>();
+            (200, serde_json::to_vec(&rows).unwrap())
+        });
+        let req = IgdbRequestor::new(server.client(&[]), "id", "secret")
+            .with_batch_size(2)
+            .with_api_url(&server.url(""));
+
+        let rows = req
+            .query_paged::<UpdatedGame>("token", "games", "fields id;", None)
+            .await
+            .unwrap();
+        assert_eq!(
+            rows.iter().map(|row| row.id).collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4]
+        );
+        let requests = server.requests();
+        assert_eq!(
+            requests
+                .iter()
+                .map(|request| (request.path.as_str(), request.body.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("/games", "fields id; limit 2; offset 0;"),
+                ("/games", "fields id; limit 2; offset 2;"),
+                ("/games", "fields id; limit 2; offset 4;"),
+            ]
+        );
+    }
 }
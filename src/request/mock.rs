@@ -0,0 +1,113 @@
+//! HTTP server on a local port answering the requests of tests
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use reqwest::Client;
+
+/// Request received by a [`MockServer`]
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub path: String,
+    pub body: String,
+}
+
+/// Status and body answering a [`Request`]
+pub type Response = (u16, Vec<u8>);
+
+/// Server answering every request on its own thread, so that concurrent requests are answered
+/// concurrently, and recording them in the order they were received
+pub struct MockServer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// Server answering each request with the response returned by `respond`
+    pub fn start<F>(respond: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let respond = Arc::new(respond);
+        let server_requests = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let (stream, requests, respond) = (
+                    stream.unwrap(),
+                    Arc::clone(&server_requests),
+                    Arc::clone(&respond),
+                );
+                thread::spawn(move || answer(stream, &requests, &*respond));
+            }
+        });
+        Self { addr, requests }
+    }
+
+    /// URL of `path` on this server
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{path}", self.addr)
+    }
+
+    /// Client sending its requests to `hosts` to this server instead, and never to a proxy
+    pub fn client(&self, hosts: &[&str]) -> Client {
+        hosts
+            .iter()
+            .fold(Client::builder().no_proxy(), |builder, host| {
+                builder.resolve(host, self.addr)
+            })
+            .build()
+            .unwrap()
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn answer<F>(mut stream: TcpStream, requests: &Mutex<Vec<Request>>, respond: &F)
+where
+    F: Fn(&Request) -> Response,
+{
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    let path = line.split(' ').nth(1).unwrap_or_default().to_string();
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap();
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    let request = Request {
+        path,
+        body: String::from_utf8(body).unwrap(),
+    };
+    requests.lock().unwrap().push(request.clone());
+
+    let (status, body) = respond(&request);
+    write!(
+        stream,
+        "HTTP/1.1 {status} Mock\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )
+    .unwrap();
+    stream.write_all(&body).unwrap();
+}
@@ -1,4 +1,6 @@
 //! API Requestors
 
 pub mod igdb;
+#[cfg(test)]
+mod mock;
 pub mod resource;
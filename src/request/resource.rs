@@ -1,22 +1,101 @@
-use std::{fmt, fs, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, fs,
+    io::Cursor,
+    iter,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
-use anyhow::{Result, anyhow};
-use reqwest::Client;
-use tokio::sync::Semaphore;
+use anyhow::{Context, Error, Result, anyhow};
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tokio::{
+    sync::{Mutex, Semaphore, mpsc::UnboundedSender},
+    task::JoinSet,
+    time::{self, Instant},
+};
 use tracing::info;
+use url::Url;
+
+use crate::{
+    data::{Data, LOGO_FILENAME, Meta, Metas},
+    plot::{Color, ImageCache},
+};
 
-const MAX_CONNECTIONS: usize = 8;
 const RESOURCE_PATH: &str = "res";
+const IGDB_IMAGE_HOST: &str = "images.igdb.com";
+const PRELOAD_REPORT_INTERVAL: usize = 10;
 
 #[derive(Debug, Clone)]
 pub struct ResourceRequestor {
     client: Client,
     sem: Arc<Semaphore>,
+    skip_fetch: bool,
+    min_interval: Option<Duration>,
+    last_start: Arc<Mutex<Option<Instant>>>,
+    events: Option<UnboundedSender<DownloadEvent>>,
+    images: ImageCache,
+    /// Root of the cache
+    dir: PathBuf,
 }
 
-#[derive(Debug)]
+/// Progress of a download
+#[derive(Debug, Clone, Copy)]
+pub enum DownloadEvent {
+    /// Waiting for a download slot
+    Queued,
+    Started,
+    Finished,
+    Failed,
+}
+
+/// Number and total size of cached files
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CacheUsage {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Results of getting resources, keyed by size and URL
+pub type Resources = HashMap<(ImageSize, String), Result<Vec<u8>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ImageSize {
     Hd,
+    CoverBig,
+    CoverSmall,
+}
+
+impl ImageSize {
+    /// Sizes requested by the visualizations, excluding fallbacks
+    pub const ALL: [Self; 2] = [Self::Hd, Self::CoverSmall];
+
+    /// Size requested instead when an image doesn't exist at this size, as for the covers of
+    /// some older games
+    const fn fallback(self) -> Option<Self> {
+        match self {
+            Self::Hd => Some(Self::CoverBig),
+            Self::CoverBig | Self::CoverSmall => None,
+        }
+    }
+
+    /// This size followed by its fallbacks
+    fn with_fallbacks(self) -> impl Iterator<Item = Self> {
+        iter::successors(Some(self), |size| size.fallback())
+    }
+
+    /// Width and height of images of this size, as served by IGDB
+    const fn dimensions(self) -> (u32, u32) {
+        match self {
+            Self::Hd => (1280, 720),
+            Self::CoverBig => (264, 374),
+            Self::CoverSmall => (90, 128),
+        }
+    }
 }
 
 impl fmt::Display for ImageSize {
@@ -26,51 +105,188 @@ impl fmt::Display for ImageSize {
             "t_{}",
             match self {
                 Self::Hd => "720p",
+                Self::CoverBig => "cover_big",
+                Self::CoverSmall => "cover_small",
             }
         )
     }
 }
 
 impl ResourceRequestor {
+    /// Requestor downloading at most `jobs` resources at once
     #[must_use]
-    pub fn new(client: Client) -> Self {
+    pub fn new(client: Client, skip_fetch: bool, jobs: usize) -> Self {
         Self {
             client,
-            sem: Arc::new(Semaphore::new(MAX_CONNECTIONS)),
+            sem: Arc::new(Semaphore::new(jobs.max(1))),
+            skip_fetch,
+            min_interval: None,
+            last_start: Arc::new(Mutex::new(None)),
+            events: None,
+            images: ImageCache::default(),
+            dir: PathBuf::from(RESOURCE_PATH),
         }
     }
 
-    pub async fn get(&self, size: ImageSize, url: &str) -> Result<Vec<u8>> {
-        let mut url_parts = url.split('/').collect::<Vec<_>>();
-        let is_igdb = url_parts[url_parts.len() - 2] == "t_thumb";
-        let size = size.to_string();
+    /// Caches resources in `dir` instead of the resource directory
+    #[cfg(test)]
+    #[must_use]
+    pub fn with_dir(self, dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            ..self
+        }
+    }
 
-        let filename = if is_igdb {
-            let ext = "png";
-            let mut filename = url_parts[url_parts.len() - 1]
-                .split('.')
-                .collect::<Vec<_>>();
-            if let Some(filename_ext) = filename.last_mut() {
-                *filename_ext = ext;
-            }
-            filename.join(".")
+    /// Sends the progress of every download to `events`
+    #[must_use]
+    pub fn with_events(self, events: UnboundedSender<DownloadEvent>) -> Self {
+        Self {
+            events: Some(events),
+            ..self
+        }
+    }
+
+    /// Waits at least `interval` between the starts of consecutive downloads
+    #[must_use]
+    pub fn with_min_interval(self, interval: Duration) -> Self {
+        Self {
+            min_interval: Some(interval),
+            ..self
+        }
+    }
+
+    /// Keeps the images decoded from resources, shared between clones of this requestor
+    #[must_use]
+    pub fn with_image_cache(self) -> Self {
+        Self {
+            images: ImageCache::enabled(),
+            ..self
+        }
+    }
+
+    /// Decoder of the images of resources, which only caches them `with_image_cache`
+    #[must_use]
+    pub const fn images(&self) -> &ImageCache {
+        &self.images
+    }
+
+    /// Absolute URL to request, and cache paths of a resource
+    ///
+    /// Protocol-relative URLs are resolved against HTTPS, and IGDB image URLs are rewritten to
+    /// request `size`. Resources are cached under a SHA-256 hash of their URL, excluding the scheme
+    /// and IGDB size, so that distinct URLs sharing a basename don't collide.
+    fn locate(&self, size: ImageSize, url: &str) -> Result<Location> {
+        let raw_url = url;
+        let mut url = if url.starts_with("//") {
+            Url::parse(&format!("https:{url}"))
         } else {
-            url_parts[url_parts.len() - 1].to_string()
+            Url::parse(url)
+        }
+        .with_context(|| format!("Invalid resource URL \"{raw_url}\""))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(anyhow!(
+                "Unsupported scheme \"{}\" in resource URL \"{raw_url}\"",
+                url.scheme()
+            ));
+        }
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("Resource URL \"{raw_url}\" lacks a host"))?
+            .to_string();
+        let mut segments = url
+            .path_segments()
+            .map(|segments| segments.map(str::to_string).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let basename = segments
+            .pop()
+            .filter(|basename| !basename.is_empty())
+            .ok_or_else(|| anyhow!("Resource URL \"{raw_url}\" lacks a filename"))?;
+        let is_igdb = host == IGDB_IMAGE_HOST && !segments.is_empty();
+        let size = size.to_string();
+
+        let (stem, ext) = match basename.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem, Some(ext)),
+            _ => (basename.as_str(), None),
         };
-        let idx = url_parts.len() - 1;
-        url_parts[idx] = filename.as_str();
+        let ext = if is_igdb { Some("png") } else { ext };
+        let remote_filename = ext.map_or_else(|| stem.to_string(), |ext| format!("{stem}.{ext}"));
 
         if is_igdb {
-            let idx = url_parts.len() - 2;
-            url_parts[idx] = size.as_str();
+            segments.pop();
         }
+        let key = format!(
+            "{host}/{}/{remote_filename}{}",
+            segments.join("/"),
+            url.query()
+                .map(|query| format!("?{query}"))
+                .unwrap_or_default()
+        );
+        let hash = Sha256::digest(key);
 
-        let mut path = PathBuf::new();
-        path.push(RESOURCE_PATH);
         if is_igdb {
-            path.push(&size);
+            segments.push(size.clone());
         }
-        path.push(&filename);
+        segments.push(remote_filename.clone());
+        url.set_path(&segments.join("/"));
+        url.set_fragment(None);
+
+        let mut dir = self.dir.clone();
+        if is_igdb {
+            dir.push(&size);
+        }
+        let with_ext = |name: String| match ext {
+            Some(ext) => format!("{name}.{}", sanitize(ext)),
+            None => name,
+        };
+        // Only IGDB images were once cached under their bare basename, in a directory of their
+        // size, whereas other basenames could collide with files of the cache root like the logo
+        let legacy_path = is_igdb.then(|| dir.join(&remote_filename));
+
+        Ok(Location {
+            url: url.into(),
+            path: dir.join(with_ext(format!("{hash:x}"))),
+            legacy_path,
+        })
+    }
+
+    /// Gets a resource from the cache, or downloads it
+    ///
+    /// Images which don't exist at `size` are obtained at its fallback sizes instead, each being
+    /// looked up in the cache before being downloaded. Images which don't exist at any of them
+    /// fail, which [`Self::is_missing`] tells apart from other failures.
+    pub async fn get(&self, size: ImageSize, url: &str) -> Result<Vec<u8>> {
+        let mut sizes = size.with_fallbacks().peekable();
+        while let Some(fallback) = sizes.next() {
+            let res = self.get_exact(fallback, url).await;
+            match res {
+                Err(err) if sizes.peek().is_some() && (self.skip_fetch || is_not_found(&err)) => {
+                    info!(
+                        "Image {url} is unavailable at {fallback}, trying a smaller size: {err:#}"
+                    );
+                }
+                Err(err) if is_not_found(&err) => {
+                    return Err(err.context(format!("Image {url} is unavailable at every size")));
+                }
+                res => return res,
+            }
+        }
+        unreachable!("Every size is followed by at least itself")
+    }
+
+    /// Whether `get` of an image at `size` failed with `err` because the image exists neither at
+    /// `size` nor at any of its fallbacks, as for the covers of some older games, rather than
+    /// only at `size` itself
+    #[must_use]
+    pub fn is_missing(size: ImageSize, err: &Error) -> bool {
+        size.fallback().is_some() && is_not_found(err)
+    }
+
+    /// Gets a resource at exactly `size`
+    async fn get_exact(&self, size: ImageSize, url: &str) -> Result<Vec<u8>> {
+        let location = self.locate(size, url)?;
+        let path = &location.path;
 
         info!("Obtaining file {}", path.to_string_lossy());
 
@@ -78,26 +294,752 @@ impl ResourceRequestor {
             return Ok(fs::read(path)?);
         }
 
-        let req_url = format!("https:{}", url_parts.join("/"));
-        let request = self.client.get(&req_url);
+        if let Some(legacy_path) = location.legacy_path.as_ref().filter(|path| path.exists()) {
+            info!(
+                "Migrating file {} to {}",
+                legacy_path.to_string_lossy(),
+                path.to_string_lossy()
+            );
+            fs::rename(legacy_path, path)?;
+            location.write_sidecar(&location.sidecar_path())?;
+            return Ok(fs::read(path)?);
+        }
 
-        let res = {
-            let _permit = self.sem.acquire().await?;
-            info!("Downloading file at {req_url}");
-            request.send().await?
+        let marker_path = location.marker_path();
+        if self.skip_fetch {
+            if marker_path.exists() {
+                return Err(KnownMissing(location.url).into());
+            }
+            return Err(anyhow!(
+                "File {} is not cached and fetching is disabled",
+                path.to_string_lossy()
+            ));
         }
-        .error_for_status()?
-        .bytes()
-        .await?
-        .to_vec();
-        info!("Downloaded file at {req_url}");
+
+        self.report(DownloadEvent::Queued);
+        let res = self.download(&location.url).await;
+        self.report(if res.is_ok() {
+            DownloadEvent::Finished
+        } else {
+            DownloadEvent::Failed
+        });
 
         fs::create_dir_all(
             path.parent()
                 .ok_or_else(|| anyhow!("Filename lacks parent"))?,
         )?;
+        let res = match res {
+            Ok(res) => res,
+            Err(err) => {
+                // Recorded so that runs without fetching know the resource is missing, rather
+                // than only uncached
+                if is_not_found(&err) {
+                    location.write_sidecar(&marker_path)?;
+                }
+                return Err(err);
+            }
+        };
         fs::write(path, &res)?;
+        location.write_sidecar(&location.sidecar_path())?;
+        if marker_path.exists() {
+            fs::remove_file(marker_path)?;
+        }
+
+        Ok(res)
+    }
+
+    /// Whether a resource can be obtained without downloading it
+    pub fn is_cached(&self, size: ImageSize, url: &str) -> Result<bool> {
+        for size in size.with_fallbacks() {
+            let location = self.locate(size, url)?;
+            if location.path.exists() || location.legacy_path.is_some_and(|path| path.exists()) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Gets all distinct `resources` concurrently
+    pub async fn get_all<I>(&self, resources: I) -> Result<Resources>
+    where
+        I: IntoIterator<Item = (ImageSize, String)>,
+    {
+        let mut tasks = JoinSet::new();
+        let mut requested = HashSet::new();
+        for (size, url) in resources {
+            if !requested.insert((size, url.clone())) {
+                continue;
+            }
+            let res = self.clone();
+            tasks.spawn(async move {
+                let resource = res.get(size, &url).await;
+                ((size, url), resource)
+            });
+        }
+        let mut resources = Resources::new();
+        while let Some(task) = tasks.join_next().await {
+            let (key, resource) = task?;
+            resources.insert(key, resource);
+        }
+        Ok(resources)
+    }
+
+    /// Caches the covers and the engine, company and platform logos of every game in `data` at
+    /// `size`, failing if any of them can't be obtained
+    pub async fn preload_all(&self, data: &Data, size: ImageSize) -> Result<()> {
+        let urls = data
+            .metas
+            .0
+            .values()
+            .flat_map(Meta::image_urls)
+            .map(str::to_string)
+            .collect::<HashSet<_>>();
+        let num_urls = urls.len();
+        let mut tasks = JoinSet::new();
+        for url in urls {
+            let res = self.clone();
+            tasks.spawn(async move { res.get(size, &url).await.map(|_| ()) });
+        }
+        let (mut num_completed, mut num_failed) = (0, 0);
+        while let Some(task) = tasks.join_next().await {
+            if task?.is_err() {
+                num_failed += 1;
+            }
+            num_completed += 1;
+            if num_completed % PRELOAD_REPORT_INTERVAL == 0 || num_completed == num_urls {
+                info!("Preloaded {num_completed} of {num_urls} {size} images, {num_failed} failed");
+            }
+        }
+        if num_failed > 0 {
+            return Err(anyhow!("Could not preload {num_failed} {size} images"));
+        }
+        Ok(())
+    }
+
+    async fn download(&self, url: &str) -> Result<Vec<u8>> {
+        let request = self.client.get(url);
+
+        let res = {
+            let _permit = self.sem.acquire().await?;
+            if let Some(min_interval) = self.min_interval {
+                let mut last_start = self.last_start.lock().await;
+                if let Some(last_start) = *last_start {
+                    time::sleep_until(last_start + min_interval).await;
+                }
+                *last_start = Some(Instant::now());
+            }
+            self.report(DownloadEvent::Started);
+            info!("Downloading file at {url}");
+            request
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?
+                .to_vec()
+        };
+        info!("Downloaded file at {url}");
 
         Ok(res)
     }
+
+    fn report(&self, event: DownloadEvent) {
+        if let Some(events) = &self.events {
+            // Progress is only informational, so a closed receiver isn't an error
+            let _ = events.send(event);
+        }
+    }
+
+    /// Usage of the cache per directory, relative to the cache root
+    pub fn cache_usage(&self) -> Result<BTreeMap<PathBuf, CacheUsage>> {
+        let mut usage = BTreeMap::<PathBuf, CacheUsage>::new();
+        for path in self.cached_files()? {
+            let dir = path
+                .parent()
+                .and_then(|dir| dir.strip_prefix(&self.dir).ok())
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            let entry = usage.entry(dir).or_default();
+            entry.files += 1;
+            entry.bytes += fs::metadata(&path)?.len();
+        }
+        Ok(usage)
+    }
+
+    /// Deletes cached files not referenced by any image in `metas`, or last modified longer than
+    /// `max_age` ago, returning the number of deleted files
+    ///
+    /// If `dry_run` is set, the files are only logged.
+    pub fn prune_cache(
+        &self,
+        metas: &Metas,
+        max_age: Option<Duration>,
+        dry_run: bool,
+    ) -> Result<usize> {
+        let referenced = metas
+            .0
+            .values()
+            .flat_map(|meta| meta.image_urls().flat_map(|url| self.cache_paths(url)))
+            .collect::<HashSet<_>>();
+
+        let mut num_deleted = 0;
+        for path in self.cached_files()? {
+            if path == self.logo_path() {
+                continue;
+            }
+            let expired = match max_age {
+                Some(max_age) => {
+                    fs::metadata(&path)?
+                        .modified()?
+                        .elapsed()
+                        .unwrap_or_default()
+                        > max_age
+                }
+                None => false,
+            };
+            if !expired && referenced.contains(&path) {
+                continue;
+            }
+            if dry_run {
+                info!("Would delete file {}", path.to_string_lossy());
+            } else {
+                info!("Deleting file {}", path.to_string_lossy());
+                fs::remove_file(&path)?;
+            }
+            num_deleted += 1;
+        }
+
+        Ok(num_deleted)
+    }
+
+    /// All cache paths a resource may be stored at, across image sizes and cache layouts
+    fn cache_paths(&self, url: &str) -> Vec<PathBuf> {
+        ImageSize::ALL
+            .into_iter()
+            .flat_map(ImageSize::with_fallbacks)
+            .filter_map(|size| self.locate(size, url).ok())
+            .flat_map(|location| {
+                let sidecar_path = location.sidecar_path();
+                let marker_path = location.marker_path();
+                location
+                    .legacy_path
+                    .into_iter()
+                    .chain([location.path, sidecar_path, marker_path])
+            })
+            .collect()
+    }
+
+    /// Path of the logo, which is kept in the cache but never deleted from it
+    fn logo_path(&self) -> PathBuf {
+        self.dir
+            .join(Path::new(LOGO_FILENAME).file_name().unwrap_or_default())
+    }
+
+    /// All files in the cache, or none if it doesn't exist
+    fn cached_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        if fs::exists(&self.dir)? {
+            collect_files(&self.dir, &mut files)?;
+        }
+        Ok(files)
+    }
+}
+
+/// Where a resource is requested from and cached
+#[derive(Debug)]
+struct Location {
+    url: String,
+    path: PathBuf,
+    /// Cache path used by previous versions, migrated to `path` when found
+    legacy_path: Option<PathBuf>,
+}
+
+impl Location {
+    /// Path of the file recording the URL of the cached resource
+    fn sidecar_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".json");
+        path.into()
+    }
+
+    /// Path of the file recording that the server had no such resource when last requested
+    fn marker_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".missing");
+        path.into()
+    }
+
+    /// Writes the URL of the resource to `path`, its sidecar or marker
+    fn write_sidecar(&self, path: &Path) -> Result<()> {
+        fs::write(
+            path,
+            serde_json::to_string_pretty(&Sidecar { url: &self.url })?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Error of a resource which the server didn't have when last requested, while fetching is
+/// disabled
+#[derive(Debug)]
+struct KnownMissing(String);
+
+impl fmt::Display for KnownMissing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} was not found when last requested and fetching is disabled",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for KnownMissing {}
+
+#[derive(Serialize)]
+struct Sidecar<'a> {
+    url: &'a str,
+}
+
+/// Replaces characters which are unsafe in filenames
+fn sanitize(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if name.is_empty() {
+        "resource".to_string()
+    } else {
+        name
+    }
+}
+
+/// PNG of `size` filled with `Color::BG_SECONDARY`, standing in for a cover which
+/// [`ResourceRequestor::is_missing`]
+pub fn placeholder(size: ImageSize) -> Result<Vec<u8>> {
+    let (width, height) = size.dimensions();
+    let mut png = Vec::new();
+    DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+        width,
+        height,
+        Color::BG_SECONDARY.into(),
+    ))
+    .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Whether `err` is a download failing because the server has no such file, or had none when
+/// last requested
+fn is_not_found(err: &Error) -> bool {
+    err.downcast_ref::<KnownMissing>().is_some()
+        || err
+            .downcast_ref::<reqwest::Error>()
+            .and_then(reqwest::Error::status)
+            == Some(StatusCode::NOT_FOUND)
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, process,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+    use crate::{
+        data::{GameId, UrlField, fixture},
+        request::mock::MockServer,
+    };
+
+    const COVER_URL: &str = "http://images.igdb.com/igdb/image/upload/t_thumb/co1abc.jpg";
+
+    /// Directory removed with its contents on drop
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = env::temp_dir().join(format!("tbp-viz-{}-{name}", process::id()));
+            let _ = fs::remove_dir_all(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn requestor(server: &MockServer, dir: &TempDir, skip_fetch: bool) -> ResourceRequestor {
+        ResourceRequestor::new(server.client(&[IGDB_IMAGE_HOST]), skip_fetch, 1).with_dir(&dir.0)
+    }
+
+    fn offline(dir: &Path) -> ResourceRequestor {
+        ResourceRequestor::new(Client::new(), true, 1).with_dir(dir)
+    }
+
+    #[test]
+    fn locates_igdb_images_by_size() {
+        let dir = Path::new("cache");
+        let location = offline(dir).locate(ImageSize::Hd, COVER_URL).unwrap();
+        assert_eq!(
+            location.url,
+            "http://images.igdb.com/igdb/image/upload/t_720p/co1abc.png"
+        );
+        let hash = Sha256::digest("images.igdb.com/igdb/image/upload/co1abc.png");
+        assert_eq!(
+            location.path,
+            dir.join("t_720p").join(format!("{hash:x}.png"))
+        );
+        assert_eq!(
+            location.legacy_path,
+            Some(dir.join("t_720p").join("co1abc.png"))
+        );
+
+        let small = offline(dir)
+            .locate(ImageSize::CoverSmall, COVER_URL)
+            .unwrap();
+        assert_eq!(small.path.file_name(), location.path.file_name());
+        assert_eq!(
+            small.path.parent(),
+            Some(dir.join("t_cover_small").as_path())
+        );
+    }
+
+    #[test]
+    fn locates_other_resources_by_url() {
+        let dir = Path::new("cache");
+        let requestor = offline(dir);
+        let location = requestor
+            .locate(ImageSize::Hd, "//example.com/images/logo.png?v=2#top")
+            .unwrap();
+        assert_eq!(location.url, "https://example.com/images/logo.png?v=2");
+        let hash = Sha256::digest("example.com/images/logo.png?v=2");
+        assert_eq!(location.path, dir.join(format!("{hash:x}.png")));
+        assert_eq!(location.legacy_path, None);
+
+        let path = |url| requestor.locate(ImageSize::Hd, url).unwrap().path;
+        assert_eq!(
+            path("http://example.com/images/logo.png?v=2"),
+            location.path
+        );
+        assert_eq!(
+            path("https://example.com/images/logo.png?v=2"),
+            location.path
+        );
+    }
+
+    #[test]
+    fn resolves_each_url_shape() {
+        let dir = Path::new("cache");
+        let requestor = offline(dir);
+        // URL, requested URL at `ImageSize::CoverSmall`, and cache directory below `dir`
+        let resolved = [
+            (
+                "//images.igdb.com/igdb/image/upload/t_thumb/co1abc.jpg",
+                "https://images.igdb.com/igdb/image/upload/t_cover_small/co1abc.png",
+                "t_cover_small",
+            ),
+            (
+                "https://images.igdb.com/igdb/image/upload/t_thumb/co1abc.jpg",
+                "https://images.igdb.com/igdb/image/upload/t_cover_small/co1abc.png",
+                "t_cover_small",
+            ),
+            (
+                "http://images.igdb.com/igdb/image/upload/t_720p/co1abc",
+                "http://images.igdb.com/igdb/image/upload/t_cover_small/co1abc.png",
+                "t_cover_small",
+            ),
+            // Not the shape of an IGDB image, so requested as is
+            (
+                "https://images.igdb.com/logo.jpg",
+                "https://images.igdb.com/logo.jpg",
+                "",
+            ),
+            (
+                "//example.com/images/t_thumb/logo.jpg",
+                "https://example.com/images/t_thumb/logo.jpg",
+                "",
+            ),
+            (
+                "https://example.com/logo?size=2#top",
+                "https://example.com/logo?size=2",
+                "",
+            ),
+        ];
+        for (url, expected_url, expected_dir) in resolved {
+            let location = requestor.locate(ImageSize::CoverSmall, url).unwrap();
+            assert_eq!(location.url, expected_url, "{url}");
+            assert_eq!(
+                location.path.parent(),
+                Some(dir.join(expected_dir).as_path()),
+                "{url}"
+            );
+        }
+
+        let rejected = [
+            ("co1abc", "Invalid resource URL"),
+            (
+                "/igdb/image/upload/t_thumb/co1abc.jpg",
+                "Invalid resource URL",
+            ),
+            ("ftp://example.com/logo.png", "Unsupported scheme \"ftp\""),
+            ("file:///logo.png", "Unsupported scheme \"file\""),
+            ("https://example.com", "lacks a filename"),
+            ("https://example.com/images/", "lacks a filename"),
+        ];
+        for (url, expected_err) in rejected {
+            let err = requestor.locate(ImageSize::Hd, url).unwrap_err();
+            assert!(err.to_string().contains(expected_err), "{url}: {err}");
+        }
+    }
+
+    #[test]
+    fn distinct_urls_sharing_a_basename_dont_collide() {
+        let requestor = offline(Path::new("cache"));
+        let paths = [
+            "https://example.com/a/logo.png",
+            "https://example.com/b/logo.png",
+            "https://example.org/a/logo.png",
+            "https://example.com/a/logo.png?v=2",
+            "https://images.igdb.com/igdb/image/upload/t_thumb/logo.png",
+            "https://images.igdb.com/other/upload/t_thumb/logo.png",
+        ]
+        .map(|url| requestor.locate(ImageSize::Hd, url).unwrap().path);
+        let distinct = paths.iter().collect::<HashSet<_>>();
+        assert_eq!(distinct.len(), paths.len(), "{paths:?}");
+    }
+
+    #[test]
+    fn derives_cache_filenames() {
+        let requestor = offline(Path::new("cache"));
+        let filename = |url| {
+            requestor
+                .locate(ImageSize::Hd, url)
+                .unwrap()
+                .path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        };
+        let hash = |key: &str| format!("{:x}", Sha256::digest(key));
+
+        // Queries are part of the key, but never of the extension
+        assert_eq!(
+            filename("https://example.com/img/logo.png?v=2.exe"),
+            format!("{}.png", hash("example.com/img/logo.png?v=2.exe"))
+        );
+        assert_ne!(
+            filename("https://example.com/logo.png?v=1"),
+            filename("https://example.com/logo.png?v=2")
+        );
+        assert_ne!(
+            filename("https://example.com/logo.png"),
+            filename("https://example.com/logo.png?v=1")
+        );
+        // Extensions are sanitized, and files without one are cached under the bare hash
+        assert_eq!(
+            filename("https://example.com/img/logo.p%20g"),
+            format!("{}.p_20g", hash("example.com/img/logo.p%20g"))
+        );
+        assert_eq!(
+            filename("https://example.com/img/logo"),
+            hash("example.com/img/logo")
+        );
+        assert_eq!(
+            filename("https://example.com/img/.hidden"),
+            hash("example.com/img/.hidden")
+        );
+        // Duplicate basenames keep their extension under distinct hashes
+        let (a, b) = (
+            filename("https://example.com/a/logo.png"),
+            filename("https://example.com/b/logo.png"),
+        );
+        assert_ne!(a, b);
+        for filename in [a, b] {
+            assert_eq!(Path::new(&filename).extension(), Some("png".as_ref()));
+        }
+    }
+
+    #[tokio::test]
+    async fn migrates_legacy_igdb_basenames() {
+        let dir = TempDir::new("legacy-covers");
+        let legacy_dir = dir.0.join("t_720p");
+        fs::create_dir_all(&legacy_dir).unwrap();
+        fs::write(legacy_dir.join("co1abc.png"), b"cover").unwrap();
+
+        let requestor = offline(&dir.0);
+        assert!(requestor.is_cached(ImageSize::Hd, COVER_URL).unwrap());
+        assert_eq!(
+            requestor.get(ImageSize::Hd, COVER_URL).await.unwrap(),
+            b"cover"
+        );
+        let location = requestor.locate(ImageSize::Hd, COVER_URL).unwrap();
+        assert!(!legacy_dir.join("co1abc.png").exists());
+        assert!(location.path.exists());
+        assert!(location.sidecar_path().exists());
+    }
+
+    #[tokio::test]
+    async fn ignores_legacy_basenames_of_other_resources() {
+        let dir = TempDir::new("legacy-others");
+        fs::create_dir_all(&dir.0).unwrap();
+        fs::write(dir.0.join("logo.png"), b"logo").unwrap();
+
+        let requestor = offline(&dir.0);
+        let url = "https://example.com/logo.png";
+        assert!(!requestor.is_cached(ImageSize::Hd, url).unwrap());
+        assert!(requestor.get(ImageSize::Hd, url).await.is_err());
+        assert!(dir.0.join("logo.png").exists());
+    }
+
+    #[test]
+    fn prunes_unreferenced_files() {
+        let dir = TempDir::new("prune");
+        let requestor = offline(&dir.0);
+        let mut metas = fixture::data().metas;
+        let referenced = GameId::Igdb(101);
+        metas.0.get_mut(&referenced).unwrap().cover = Some(UrlField {
+            url: COVER_URL.to_string(),
+        });
+        let cover = COVER_URL;
+        let kept = [
+            requestor.logo_path(),
+            requestor.locate(ImageSize::Hd, cover).unwrap().path,
+            requestor.locate(ImageSize::CoverSmall, cover).unwrap().path,
+        ];
+        let unreferenced = [
+            dir.0.join("t_720p").join("unknown.png"),
+            requestor
+                .locate(ImageSize::Hd, "https://example.com/unknown.png")
+                .unwrap()
+                .path,
+        ];
+        for path in kept.iter().chain(&unreferenced) {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, b"image").unwrap();
+        }
+
+        assert_eq!(requestor.prune_cache(&metas, None, true).unwrap(), 2);
+        assert!(unreferenced.iter().all(|path| path.exists()));
+        assert_eq!(requestor.prune_cache(&metas, None, false).unwrap(), 2);
+        assert!(kept.iter().all(|path| path.exists()));
+        assert!(!unreferenced.iter().any(|path| path.exists()));
+
+        metas.0.remove(&referenced);
+        assert_eq!(requestor.prune_cache(&metas, None, false).unwrap(), 2);
+        assert!(requestor.logo_path().exists());
+    }
+
+    #[tokio::test]
+    async fn limits_concurrent_downloads_to_jobs() {
+        const JOBS: usize = 2;
+        let (in_flight, max_in_flight) =
+            (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let server = MockServer::start({
+            let (in_flight, max_in_flight) = (Arc::clone(&in_flight), Arc::clone(&max_in_flight));
+            move |_| {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(50));
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                (200, b"image".to_vec())
+            }
+        });
+        let dir = TempDir::new("concurrency");
+        let requestor = ResourceRequestor::new(server.client(&[]), false, JOBS).with_dir(&dir.0);
+
+        let resources = (0..8)
+            .map(|i| (ImageSize::Hd, server.url(&format!("/images/{i}.png"))))
+            .collect::<Vec<_>>();
+        let results = requestor.get_all(resources).await.unwrap();
+        assert!(results.values().all(Result::is_ok));
+        assert_eq!(server.requests().len(), 8);
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), JOBS);
+    }
+
+    #[tokio::test]
+    async fn rerun_without_fetching_knows_missing_covers() {
+        let server = MockServer::start(|_| (404, Vec::new()));
+        let dir = TempDir::new("missing-covers");
+
+        let err = requestor(&server, &dir, false)
+            .get(ImageSize::Hd, COVER_URL)
+            .await
+            .unwrap_err();
+        assert!(
+            ResourceRequestor::is_missing(ImageSize::Hd, &err),
+            "{err:#}"
+        );
+        let paths = server
+            .requests()
+            .into_iter()
+            .map(|request| request.path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "/igdb/image/upload/t_720p/co1abc.png",
+                "/igdb/image/upload/t_cover_big/co1abc.png"
+            ]
+        );
+
+        let offline = requestor(&server, &dir, true);
+        let err = offline.get(ImageSize::Hd, COVER_URL).await.unwrap_err();
+        assert!(
+            ResourceRequestor::is_missing(ImageSize::Hd, &err),
+            "{err:#}"
+        );
+        // Never requested, so only uncached rather than missing
+        let err = offline
+            .get(ImageSize::CoverSmall, COVER_URL)
+            .await
+            .unwrap_err();
+        assert!(!is_not_found(&err), "{err:#}");
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn rerun_without_fetching_uses_fallbacks() {
+        let server = MockServer::start(|request| {
+            if request.path.contains("/t_cover_big/") {
+                (200, b"cover".to_vec())
+            } else {
+                (404, Vec::new())
+            }
+        });
+        let dir = TempDir::new("fallback-covers");
+
+        let online = requestor(&server, &dir, false);
+        assert_eq!(
+            online.get(ImageSize::Hd, COVER_URL).await.unwrap(),
+            b"cover"
+        );
+        let offline = requestor(&server, &dir, true);
+        assert_eq!(
+            offline.get(ImageSize::Hd, COVER_URL).await.unwrap(),
+            b"cover"
+        );
+        assert!(offline.is_cached(ImageSize::Hd, COVER_URL).unwrap());
+        assert_eq!(server.requests().len(), 2);
+    }
 }